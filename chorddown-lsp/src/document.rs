@@ -0,0 +1,376 @@
+use std::collections::HashSet;
+
+use libchordr::models::chord::fmt::Formatting;
+use libchordr::models::chord::{Chord, Chords, NoteDisplay};
+use libchordr::models::structure::{ReferenceResolver, Section, SectionType};
+use libchordr::modification::transposition::TransposableTrait;
+use libchordr::prelude::*;
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionTextEdit, Diagnostic as LspDiagnostic,
+    DiagnosticSeverity, DocumentSymbol, Hover, HoverContents, MarkupContent, MarkupKind,
+    NumberOrString, Position as LspPosition, Range, SymbolKind, TextEdit,
+};
+
+use crate::position::{from_lsp_position, to_lsp_position, to_lsp_range};
+
+/// Semitones the chord under the cursor is transposed by in the hover preview
+const TRANSPOSE_PREVIEW: [isize; 4] = [-2, -1, 1, 2];
+
+/// Open Chorddown document with the results of the tokenizer and parser
+pub(crate) struct Document {
+    text: String,
+    tokens: Vec<SpannedToken>,
+    /// Sections of the last source that could be parsed
+    sections: Vec<Section>,
+    b_notation: BNotation,
+    semitone_notation: SemitoneNotation,
+}
+
+impl Document {
+    pub fn new(text: String) -> Self {
+        let mut document = Self {
+            text: String::new(),
+            tokens: vec![],
+            sections: vec![],
+            b_notation: BNotation::default(),
+            semitone_notation: SemitoneNotation::default(),
+        };
+        document.update(text);
+
+        document
+    }
+
+    /// Replace the source of the document
+    ///
+    /// If the new source can not be parsed (e.g. while a chord is typed) the previous Sections
+    /// are kept
+    pub fn update(&mut self, text: String) {
+        let (tokens, _warnings) = build_tokenizer()
+            .tokenize_with_spans(text.as_bytes())
+            .unwrap_or_default();
+
+        self.b_notation = tokens
+            .iter()
+            .find_map(|t| match &t.token {
+                Token::Meta(Meta::BNotation(b_notation)) => Some(*b_notation),
+                _ => None,
+            })
+            .unwrap_or_default();
+        self.semitone_notation = tokens
+            .iter()
+            .find_map(|t| match &t.token {
+                Token::Meta(Meta::Key(key)) => Chord::try_from(key, self.b_notation).ok(),
+                _ => None,
+            })
+            .map(|key| SemitoneNotation::for_key(&key))
+            .unwrap_or_default();
+
+        match Parser::new().parse_with_spans(tokens.clone()) {
+            Ok(result) => self.sections = result.node_as_ref().get_sections(),
+            Err(error) => log::debug!("Could not parse the document: {}", error),
+        }
+        self.tokens = tokens;
+        self.text = text;
+    }
+
+    /// Return the problems found by the [`Validator`]
+    pub fn diagnostics(&self) -> Vec<LspDiagnostic> {
+        Validator::new()
+            .validate_source(&self.text)
+            .into_iter()
+            .map(|diagnostic| {
+                let start = Position::new(diagnostic.line(), diagnostic.column());
+                let end = Position::new(diagnostic.line(), diagnostic.column() + 1);
+                let severity = match diagnostic.severity() {
+                    Severity::Error => DiagnosticSeverity::ERROR,
+                    Severity::Warning => DiagnosticSeverity::WARNING,
+                };
+                let code = serde_json::to_value(diagnostic.kind())
+                    .ok()
+                    .and_then(|v| v.as_str().map(|s| NumberOrString::String(s.to_owned())));
+
+                LspDiagnostic {
+                    range: to_lsp_range(&self.text, Span::new(start, end)),
+                    severity: Some(severity),
+                    code,
+                    source: Some("chorddown".to_owned()),
+                    message: diagnostic.message().to_owned(),
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+
+    /// Describe the chord at the position and preview it transposed
+    pub fn hover(&self, position: LspPosition) -> Option<Hover> {
+        let token = self.token_at(position)?;
+        let raw = match &token.token {
+            Token::Chord(raw) => raw,
+            _ => return None,
+        };
+        let chords = Chords::try_from(raw, self.b_notation).ok()?;
+
+        let name = chords.note_format(self.formatting(self.semitone_notation));
+        let other_notation = match self.semitone_notation {
+            SemitoneNotation::Sharp => SemitoneNotation::Flat,
+            SemitoneNotation::Flat => SemitoneNotation::Sharp,
+        };
+        let other_name = chords.note_format(self.formatting(other_notation));
+
+        let mut value = if other_name != name {
+            format!("**{}** ({})", name, other_name)
+        } else {
+            format!("**{}**", name)
+        };
+        value.push_str("\n\nTransposed:\n");
+        for semitones in TRANSPOSE_PREVIEW {
+            let transposed = chords.clone().transpose(semitones);
+            value.push_str(&format!(
+                "\n- {:+}: `{}`",
+                semitones,
+                transposed.note_format(self.formatting(self.semitone_notation))
+            ));
+        }
+
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: Some(to_lsp_range(&self.text, token.span)),
+        })
+    }
+
+    /// Suggest section names in references and meta keywords in the head of the song
+    pub fn completion(&self, position: LspPosition) -> Vec<CompletionItem> {
+        let line = self.text.lines().nth(position.line as usize).unwrap_or("");
+        let cursor = from_lsp_position(&self.text, position);
+        let prefix: String = line.chars().take(cursor.column - 1).collect();
+
+        if let Some(reference) = prefix.strip_prefix('>') {
+            let start_column = cursor.column - reference.trim_start().chars().count();
+            let range = self.line_range(cursor.line, start_column, cursor.column);
+
+            return self.complete_section_names(range);
+        }
+
+        let is_keyword = !prefix.contains([':', '[', '#', '>']);
+        if is_keyword && self.is_in_head(cursor.line) {
+            let range = self.line_range(cursor.line, 1, cursor.column);
+
+            return self.complete_meta_keywords(range);
+        }
+
+        vec![]
+    }
+
+    /// Return the range of the Section a reference (`> Chorus`) at the position points to
+    pub fn definition(&self, position: LspPosition) -> Option<Range> {
+        let cursor = from_lsp_position(&self.text, position);
+        let reference = self
+            .sections
+            .iter()
+            .find(|s| s.is_reference && s.span.start.line == cursor.line)?;
+        let target = ReferenceResolver::new().resolve_reference(reference, &self.sections)?;
+
+        Some(to_lsp_range(&self.text, target.span))
+    }
+
+    /// Return a symbol for each Section
+    pub fn symbols(&self) -> Vec<DocumentSymbol> {
+        let end_of_document = Position::new(self.text.lines().count().max(1), usize::MAX);
+
+        self.sections
+            .iter()
+            .enumerate()
+            .map(|(index, section)| {
+                let end = self
+                    .sections
+                    .get(index + 1)
+                    .map_or(end_of_document, |next| next.span.start);
+                let kind = if section.section_type == SectionType::Reference {
+                    SymbolKind::KEY
+                } else {
+                    SymbolKind::NAMESPACE
+                };
+
+                #[allow(deprecated)]
+                DocumentSymbol {
+                    name: section.title.clone(),
+                    detail: Some(format!("{:?}", section.section_type)),
+                    kind,
+                    tags: None,
+                    deprecated: None,
+                    range: to_lsp_range(&self.text, Span::new(section.span.start, end)),
+                    selection_range: to_lsp_range(&self.text, section.span),
+                    children: None,
+                }
+            })
+            .collect()
+    }
+
+    fn complete_section_names(&self, range: Range) -> Vec<CompletionItem> {
+        let mut seen = HashSet::new();
+
+        self.sections
+            .iter()
+            .filter(|section| !section.is_reference && seen.insert(section.title.clone()))
+            .map(|section| CompletionItem {
+                label: section.title.clone(),
+                kind: Some(CompletionItemKind::REFERENCE),
+                detail: Some(format!("{:?}", section.section_type)),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit::new(
+                    range,
+                    section.title.clone(),
+                ))),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn complete_meta_keywords(&self, range: Range) -> Vec<CompletionItem> {
+        let used: HashSet<&str> = self
+            .tokens
+            .iter()
+            .filter_map(|t| match &t.token {
+                Token::Meta(meta) => Some(meta.keyword()),
+                _ => None,
+            })
+            .collect();
+
+        Meta::keywords()
+            .iter()
+            .filter(|keyword| !used.contains(*keyword))
+            .map(|keyword| CompletionItem {
+                label: keyword.to_string(),
+                kind: Some(CompletionItemKind::PROPERTY),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit::new(
+                    range,
+                    format!("{}: ", keyword),
+                ))),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn token_at(&self, position: LspPosition) -> Option<&SpannedToken> {
+        let position = from_lsp_position(&self.text, position);
+
+        self.tokens.iter().find(|t| t.span.contains(position))
+    }
+
+    /// Return if the line is part of the head (title and meta information) of the song
+    fn is_in_head(&self, line: usize) -> bool {
+        let first_section = self.tokens.iter().find(|t| match &t.token {
+            Token::Headline { level, .. } => *level > 1,
+            Token::Quote(_) => true,
+            _ => false,
+        });
+
+        first_section.is_none_or(|t| line < t.span.start.line)
+    }
+
+    fn line_range(&self, line: usize, start_column: usize, end_column: usize) -> Range {
+        Range::new(
+            to_lsp_position(&self.text, Position::new(line, start_column)),
+            to_lsp_position(&self.text, Position::new(line, end_column)),
+        )
+    }
+
+    fn formatting(&self, semitone_notation: SemitoneNotation) -> Formatting {
+        Formatting {
+            b_notation: self.b_notation,
+            semitone_notation,
+            ..Formatting::with_format(Format::Text)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "# Song
+Key: F
+Ar
+
+##! Chorus
+Swing [Bb]low
+
+## Verse
+La [C7]la
+
+> Chorus
+>
+";
+
+    #[test]
+    fn test_diagnostics() {
+        let document = Document::new("# Song\n\n## Verse\nLa [Xyz]la\n".to_owned());
+        let diagnostics = document.diagnostics();
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].range.start, LspPosition::new(3, 4));
+        assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::ERROR));
+    }
+
+    #[test]
+    fn test_hover() {
+        let document = Document::new(SOURCE.to_owned());
+        let hover = document.hover(LspPosition::new(5, 8)).unwrap();
+
+        let value = match hover.contents {
+            HoverContents::Markup(content) => content.value,
+            _ => panic!("Expected markup"),
+        };
+        assert!(value.starts_with("**Bb** (A#)"));
+        assert!(value.contains("- +2: `C`"));
+        assert_eq!(
+            hover.range,
+            Some(Range::new(LspPosition::new(5, 6), LspPosition::new(5, 10)))
+        );
+        assert!(document.hover(LspPosition::new(5, 2)).is_none());
+    }
+
+    #[test]
+    fn test_completion() {
+        let document = Document::new(SOURCE.to_owned());
+
+        let keywords = document.completion(LspPosition::new(2, 2));
+        assert!(keywords.iter().any(|item| item.label == "Artist"));
+        // `Key` is already defined
+        assert!(!keywords.iter().any(|item| item.label == "Key"));
+
+        let sections = document.completion(LspPosition::new(11, 2));
+        let labels: Vec<&str> = sections.iter().map(|item| item.label.as_str()).collect();
+        assert_eq!(labels, vec!["Chorus", "Verse"]);
+
+        assert!(document.completion(LspPosition::new(8, 2)).is_empty());
+    }
+
+    #[test]
+    fn test_definition() {
+        let document = Document::new(SOURCE.to_owned());
+
+        assert_eq!(
+            document.definition(LspPosition::new(10, 3)).unwrap().start,
+            LspPosition::new(4, 0)
+        );
+        assert!(document.definition(LspPosition::new(8, 3)).is_none());
+    }
+
+    #[test]
+    fn test_symbols() {
+        let document = Document::new(SOURCE.to_owned());
+        let symbols = document.symbols();
+
+        let names: Vec<&str> = symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["Chorus", "Verse", "Chorus"]);
+        assert_eq!(symbols[0].kind, SymbolKind::NAMESPACE);
+        assert_eq!(symbols[2].kind, SymbolKind::KEY);
+        assert_eq!(
+            symbols[0].range,
+            Range::new(LspPosition::new(4, 0), LspPosition::new(7, 0))
+        );
+    }
+}
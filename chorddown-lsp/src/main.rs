@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use log::{debug, info, LevelFilter};
+use lsp_server::{Connection, ExtractError, Message, Notification, Request, RequestId, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+    Notification as NotificationTrait, PublishDiagnostics,
+};
+use lsp_types::request::{
+    Completion, DocumentSymbolRequest, GotoDefinition, HoverRequest, Request as RequestTrait,
+};
+use lsp_types::{
+    CompletionOptions, CompletionResponse, DocumentSymbolResponse, GotoDefinitionResponse,
+    HoverProviderCapability, Location, OneOf, PublishDiagnosticsParams, ServerCapabilities,
+    TextDocumentSyncCapability, TextDocumentSyncKind, Url,
+};
+use simplelog::{ColorChoice, Config, TermLogger, TerminalMode};
+
+use crate::document::Document;
+
+mod document;
+mod position;
+
+type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync>>;
+
+fn main() -> Result<()> {
+    // stdout is used for the communication with the client
+    TermLogger::init(
+        LevelFilter::Info,
+        Config::default(),
+        TerminalMode::Stderr,
+        ColorChoice::Never,
+    )?;
+
+    let (connection, io_threads) = Connection::stdio();
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![">".to_owned()]),
+            ..Default::default()
+        }),
+        definition_provider: Some(OneOf::Left(true)),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        ..Default::default()
+    };
+    connection.initialize(serde_json::to_value(capabilities)?)?;
+    info!("chorddown language server initialized");
+
+    Server::default().run(&connection)?;
+    io_threads.join()?;
+
+    info!("chorddown language server stopped");
+    Ok(())
+}
+
+#[derive(Default)]
+struct Server {
+    documents: HashMap<Url, Document>,
+}
+
+impl Server {
+    fn run(&mut self, connection: &Connection) -> Result<()> {
+        for message in &connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    let response = self.handle_request(request);
+                    connection.sender.send(Message::Response(response))?;
+                }
+                Message::Notification(notification) => {
+                    if let Some(notification) = self.handle_notification(notification)? {
+                        connection
+                            .sender
+                            .send(Message::Notification(notification))?;
+                    }
+                }
+                Message::Response(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_request(&self, request: Request) -> Response {
+        let id = request.id.clone();
+        let result = match request.method.as_str() {
+            HoverRequest::METHOD => extract::<HoverRequest>(request).map(|params| {
+                let position = params.text_document_position_params;
+                serde_json::to_value(
+                    self.documents
+                        .get(&position.text_document.uri)
+                        .and_then(|d| d.hover(position.position)),
+                )
+            }),
+            Completion::METHOD => extract::<Completion>(request).map(|params| {
+                let position = params.text_document_position;
+                serde_json::to_value(
+                    self.documents
+                        .get(&position.text_document.uri)
+                        .map(|d| CompletionResponse::Array(d.completion(position.position))),
+                )
+            }),
+            GotoDefinition::METHOD => extract::<GotoDefinition>(request).map(|params| {
+                let position = params.text_document_position_params;
+                let uri = position.text_document.uri;
+                serde_json::to_value(
+                    self.documents
+                        .get(&uri)
+                        .and_then(|d| d.definition(position.position))
+                        .map(|range| GotoDefinitionResponse::Scalar(Location::new(uri, range))),
+                )
+            }),
+            DocumentSymbolRequest::METHOD => {
+                extract::<DocumentSymbolRequest>(request).map(|params| {
+                    serde_json::to_value(
+                        self.documents
+                            .get(&params.text_document.uri)
+                            .map(|d| DocumentSymbolResponse::Nested(d.symbols())),
+                    )
+                })
+            }
+            method => {
+                debug!("Unhandled request {}", method);
+                return Response::new_err(
+                    id,
+                    lsp_server::ErrorCode::MethodNotFound as i32,
+                    format!("Unhandled request {}", method),
+                );
+            }
+        };
+
+        match result {
+            Ok(Ok(value)) => Response::new_ok(id, value),
+            Ok(Err(error)) => internal_error(id, error.to_string()),
+            Err(error) => internal_error(id, error.to_string()),
+        }
+    }
+
+    /// Update the open documents and return the diagnostics to publish
+    fn handle_notification(&mut self, notification: Notification) -> Result<Option<Notification>> {
+        let uri = match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params = extract_notification::<DidOpenTextDocument>(notification)?;
+                let uri = params.text_document.uri;
+                self.documents
+                    .insert(uri.clone(), Document::new(params.text_document.text));
+
+                uri
+            }
+            DidChangeTextDocument::METHOD => {
+                let params = extract_notification::<DidChangeTextDocument>(notification)?;
+                let uri = params.text_document.uri;
+                // The server only supports full synchronization, so the last change contains
+                // the whole text
+                if let Some(change) = params.content_changes.into_iter().last() {
+                    match self.documents.get_mut(&uri) {
+                        Some(document) => document.update(change.text),
+                        None => {
+                            self.documents
+                                .insert(uri.clone(), Document::new(change.text));
+                        }
+                    }
+                }
+
+                uri
+            }
+            DidCloseTextDocument::METHOD => {
+                let params = extract_notification::<DidCloseTextDocument>(notification)?;
+                self.documents.remove(&params.text_document.uri);
+
+                // Clear the diagnostics of the closed document
+                return Ok(Some(publish_diagnostics(params.text_document.uri, vec![])));
+            }
+            method => {
+                debug!("Unhandled notification {}", method);
+                return Ok(None);
+            }
+        };
+
+        let diagnostics = self
+            .documents
+            .get(&uri)
+            .map(Document::diagnostics)
+            .unwrap_or_default();
+
+        Ok(Some(publish_diagnostics(uri, diagnostics)))
+    }
+}
+
+fn extract<R: RequestTrait>(
+    request: Request,
+) -> std::result::Result<R::Params, ExtractError<Request>> {
+    request.extract(R::METHOD).map(|(_, params)| params)
+}
+
+fn extract_notification<N: NotificationTrait>(notification: Notification) -> Result<N::Params> {
+    notification
+        .extract(N::METHOD)
+        .map_err(|e| format!("Invalid notification: {:?}", e).into())
+}
+
+fn publish_diagnostics(uri: Url, diagnostics: Vec<lsp_types::Diagnostic>) -> Notification {
+    Notification::new(
+        PublishDiagnostics::METHOD.to_owned(),
+        PublishDiagnosticsParams::new(uri, diagnostics, None),
+    )
+}
+
+fn internal_error(id: RequestId, message: String) -> Response {
+    Response::new_err(id, lsp_server::ErrorCode::InternalError as i32, message)
+}
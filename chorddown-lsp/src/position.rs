@@ -0,0 +1,58 @@
+use libchordr::prelude::{Position, Span};
+use lsp_types::{Position as LspPosition, Range};
+
+/// Convert the Position into an LSP Position
+///
+/// libchordr counts lines and columns from 1 and columns in characters, while LSP counts from 0
+/// and columns in UTF-16 code units
+pub(crate) fn to_lsp_position(text: &str, position: Position) -> LspPosition {
+    let line = position.line.saturating_sub(1);
+    let character: usize = text.lines().nth(line).map_or(0, |line_text| {
+        line_text
+            .chars()
+            .take(position.column.saturating_sub(1))
+            .map(char::len_utf16)
+            .sum()
+    });
+
+    LspPosition::new(line as u32, character as u32)
+}
+
+/// Convert the LSP Position into a Position
+pub(crate) fn from_lsp_position(text: &str, position: LspPosition) -> Position {
+    let line_text = text.lines().nth(position.line as usize).unwrap_or("");
+    let mut code_units = 0;
+    let mut column = 1;
+    for c in line_text.chars() {
+        if code_units >= position.character as usize {
+            break;
+        }
+        code_units += c.len_utf16();
+        column += 1;
+    }
+
+    Position::new(position.line as usize + 1, column)
+}
+
+pub(crate) fn to_lsp_range(text: &str, span: Span) -> Range {
+    Range::new(
+        to_lsp_position(text, span.start),
+        to_lsp_position(text, span.end),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_position() {
+        let text = "# Song\nS𝄞ng [D]low\n";
+        let position = Position::new(2, 4);
+        let lsp_position = to_lsp_position(text, position);
+
+        // `𝄞` takes two UTF-16 code units
+        assert_eq!(lsp_position, LspPosition::new(1, 4));
+        assert_eq!(from_lsp_position(text, lsp_position), position);
+    }
+}
@@ -16,13 +16,13 @@ pub struct Configuration {
     /// Path to the output directory
     pub output_directory: PathBuf,
 
-    /// Online service configuration (dropbox, WebDAV)
+    /// Service configuration (dropbox, WebDAV, local, Git)
     pub service: ServiceConfiguration,
 }
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct ServiceConfiguration {
-    /// Online service to use (dropbox, WebDAV)
+    /// Service to use (dropbox, WebDAV, local, Git)
     pub identifier: ServiceIdentifier,
 
     /// API key to authenticate with the service (dropbox)
@@ -34,12 +34,27 @@ pub struct ServiceConfiguration {
     /// Password to authenticate with the service (WebDAV)
     pub password: Option<String>,
 
-    /// WebDAV entry point URL (WebDAV)
+    /// WebDAV entry point URL (WebDAV) or repository URL (Git)
     pub url: Option<String>,
 
-    /// Remote directory to list
+    /// Remote directory to list (source directory for the local service, optional for Git)
     pub remote_directory: Option<String>,
 
     /// Number of seconds to wait between service updates
     pub sync_interval: u64,
+
+    /// Upload local changes to the service instead of only downloading (two-way sync)
+    #[serde(default)]
+    pub upload_local_changes: bool,
+
+    /// Maximum percentage of the synchronized files that may be deleted in one run (default: 50)
+    pub max_delete_percent: Option<u8>,
+
+    /// Only synchronize files matching one of the glob patterns (e.g. `*.chorddown`)
+    #[serde(default)]
+    pub include: Vec<String>,
+
+    /// Do not synchronize files matching one of the glob patterns
+    #[serde(default)]
+    pub exclude: Vec<String>,
 }
@@ -109,6 +109,8 @@ mod test {
 
         let configuration = assert_valid_mandatory_configuration(result);
         assert_eq!(configuration.service.identifier, ServiceIdentifier::Dropbox);
+        assert!(!configuration.service.upload_local_changes);
+        assert!(configuration.service.include.is_empty());
         assert_eq!(configuration.service.api_token.unwrap(), "MY_API_TOKEN");
     }
 
@@ -116,6 +118,8 @@ mod test {
         assert!(result.is_ok(), "{}", result.unwrap_err().to_string());
 
         let configuration = assert_valid_mandatory_configuration(result);
+        assert!(configuration.service.upload_local_changes);
+        assert_eq!(configuration.service.include, vec!["*.chorddown"]);
         assert_valid_webdav_configuration_values(configuration);
     }
 
@@ -1,13 +1,32 @@
 use crate::configuration::Configuration;
 use crate::error::Error;
 use crate::task::{RecurringTaskTrait, TaskTrait};
-use libchordr::prelude::{CatalogBuildResult, CatalogBuilder, FileType};
-use log::info;
+use libchordr::prelude::{CatalogBuildResult, CatalogBuilder, CatalogCache, FileType};
+use log::{info, warn};
+use std::cell::RefCell;
 use std::fs;
+use std::path::{Path, PathBuf};
 
 pub struct BuildCatalogTask {
     catalog_builder: CatalogBuilder,
     configuration: Configuration,
+    cache: RefCell<CatalogCache>,
+}
+
+impl BuildCatalogTask {
+    /// Path of the file to persist the catalog cache (next to the catalog file)
+    fn cache_file(&self) -> PathBuf {
+        cache_file_for_configuration(&self.configuration)
+    }
+
+    fn write_cache(&self) -> Result<(), Error> {
+        let output = match serde_json::to_string(&*self.cache.borrow()) {
+            Ok(s) => s,
+            Err(e) => return Err(Error::serialization_error(format!("{}", e))),
+        };
+
+        Ok(fs::write(self.cache_file(), output)?)
+    }
 }
 
 impl TaskTrait for BuildCatalogTask {
@@ -16,9 +35,11 @@ impl TaskTrait for BuildCatalogTask {
         Self: Sized,
     {
         let catalog_builder = CatalogBuilder::new();
+        let cache = read_cache(&cache_file_for_configuration(&configuration));
         Ok(Self {
             catalog_builder,
             configuration,
+            cache: RefCell::new(cache),
         })
     }
 }
@@ -27,11 +48,27 @@ impl RecurringTaskTrait for BuildCatalogTask {
     fn run(&self) -> Result<(), Error> {
         info!("Run Build Catalog Task");
         let pretty = true;
-        let catalog: CatalogBuildResult = self.catalog_builder.build_catalog_for_directory(
-            self.configuration.output_directory.as_path(),
-            FileType::Chorddown,
-            true,
-        )?;
+        let catalog: CatalogBuildResult = self
+            .catalog_builder
+            .build_catalog_for_directory_incremental(
+                self.configuration.output_directory.as_path(),
+                &[FileType::Chorddown, FileType::ChordPro],
+                true,
+                &mut self.cache.borrow_mut(),
+            )?;
+
+        let catalog_file = self.configuration.catalog_file.as_path();
+        if catalog.changes.is_empty() && catalog_file.exists() {
+            info!("Catalog is up to date");
+            // The cache may still have been refreshed (e.g. with touched modification times)
+            return self.write_cache();
+        }
+        info!(
+            "Catalog changed: {} added, {} changed, {} removed",
+            catalog.changes.added.len(),
+            catalog.changes.changed.len(),
+            catalog.changes.removed.len()
+        );
 
         let serialization_result = if pretty {
             serde_json::to_string_pretty(&catalog.catalog)
@@ -44,13 +81,35 @@ impl RecurringTaskTrait for BuildCatalogTask {
             Err(e) => return Err(Error::serialization_error(format!("{}", e))),
         };
 
-        info!(
-            "Write catalog to {}",
-            self.configuration.catalog_file.as_path().to_string_lossy()
-        );
-        Ok(fs::write(
-            self.configuration.catalog_file.as_path(),
-            output,
-        )?)
+        info!("Write catalog to {}", catalog_file.to_string_lossy());
+        fs::write(catalog_file, output)?;
+
+        self.write_cache()
+    }
+}
+
+fn cache_file_for_configuration(configuration: &Configuration) -> PathBuf {
+    configuration.catalog_file.with_extension("cache.json")
+}
+
+/// Read the persisted cache or return an empty cache if it does not exist or is invalid
+fn read_cache(path: &Path) -> CatalogCache {
+    if !path.exists() {
+        return CatalogCache::new();
+    }
+
+    match fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()))
+    {
+        Ok(cache) => cache,
+        Err(e) => {
+            warn!(
+                "Could not read the catalog cache {}: {}",
+                path.to_string_lossy(),
+                e
+            );
+            CatalogCache::new()
+        }
     }
 }
@@ -1,4 +1,6 @@
 use libsynchord::error::Error as SynchordError;
+use libsynchord::file_filter::FileFilter;
+use libsynchord::helper::SyncOptions;
 use libsynchord::prelude::{
     AbstractServiceConfig, ServiceConfigurationTrait, ServiceTrait, Services,
 };
@@ -12,28 +14,50 @@ use crate::error::Result;
 pub struct DownloadTask {
     service_config: AbstractServiceConfig,
     service: Services,
+    upload_local_changes: bool,
+    sync_options: SyncOptions,
 }
 
 impl TaskTrait for DownloadTask {
     fn with_configuration(configuration: Configuration) -> Result<Self> {
+        let upload_local_changes = configuration.service.upload_local_changes;
+        let mut sync_options = SyncOptions::default();
+        if let Some(max_delete_percent) = configuration.service.max_delete_percent {
+            sync_options.max_delete_percent = max_delete_percent;
+        }
+        sync_options.filter = FileFilter::new(
+            &configuration.service.include,
+            &configuration.service.exclude,
+        )?;
         let service_config = build_service_config(configuration);
         let service = get_service(service_config.clone())?;
 
         Ok(Self {
             service_config,
             service,
+            upload_local_changes,
+            sync_options,
         })
     }
 }
 
 impl RecurringTaskTrait for DownloadTask {
     fn run(&self) -> Result<()> {
-        info!(
-            "Run Download Task: Download files using service {} to {}",
-            self.service.identifier(),
-            self.service_config.local_directory().display()
-        );
-        libsynchord::helper::download(&self.service, &self.service_config)?;
+        if self.upload_local_changes {
+            info!(
+                "Run Download Task: Synchronize files using service {} with {}",
+                self.service.identifier(),
+                self.service_config.local_directory().display()
+            );
+            libsynchord::helper::sync(&self.service, &self.service_config, &self.sync_options)?;
+        } else {
+            info!(
+                "Run Download Task: Download files using service {} to {}",
+                self.service.identifier(),
+                self.service_config.local_directory().display()
+            );
+            libsynchord::helper::download(&self.service, &self.service_config, &self.sync_options)?;
+        }
 
         Ok(())
     }
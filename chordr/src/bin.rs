@@ -3,6 +3,7 @@ use std::error::Error as StdError;
 use std::fs;
 use std::fs::File;
 use std::io::BufReader;
+use std::path::Path;
 use std::process::exit;
 
 use ansi_term::Colour;
@@ -11,8 +12,9 @@ use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
 use log::LevelFilter;
 use simplelog::{ColorChoice, Config, TermLogger, TerminalMode};
 
-use libchordr::models::chord::fmt::Formatting;
-use libchordr::modification::transposition::TransposableTrait;
+use libchordr::models::chord::fmt::{CapoFormatting, ChordDiagramFormatting, Formatting};
+use libchordr::models::chord::{Chord, NoteDisplay};
+use libchordr::modification::transposition::{KeyTransposableTrait, TransposableTrait};
 use libchordr::prelude::Error;
 use libchordr::prelude::Result;
 use libchordr::prelude::*;
@@ -32,7 +34,7 @@ fn main() {
         .arg(
             Arg::with_name("input")
                 .required(true)
-                .help("Chorddown file to parse"),
+                .help("Chorddown or ChordPro file to parse"),
         )
         .arg(output_arg.clone())
         .arg(Arg::with_name("format").help(&format_help))
@@ -42,6 +44,13 @@ fn main() {
                 .takes_value(true)
                 .help("Number of semitones to transpose the song"),
         )
+        .arg(
+            Arg::with_name("to-key")
+                .long("to-key")
+                .takes_value(true)
+                .conflicts_with("transpose")
+                .help("Transpose the song into the given key (the song must define its key)"),
+        )
         .arg(
             Arg::with_name("b-notation")
                 .long("b-notation")
@@ -54,6 +63,43 @@ fn main() {
                 .takes_value(true)
                 .help("Define if `#` or `b` should be used"),
         )
+        .arg(
+            Arg::with_name("chord-notation")
+                .long("chord-notation")
+                .takes_value(true)
+                .help(
+                    "Display the chords by name (letter) or relative to the key (nashville, roman)",
+                ),
+        )
+        .arg(
+            Arg::with_name("capo")
+                .long("capo")
+                .takes_value(true)
+                .min_values(0)
+                .help(
+                    "Render the chord shapes for the given capo fret (defaults to the song's capo)",
+                ),
+        )
+        .arg(
+            Arg::with_name("concert-pitch")
+                .long("concert-pitch")
+                .requires("capo")
+                .help("Show the concert-pitch chords alongside the chord shapes"),
+        )
+        .arg(
+            Arg::with_name("chord-diagrams")
+                .long("chord-diagrams")
+                .takes_value(true)
+                .min_values(0)
+                .help("Add diagrams of the used chords for the instrument (guitar, ukulele)"),
+        )
+        .arg(
+            Arg::with_name("tuning")
+                .long("tuning")
+                .takes_value(true)
+                .requires("chord-diagrams")
+                .help("Tuning of the instrument (standard, half-step-down, whole-step-down, drop)"),
+        )
         .arg(verbosity_arg.clone());
 
     let subcommand_build_catalog = SubCommand::with_name("build-catalog")
@@ -61,7 +107,7 @@ fn main() {
         .arg(
             Arg::with_name("dir")
                 .required(true)
-                .help("Path to the directory of chorddown files"),
+                .help("Path to the directory of chorddown and ChordPro files"),
         )
         .arg(output_arg.clone())
         .arg(
@@ -70,7 +116,76 @@ fn main() {
                 .short("p")
                 .help("Output indented JSON"),
         )
-        .arg(verbosity_arg);
+        .arg(verbosity_arg.clone());
+
+    let subcommand_export_setlist = SubCommand::with_name("export-setlist")
+        .about("Render all songs of a setlist into one document")
+        .arg(
+            Arg::with_name("setlist")
+                .required(true)
+                .help("Setlist JSON file or file containing a shared setlist link"),
+        )
+        .arg(
+            Arg::with_name("catalog")
+                .required(true)
+                .help("Catalog JSON file or directory of chorddown and ChordPro files"),
+        )
+        .arg(output_arg.clone())
+        .arg(Arg::with_name("format").help(&format_help))
+        .arg(verbosity_arg.clone());
+
+    let subcommand_analyze = SubCommand::with_name("analyze")
+        .about("Analyze a song and estimate its key from the chords")
+        .arg(
+            Arg::with_name("input")
+                .required(true)
+                .help("Chorddown or ChordPro file to analyze"),
+        )
+        .arg(
+            Arg::with_name("json")
+                .long("json")
+                .help("Output the result as JSON"),
+        )
+        .arg(verbosity_arg.clone());
+
+    let subcommand_lint = SubCommand::with_name("lint")
+        .about("Check chorddown files for problems")
+        .arg(
+            Arg::with_name("path")
+                .required(true)
+                .help("Chorddown file or directory of chorddown files"),
+        )
+        .arg(
+            Arg::with_name("json")
+                .long("json")
+                .help("Output the diagnostics as JSON"),
+        )
+        .arg(
+            Arg::with_name("deny-warnings")
+                .long("deny-warnings")
+                .help("Exit with an error code if there are warnings"),
+        )
+        .arg(verbosity_arg.clone());
+
+    let subcommand_fmt = SubCommand::with_name("fmt")
+        .about("Rewrite chorddown files in the canonical style")
+        .arg(
+            Arg::with_name("path")
+                .required(true)
+                .help("Chorddown file or directory of chorddown files"),
+        )
+        .arg(
+            Arg::with_name("check")
+                .long("check")
+                .help("Only check if the files are formatted (exit with an error code if not)"),
+        )
+        .arg(
+            Arg::with_name("semitone-notation")
+                .long("semitone-notation")
+                .takes_value(true)
+                .help("Define if `#` or `b` should be used (defaults to the notation of the key)"),
+        )
+        .arg(verbosity_arg.clone());
 
     let args = App::new("chordr")
         .version(env!("CARGO_PKG_VERSION"))
@@ -80,6 +195,10 @@ fn main() {
         .setting(AppSettings::ColoredHelp)
         .subcommand(subcommand_convert)
         .subcommand(subcommand_build_catalog)
+        .subcommand(subcommand_export_setlist)
+        .subcommand(subcommand_analyze)
+        .subcommand(subcommand_lint)
+        .subcommand(subcommand_fmt)
         .get_matches();
 
     if let Err(error) = run(args) {
@@ -95,6 +214,18 @@ fn run(args: ArgMatches<'_>) -> Result<()> {
     } else if let Some(matches) = args.subcommand_matches("build-catalog") {
         configure_logging(matches)?;
         build_catalog(matches)
+    } else if let Some(matches) = args.subcommand_matches("export-setlist") {
+        configure_logging(matches)?;
+        export_setlist(matches)
+    } else if let Some(matches) = args.subcommand_matches("analyze") {
+        configure_logging(matches)?;
+        analyze(matches)
+    } else if let Some(matches) = args.subcommand_matches("lint") {
+        configure_logging(matches)?;
+        lint(matches)
+    } else if let Some(matches) = args.subcommand_matches("fmt") {
+        configure_logging(matches)?;
+        fmt(matches)
     } else {
         eprintln!("Missing argument subcommand");
         exit(1);
@@ -110,50 +241,324 @@ fn convert(args: &ArgMatches<'_>) -> Result<()> {
         Some(b) => BNotation::try_from(b)?,
     };
     let semitone_notation = match args.value_of("semitone-notation") {
-        None => SemitoneNotation::default(),
-        Some(b) => SemitoneNotation::try_from(b)?,
+        None => None,
+        Some(b) => Some(SemitoneNotation::try_from(b)?),
+    };
+
+    let chord_notation = match args.value_of("chord-notation") {
+        None => ChordNotation::default(),
+        Some(n) => ChordNotation::try_from(n)?,
     };
 
     let transpose: Option<isize> = match args.value_of("transpose") {
         None => None,
         Some(raw) => Some(str::parse::<isize>(raw)?),
     };
+    let to_key = match args.value_of("to-key") {
+        None => None,
+        Some(raw) => Some(Chord::try_from(raw, b_notation)?),
+    };
 
-    let format = get_output_format(args);
+    let capo = if args.is_present("capo") {
+        let fret = match args.value_of("capo") {
+            None => None,
+            Some(raw) => Some(str::parse::<u8>(raw)?),
+        };
+        Some(CapoFormatting::new(fret, args.is_present("concert-pitch")))
+    } else {
+        None
+    };
 
-    let formatting = Formatting {
-        b_notation,
-        semitone_notation,
-        format,
+    let chord_diagrams = if args.is_present("chord-diagrams") {
+        let instrument = match args.value_of("chord-diagrams") {
+            None => Instrument::default(),
+            Some(raw) => Instrument::try_from(raw)
+                .map_err(|_| Error::unknown_error(format!("Invalid instrument '{}'", raw)))?,
+        };
+        let tuning = match args.value_of("tuning") {
+            None => Tuning::default(),
+            Some(raw) => Tuning::try_from(raw)
+                .map_err(|_| Error::unknown_error(format!("Invalid tuning '{}'", raw)))?,
+        };
+        Some(ChordDiagramFormatting::new(instrument, tuning))
+    } else {
+        None
     };
 
+    let format = get_output_format(args);
+
     let file = match File::open(input_file_path) {
         Ok(c) => c,
         Err(e) => return Err(Error::unknown_error(format!("Could not read file: {}", e))),
     };
     log::debug!("Did open file");
 
-    let (tokens, _warnings) = build_tokenizer().tokenize(BufReader::new(file))?;
+    let (tokens, warnings) = if FileType::ChordPro.path_matches(Path::new(input_file_path)) {
+        build_chord_pro_tokenizer().tokenize(BufReader::new(file))?
+    } else {
+        build_tokenizer().tokenize(BufReader::new(file))?
+    };
     log::debug!("Did tokenize content");
+    for warning in warnings {
+        log::warn!("Tokenizer warning: {}", warning);
+    }
 
     let parser_result = Parser::new().parse(tokens)?;
     log::debug!("Did parse content");
-    let ParserResult { meta, node } = parser_result;
-    let parser_result_node = match transpose {
-        None => node,
-        Some(t) => {
-            let transposed = node.transpose(t);
+    let ParserResult { meta, node } = match (transpose, to_key) {
+        (Some(t), _) => {
+            let ParserResult { meta, node } = parser_result;
             log::debug!("Did transpose");
+            ParserResult::new(node.transpose(t), meta.transpose(t))
+        }
+        (None, Some(key)) => {
+            let transposed = parser_result.transpose_to_key(key)?;
+            log::debug!("Did transpose to key");
             transposed
         }
+        (None, None) => parser_result,
+    };
+
+    let formatting = Formatting {
+        b_notation,
+        // Without an explicit notation the key signature decides between sharps and flats
+        semitone_notation: semitone_notation.unwrap_or_default(),
+        semitone_notation_for_key: semitone_notation.is_none(),
+        format,
+        capo,
+        chord_notation,
+        key: None,
+        chord_diagrams,
     };
 
-    let converted = Converter::new().convert(&parser_result_node, &meta, formatting)?;
+    let converted = Converter::new().convert(&node, &meta, formatting)?;
     log::debug!("Did convert content");
 
     let output = if format == Format::HTML {
-        format!(
-            r#"
+        wrap_html_document(&meta.title().unwrap_or_default(), &converted)
+    } else {
+        converted
+    };
+
+    handle_output(output_file_path, output)
+}
+
+fn export_setlist(args: &ArgMatches<'_>) -> Result<()> {
+    let setlist_file_path = args.value_of("setlist").unwrap();
+    let catalog_path = args.value_of("catalog").unwrap();
+    let output_file_path = args.value_of("output").unwrap();
+    let format = get_output_format(args);
+
+    let songbook = read_songbook(setlist_file_path)?;
+    log::debug!("Did read setlist '{}'", songbook.title());
+    let catalog = read_catalog(catalog_path)?;
+    log::debug!("Did read catalog '{}'", catalog.revision());
+
+    let rendered = songbook.render(&catalog, format)?;
+    let output = if format == Format::HTML {
+        wrap_html_document(songbook.title(), &rendered)
+    } else {
+        rendered
+    };
+
+    handle_output(output_file_path, output)
+}
+
+/// Number of detected keys `chordr analyze` displays
+const ANALYZE_MAX_KEYS: usize = 5;
+
+fn analyze(args: &ArgMatches<'_>) -> Result<()> {
+    let input_file_path = Path::new(args.value_of("input").unwrap());
+    let file_type = FileType::try_from(input_file_path)?;
+    let content = match fs::read_to_string(input_file_path) {
+        Ok(c) => c,
+        Err(e) => return Err(Error::unknown_error(format!("Could not read file: {}", e))),
+    };
+    let parser_result = parse_content_for_file_type(content.as_bytes(), file_type)?;
+    let key = parser_result.meta_as_ref().key();
+    let guesses: Vec<KeyGuess> = KeyDetector::new()
+        .detect(parser_result.node_as_ref())
+        .into_iter()
+        .take(ANALYZE_MAX_KEYS)
+        .collect();
+
+    if args.is_present("json") {
+        let output = serde_json::json!({
+            "key": key,
+            "detected_keys": guesses,
+        });
+        println!("{}", output);
+        return Ok(());
+    }
+
+    let formatting = Formatting::with_format(Format::Text);
+    if let Some(title) = parser_result.meta_as_ref().title() {
+        println!("{}", title);
+    }
+    match key {
+        Some(key) => println!("Key: {}", key.note_format(formatting)),
+        None => println!("Key: None"),
+    }
+    if guesses.is_empty() {
+        println!("No chords found to detect the key");
+    } else {
+        println!("Detected keys:");
+        for guess in guesses {
+            println!(
+                "  {:<4} {:>3.0}%",
+                guess.key.note_format(formatting),
+                guess.confidence * 100.0
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn lint(args: &ArgMatches<'_>) -> Result<()> {
+    let path = Path::new(args.value_of("path").unwrap());
+    let validator = Validator::new();
+    let diagnostics = if path.is_dir() {
+        validator.validate_directory(path, true)?
+    } else {
+        validator.validate_file(path)
+    };
+
+    if args.is_present("json") {
+        match serde_json::to_string_pretty(&diagnostics) {
+            Ok(output) => println!("{}", output),
+            Err(e) => return Err(Error::unknown_error(format!("{}", e))),
+        }
+    } else {
+        let colored = atty::is(Stream::Stdout);
+        for diagnostic in &diagnostics {
+            let output = diagnostic.to_string();
+            match (colored, diagnostic.severity()) {
+                (true, Severity::Error) => println!("{}", Colour::Red.paint(output)),
+                (true, Severity::Warning) => println!("{}", Colour::Yellow.paint(output)),
+                (false, _) => println!("{}", output),
+            }
+        }
+    }
+
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity() == Severity::Error)
+        .count();
+    let warnings = diagnostics.len() - errors;
+    if errors > 0 || (warnings > 0 && args.is_present("deny-warnings")) {
+        Err(Error::unknown_error(format!(
+            "Found {} error(s) and {} warning(s)",
+            errors, warnings
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+fn fmt(args: &ArgMatches<'_>) -> Result<()> {
+    let path = Path::new(args.value_of("path").unwrap());
+    let semitone_notation = match args.value_of("semitone-notation") {
+        None => None,
+        Some(n) => Some(SemitoneNotation::try_from(n)?),
+    };
+    let mode = if args.is_present("check") {
+        FormatMode::Check
+    } else {
+        FormatMode::Write
+    };
+
+    let formatter = ChorddownFormatter::new().with_semitone_notation(semitone_notation);
+    let results = if path.is_dir() {
+        formatter.format_directory(path, mode, true)
+    } else {
+        vec![(path.to_path_buf(), formatter.format_file(path, mode))]
+    };
+
+    let mut unformatted = 0;
+    let mut failed = 0;
+    for (file, result) in results {
+        match result {
+            Ok(false) => {}
+            Ok(true) => {
+                unformatted += 1;
+                match mode {
+                    FormatMode::Check => println!("{} is not formatted", file.display()),
+                    FormatMode::Write => println!("Formatted {}", file.display()),
+                }
+            }
+            Err(error) => {
+                failed += 1;
+                eprintln!("{}: {}", file.display(), error);
+            }
+        }
+    }
+
+    if failed > 0 {
+        Err(Error::unknown_error(format!(
+            "{} file(s) could not be formatted",
+            failed
+        )))
+    } else if mode == FormatMode::Check && unformatted > 0 {
+        Err(Error::unknown_error(format!(
+            "{} file(s) are not formatted",
+            unformatted
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Read the `Songbook` from a `Setlist` JSON file or a file containing a shared setlist link
+fn read_songbook(path: &str) -> Result<Songbook> {
+    let content = match fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) => return Err(Error::unknown_error(format!("Could not read file: {}", e))),
+    };
+    let content = content.trim();
+    if content.starts_with('{') {
+        return match serde_json::from_str::<Setlist>(content) {
+            Ok(setlist) => Ok(Songbook::from(&setlist)),
+            Err(e) => Err(Error::setlist_error(format!(
+                "Could not read setlist: {}",
+                e
+            ))),
+        };
+    }
+
+    // Accept both the complete link and the query string only
+    let query = match content.find('?') {
+        Some(position) => &content[position + 1..],
+        None => content,
+    };
+    Songbook::from_sharing_setlist_query(query)
+}
+
+/// Read the `Catalog` from a JSON file or build it from a directory
+fn read_catalog(path: &str) -> Result<Catalog> {
+    if Path::new(path).is_dir() {
+        let catalog_result = CatalogBuilder::new().build_catalog_for_directory_with_file_types(
+            path,
+            &[FileType::Chorddown, FileType::ChordPro],
+            true,
+        )?;
+        for error in catalog_result.errors {
+            handle_error_output(error)
+        }
+        return Ok(catalog_result.catalog);
+    }
+
+    let file = match File::open(path) {
+        Ok(c) => c,
+        Err(e) => return Err(Error::unknown_error(format!("Could not read file: {}", e))),
+    };
+    serde_json::from_reader(BufReader::new(file))
+        .map_err(|e| Error::unknown_error(format!("Could not read catalog: {}", e)))
+}
+
+fn wrap_html_document(title: &str, content: &str) -> String {
+    format!(
+        r#"
 <!DOCTYPE html>
 <html lang="en">
 <head>
@@ -168,16 +573,10 @@ fn convert(args: &ArgMatches<'_>) -> Result<()> {
 </body>
 </html>
     "#,
-            title = meta.title().unwrap_or_default(),
-            styles =
-                include_str!("../../webchordr/app/static/stylesheets/chordr-default-styles.css"),
-            content = converted
-        )
-    } else {
-        converted
-    };
-
-    handle_output(output_file_path, output)
+        title = title,
+        styles = include_str!("../../webchordr/app/static/stylesheets/chordr-default-styles.css"),
+        content = content
+    )
 }
 
 fn get_output_format_help() -> String {
@@ -215,8 +614,11 @@ fn build_catalog(args: &ArgMatches<'_>) -> Result<()> {
     let pretty = args.is_present("pretty");
     let output_file_path = args.value_of("output").unwrap();
 
-    let catalog_result =
-        CatalogBuilder::new().build_catalog_for_directory(dir_path, FileType::Chorddown, true)?;
+    let catalog_result = CatalogBuilder::new().build_catalog_for_directory_with_file_types(
+        dir_path,
+        &[FileType::Chorddown, FileType::ChordPro],
+        true,
+    )?;
 
     let serialization_result = if pretty {
         serde_json::to_string_pretty(&catalog_result.catalog)
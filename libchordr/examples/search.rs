@@ -2,9 +2,7 @@ use std::env;
 use std::path::PathBuf;
 use std::rc::Rc;
 
-use libchordr::prelude::{
-    CatalogBuilder, FileType, ListEntryTrait, SearchIndex, SongData, SongSorting,
-};
+use libchordr::prelude::{CatalogBuilder, FileType, ListEntryTrait, SearchIndex, SongData};
 
 fn main() {
     let args = env::args().collect::<Vec<String>>();
@@ -29,7 +27,7 @@ fn main() {
     if verbose {
         println!("{:?}", search_results)
     } else {
-        for song in search_results.sort_by_title() {
+        for song in search_results {
             println!("{} (ID: '{}')", song.title(), song.id())
         }
     }
@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use std::hash::Hasher;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::helper::ContentHasher;
+use crate::models::song::Song;
+
+/// Cache of the Songs built by an incremental build of the [`CatalogBuilder`]
+///
+/// The cache can be serialized to persist it between multiple runs
+///
+/// [`CatalogBuilder`]: super::CatalogBuilder
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct CatalogCache {
+    entries: HashMap<PathBuf, CacheEntry>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub(super) struct CacheEntry {
+    pub(super) modified: Option<SystemTime>,
+    pub(super) size: u64,
+    pub(super) content_hash: u64,
+    pub(super) song: Song,
+}
+
+impl CatalogCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Remove all entries (the next incremental build will parse all files)
+    pub fn clear(&mut self) {
+        self.entries.clear()
+    }
+
+    pub(super) fn get(&self, path: &Path) -> Option<&CacheEntry> {
+        self.entries.get(path)
+    }
+
+    pub(super) fn replace_entries(&mut self, entries: HashMap<PathBuf, CacheEntry>) {
+        self.entries = entries;
+    }
+
+    pub(super) fn take_entries(&mut self) -> HashMap<PathBuf, CacheEntry> {
+        std::mem::take(&mut self.entries)
+    }
+}
+
+/// Hash the file content with the [`ContentHasher`]
+pub(crate) fn content_hash(content: &[u8]) -> u64 {
+    let mut hasher = ContentHasher::default();
+    hasher.write(content);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_hash() {
+        assert_eq!(content_hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(content_hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_ne!(content_hash(b"# Song 1"), content_hash(b"# Song 2"));
+    }
+}
@@ -1,8 +1,10 @@
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::fmt::Debug;
 use std::fs::{self, DirEntry};
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::SystemTime;
 
 use chrono::Local;
 
@@ -11,15 +13,52 @@ use crate::models::catalog::*;
 use crate::models::file_type::FileType;
 use crate::models::list::ListEntryTrait;
 use crate::models::song::Song;
+use crate::models::song_id::SongId;
+
+use self::catalog_cache::{content_hash, CacheEntry};
+use self::song_from_dir_entry::song_from_source;
 
 pub use self::catalog_build_error::CatalogBuildError;
+pub use self::catalog_cache::CatalogCache;
 
 mod catalog_build_error;
+mod catalog_cache;
 mod song_from_dir_entry;
 
 pub struct CatalogBuildResult {
     pub catalog: Catalog,
     pub errors: Vec<CatalogBuildError>,
+    /// Songs that were added, changed or removed since the previous build
+    ///
+    /// A full (non-incremental) build reports all Songs as added
+    pub changes: CatalogChanges,
+}
+
+/// IDs of the Songs that changed between two builds
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CatalogChanges {
+    pub added: Vec<SongId>,
+    pub changed: Vec<SongId>,
+    pub removed: Vec<SongId>,
+}
+
+impl CatalogChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// State of a song file compared to its cache entry
+enum FileStatus {
+    /// Modification time and size did not change
+    Unchanged,
+    /// Modification time or size changed, but the content is the same
+    Touched {
+        modified: Option<SystemTime>,
+        size: u64,
+    },
+    /// The file is new or its content changed
+    Changed(Box<CacheEntry>),
 }
 
 /// Catalog Builder provides functions to build a Song Catalog from a given directory
@@ -36,6 +75,19 @@ impl CatalogBuilder {
         path: P,
         file_type: FileType,
         recursive: bool,
+    ) -> Result<CatalogBuildResult> {
+        self.build_catalog_for_directory_with_file_types(path, &[file_type], recursive)
+    }
+
+    /// Build a Catalog from all files in `path` matching one of the given `file_types`
+    ///
+    /// This allows to build a single Catalog for libraries containing e.g. chorddown and
+    /// ChordPro files
+    pub fn build_catalog_for_directory_with_file_types<P: AsRef<Path>>(
+        &self,
+        path: P,
+        file_types: &[FileType],
+        recursive: bool,
     ) -> Result<CatalogBuildResult> {
         let path_ref = path.as_ref();
         if !path_ref.is_dir() {
@@ -46,40 +98,103 @@ impl CatalogBuilder {
         }
 
         let song_files_r: Vec<Result<PathBuf, CatalogBuildError>> =
-            self.collect_song_files(path_ref, file_type, recursive);
+            self.collect_song_files(path_ref, file_types, recursive);
         let (song_file_results, io_errors): (Vec<_>, Vec<_>) = partition_results(song_files_r);
-        let song_results = self.build_songs_for_file_list(song_file_results);
+        let song_results = map_files(song_file_results, |e| Song::try_from(e.as_path()));
         let (songs, mut parse_errors) = self.partition_songs(song_results);
 
         parse_errors.extend(io_errors.into_iter());
 
+        let changes = CatalogChanges {
+            added: songs.iter().map(|s| s.id()).collect(),
+            ..CatalogChanges::default()
+        };
+
         Ok(CatalogBuildResult {
-            catalog: Catalog::new(Local::now().to_rfc2822(), songs),
+            catalog: Catalog::from_songs(songs).with_built_at(Local::now().to_rfc2822()),
             errors: parse_errors,
+            changes,
         })
     }
 
-    #[cfg(not(feature = "parallel_catalog_builder"))]
-    fn build_songs_for_file_list(
+    /// Build a Catalog from all files in `path` only parsing the files that changed since the
+    /// last build with the same `cache`
+    ///
+    /// Files are considered unchanged if their modification time and size match the cache
+    /// entry. Otherwise the content hash is compared before the file is parsed again. The result
+    /// contains the IDs of the Songs that were added, changed or removed (including Songs that
+    /// could not be parsed anymore)
+    pub fn build_catalog_for_directory_incremental<P: AsRef<Path>>(
         &self,
-        song_file_results: Vec<PathBuf>,
-    ) -> Vec<Result<Song, CatalogBuildError>> {
-        song_file_results
-            .into_iter()
-            .map(|e| Song::try_from(e.as_path()))
-            .collect()
-    }
+        path: P,
+        file_types: &[FileType],
+        recursive: bool,
+        cache: &mut CatalogCache,
+    ) -> Result<CatalogBuildResult> {
+        let path_ref = path.as_ref();
+        if !path_ref.is_dir() {
+            return Err(Error::catalog_builder_fatal_error(
+                "Given path is not a directory",
+                path_ref.to_path_buf(),
+            ));
+        }
 
-    #[cfg(feature = "parallel_catalog_builder")]
-    fn build_songs_for_file_list(
-        &self,
-        song_file_results: Vec<PathBuf>,
-    ) -> Vec<Result<Song, CatalogBuildError>> {
-        use rayon::prelude::*;
-        song_file_results
-            .into_par_iter()
-            .map(|e| Song::try_from(e.as_path()))
-            .collect()
+        let song_files_r = self.collect_song_files(path_ref, file_types, recursive);
+        let (song_files, mut errors): (Vec<_>, Vec<_>) = partition_results(song_files_r);
+        let statuses = {
+            let cache: &CatalogCache = cache;
+            map_files(song_files, |path| {
+                let status = check_file(path.as_path(), cache.get(path.as_path()));
+                (path, status)
+            })
+        };
+
+        let mut previous_entries = cache.take_entries();
+        let mut entries = HashMap::with_capacity(statuses.len());
+        let mut changes = CatalogChanges::default();
+        for (path, status) in statuses {
+            let previous_entry = previous_entries.remove(&path);
+            let entry = match (status, previous_entry) {
+                (Ok(FileStatus::Unchanged), Some(previous_entry)) => previous_entry,
+                (Ok(FileStatus::Touched { modified, size }), Some(previous_entry)) => CacheEntry {
+                    modified,
+                    size,
+                    ..previous_entry
+                },
+                (Ok(FileStatus::Changed(entry)), previous_entry) => {
+                    match previous_entry {
+                        Some(_) => changes.changed.push(entry.song.id()),
+                        None => changes.added.push(entry.song.id()),
+                    }
+                    *entry
+                }
+                (Ok(_), None) => unreachable!("Unchanged files must have a cache entry"),
+                (Err(error), previous_entry) => {
+                    if let Some(previous_entry) = previous_entry {
+                        changes.removed.push(previous_entry.song.id());
+                    }
+                    errors.push(error);
+                    continue;
+                }
+            };
+            entries.insert(path, entry);
+        }
+        changes
+            .removed
+            .extend(previous_entries.into_values().map(|entry| entry.song.id()));
+        changes.added.sort();
+        changes.changed.sort();
+        changes.removed.sort();
+
+        let mut songs: Vec<Song> = entries.values().map(|entry| entry.song.clone()).collect();
+        songs.sort_by_key(|a| a.id());
+        cache.replace_entries(entries);
+
+        Ok(CatalogBuildResult {
+            catalog: Catalog::from_songs(songs).with_built_at(Local::now().to_rfc2822()),
+            errors,
+            changes,
+        })
     }
 
     fn partition_songs(
@@ -93,10 +208,10 @@ impl CatalogBuilder {
         (songs, errors)
     }
 
-    fn collect_song_files(
+    pub(crate) fn collect_song_files(
         &self,
         path: &Path,
-        file_type: FileType,
+        file_types: &[FileType],
         recursive: bool,
     ) -> Vec<Result<PathBuf, CatalogBuildError>> {
         if !path.is_dir() {
@@ -112,7 +227,7 @@ impl CatalogBuilder {
         for entry in entry_iterator {
             match entry {
                 Ok(entry) => {
-                    songs.append(&mut self.collect_songs_of_entry(entry, file_type, recursive))
+                    songs.append(&mut self.collect_songs_of_entry(entry, file_types, recursive))
                 }
                 Err(error) => songs.push(Err(CatalogBuildError::from_error(error, path))),
             }
@@ -123,20 +238,20 @@ impl CatalogBuilder {
     fn collect_songs_of_entry(
         &self,
         entry: DirEntry,
-        file_type: FileType,
+        file_types: &[FileType],
         recursive: bool,
     ) -> Vec<Result<PathBuf, CatalogBuildError>> {
         let path = entry.path();
         if path.is_file() {
-            if file_type.dir_entry_matches(&entry) {
+            if file_types.iter().any(|t| t.dir_entry_matches(&entry)) {
                 vec![Ok(entry.path())]
             } else {
-                // This is **not** an error situation. If `entry` is not of `file_type` skip the entry
+                // This is **not** an error situation. If `entry` is not of `file_types` skip the entry
                 vec![]
             }
         } else if path.is_dir() {
             if recursive {
-                self.collect_song_files(path.as_path(), file_type, recursive)
+                self.collect_song_files(path.as_path(), file_types, recursive)
             } else {
                 vec![]
             }
@@ -149,6 +264,49 @@ impl CatalogBuilder {
     }
 }
 
+/// Compare the file at `path` with its cache entry and parse it if it changed
+fn check_file(path: &Path, cached: Option<&CacheEntry>) -> Result<FileStatus, CatalogBuildError> {
+    let metadata = fs::metadata(path).map_err(|e| CatalogBuildError::from_error(e, path))?;
+    let modified = metadata.modified().ok();
+    let size = metadata.len();
+    if let Some(cached) = cached {
+        if modified.is_some() && cached.modified == modified && cached.size == size {
+            return Ok(FileStatus::Unchanged);
+        }
+    }
+
+    let src = fs::read_to_string(path).map_err(|e| CatalogBuildError::from_error(e, path))?;
+    let content_hash = content_hash(src.as_bytes());
+    if cached.is_some_and(|cached| cached.content_hash == content_hash) {
+        return Ok(FileStatus::Touched { modified, size });
+    }
+
+    Ok(FileStatus::Changed(Box::new(CacheEntry {
+        modified,
+        size,
+        content_hash,
+        song: song_from_source(path, src)?,
+    })))
+}
+
+#[cfg(not(feature = "parallel_catalog_builder"))]
+fn map_files<T, F>(files: Vec<PathBuf>, f: F) -> Vec<T>
+where
+    F: Fn(PathBuf) -> T,
+{
+    files.into_iter().map(f).collect()
+}
+
+#[cfg(feature = "parallel_catalog_builder")]
+fn map_files<T, F>(files: Vec<PathBuf>, f: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(PathBuf) -> T + Sync + Send,
+{
+    use rayon::prelude::*;
+    files.into_par_iter().map(f).collect()
+}
+
 pub fn partition_results<T: Debug, E: Debug>(results: Vec<Result<T, E>>) -> (Vec<T>, Vec<E>) {
     let mut left: Vec<T> = Vec::with_capacity(results.len() / 2);
     let mut right: Vec<E> = Vec::with_capacity(results.len() / 2);
@@ -242,4 +400,110 @@ mod tests {
         let song = catalog.get(song_id).unwrap();
         assert_eq!(SongId::new(song_id), song.id());
     }
+
+    #[test]
+    fn test_build_catalog_for_test_directory_with_file_types() {
+        let songs_dir = format!("{}/tests/resources", env!("CARGO_MANIFEST_DIR"));
+        let songs_dir = Path::new(&songs_dir);
+        let result = CatalogBuilder::new().build_catalog_for_directory_with_file_types(
+            songs_dir,
+            &[FileType::Chorddown, FileType::ChordPro],
+            false,
+        );
+        assert!(result.is_ok());
+        let catalog_and_errors = result.unwrap();
+        assert!(catalog_and_errors.errors.is_empty());
+        let catalog = catalog_and_errors.catalog;
+        assert_eq!(4, catalog.len());
+
+        let song_id = "swing_low_sweet_chariot.cho";
+        assert!(catalog.contains_id(song_id));
+        let song = catalog.get(song_id).unwrap();
+        assert_eq!("Swing Low Sweet Chariot", song.title());
+        assert_eq!(FileType::ChordPro, song.file_type());
+    }
+
+    #[test]
+    fn test_build_catalog_for_directory_reports_all_songs_as_added() {
+        let songs_dir = format!("{}/tests/resources", env!("CARGO_MANIFEST_DIR"));
+        let result = CatalogBuilder::new()
+            .build_catalog_for_directory(Path::new(&songs_dir), FileType::Chorddown, false)
+            .unwrap();
+
+        assert_eq!(result.changes.added.len(), 3);
+        assert!(result.changes.changed.is_empty());
+        assert!(result.changes.removed.is_empty());
+    }
+
+    #[test]
+    fn test_build_catalog_for_directory_incremental() {
+        let songs_dir = std::env::temp_dir().join(format!(
+            "chordr-test-incremental-catalog-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&songs_dir);
+        fs::create_dir_all(songs_dir.join("sub")).unwrap();
+        fs::write(songs_dir.join("song-a.chorddown"), "# Song A\n[C]La").unwrap();
+        fs::write(songs_dir.join("sub/song-b.chorddown"), "# Song B\n[G]La").unwrap();
+
+        let builder = CatalogBuilder::new();
+        let mut cache = CatalogCache::new();
+        let build = |cache: &mut CatalogCache| {
+            builder
+                .build_catalog_for_directory_incremental(
+                    &songs_dir,
+                    &[FileType::Chorddown],
+                    true,
+                    cache,
+                )
+                .unwrap()
+        };
+        let ids = |ids: &[&str]| ids.iter().map(|id| SongId::new(*id)).collect::<Vec<_>>();
+
+        let result = build(&mut cache);
+        assert_eq!(result.catalog.len(), 2);
+        assert_eq!(
+            result.changes.added,
+            ids(&["song-a.chorddown", "song-b.chorddown"])
+        );
+        assert_eq!(cache.len(), 2);
+        assert!(result.catalog.built_at().is_some());
+        let revision = result.catalog.revision();
+
+        // Nothing changed
+        let result = build(&mut cache);
+        assert_eq!(result.catalog.len(), 2);
+        assert!(result.changes.is_empty());
+        assert_eq!(result.catalog.revision(), revision);
+
+        // Same content written again
+        fs::write(songs_dir.join("song-a.chorddown"), "# Song A\n[C]La").unwrap();
+        assert!(build(&mut cache).changes.is_empty());
+
+        fs::write(
+            songs_dir.join("song-a.chorddown"),
+            "# Song A (new)\n[C]La la",
+        )
+        .unwrap();
+        fs::remove_file(songs_dir.join("sub/song-b.chorddown")).unwrap();
+        fs::write(songs_dir.join("song-c.chorddown"), "# Song C\n[D]La").unwrap();
+        let result = build(&mut cache);
+        assert_eq!(result.changes.added, ids(&["song-c.chorddown"]));
+        assert_eq!(result.changes.changed, ids(&["song-a.chorddown"]));
+        assert_eq!(result.changes.removed, ids(&["song-b.chorddown"]));
+        assert_eq!(result.catalog.len(), 2);
+        assert_ne!(result.catalog.revision(), revision);
+        assert_eq!(
+            result.catalog.get("song-a.chorddown").unwrap().title(),
+            "Song A (new)"
+        );
+
+        // A new cache builds everything again
+        let revision = result.catalog.revision();
+        let result = build(&mut CatalogCache::new());
+        assert_eq!(result.changes.added.len(), 2);
+        assert_eq!(result.catalog.revision(), revision);
+
+        fs::remove_dir_all(&songs_dir).unwrap();
+    }
 }
@@ -3,7 +3,8 @@ use std::fs;
 use std::fs::DirEntry;
 use std::path::Path;
 
-use crate::helper::parse_content;
+use crate::helper::parse_content_for_file_type;
+use crate::key_detector::KeyDetector;
 use crate::models::file_type::FileType;
 use crate::models::song::Song;
 use crate::models::song_id::SongId;
@@ -28,30 +29,37 @@ impl TryFrom<&Path> for Song {
             Err(e) => return Err(CatalogBuildError::from_error(e, path_buf)),
         };
 
-        let song_id = SongId::from(path);
-        let parser_result = match parse_content(src.as_bytes()) {
-            Ok(p) => p,
-            Err(e) => return Err(CatalogBuildError::from_error(e, path_buf)),
-        };
-        let title = parser_result
-            .meta()
-            .title
-            .unwrap_or_else(|| song_id.to_string());
-        let file_type = match FileType::try_from(path) {
-            Ok(f) => f,
-            Err(e) => return Err(CatalogBuildError::from_error(e, path_buf)),
-        };
-        //        let meta = SongMeta::new(song_id, title, file_type);
-        let meta = SongMeta::new_with_meta_information(
-            song_id,
-            title,
-            file_type,
-            parser_result.meta_as_ref(),
-        );
-        Ok(Song::new(meta, src))
+        song_from_source(path, src)
     }
 }
 
+/// Build the Song from the content `src` of the file at `path`
+pub(super) fn song_from_source(path: &Path, src: String) -> Result<Song, CatalogBuildError> {
+    let path_buf = path.to_path_buf();
+    let song_id = SongId::from(path);
+    let file_type = match FileType::try_from(path) {
+        Ok(f) => f,
+        Err(e) => return Err(CatalogBuildError::from_error(e, path_buf)),
+    };
+    let parser_result = match parse_content_for_file_type(src.as_bytes(), file_type) {
+        Ok(p) => p,
+        Err(e) => return Err(CatalogBuildError::from_error(e, path_buf)),
+    };
+    let title = parser_result
+        .meta()
+        .title
+        .unwrap_or_else(|| song_id.to_string());
+    // Only estimate the key if the song does not define it
+    let detected_key = match parser_result.meta_as_ref().key {
+        Some(_) => None,
+        None => KeyDetector::new().detect_key(parser_result.node_as_ref()),
+    };
+    let meta =
+        SongMeta::new_with_meta_information(song_id, title, file_type, parser_result.meta_as_ref())
+            .with_detected_key(detected_key);
+    Ok(Song::new(meta, src))
+}
+
 impl TryFrom<DirEntry> for Song {
     type Error = CatalogBuildError;
 
@@ -62,6 +70,7 @@ impl TryFrom<DirEntry> for Song {
 
 #[cfg(test)]
 mod tests {
+    use crate::models::chord::{Chord, Note};
     use crate::models::list::ListEntryTrait;
     use crate::models::song_data::SongData;
 
@@ -82,4 +91,32 @@ mod tests {
         assert_eq!(FileType::Chorddown, song.file_type());
         assert!(!song.src().is_empty());
     }
+
+    #[test]
+    fn test_try_from_chord_pro() {
+        let song_path = format!(
+            "{}/tests/resources/swing_low_sweet_chariot.cho",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let song_path = Path::new(&song_path);
+        let result = Song::try_from(song_path);
+        assert!(result.is_ok(), "{:?}", result.unwrap_err());
+        let song = result.unwrap();
+        assert_eq!(SongId::new("swing_low_sweet_chariot.cho"), song.id());
+        assert_eq!("Swing Low Sweet Chariot", &song.title());
+        assert_eq!(FileType::ChordPro, song.file_type());
+    }
+
+    #[test]
+    fn test_try_from_detects_key() {
+        let song_path = format!(
+            "{}/tests/resources/swing_low_sweet_chariot.cho",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let song = Song::try_from(Path::new(&song_path)).unwrap();
+        let detected_key = song.meta().detected_key().unwrap();
+
+        assert_eq!(detected_key.key, Chord::new_without_variant(Note::D));
+        assert!(detected_key.confidence > 0.0);
+    }
 }
@@ -0,0 +1,197 @@
+use crate::models::chord::{ChordStructure, Note};
+use crate::modification::transposition::TransposableTrait;
+
+use super::instrument::Instrument;
+
+/// Number of frets a hand can span
+const FRET_SPAN: u8 = 4;
+/// Highest fret at which a shape may start
+const MAX_BASE_FRET: u8 = 9;
+/// Number of fingers available to fret the strings
+const MAX_FINGERS: usize = 4;
+/// Interval of the perfect fifth, which may be omitted from a shape
+const PERFECT_FIFTH: u8 = 7;
+
+/// Search the easiest playable shape for the chord on the given strings
+///
+/// Shapes are ranked by their position on the neck, the number of muted strings and the number
+/// of fingers required. On instruments with a bass string the lowest sounding note must be the
+/// chord's bass note (or the root if no bass is given)
+pub(crate) fn find_shape(
+    structure: &ChordStructure,
+    strings: &[Note],
+    instrument: Instrument,
+) -> Option<Vec<Option<u8>>> {
+    let root = structure.root();
+    let intervals = structure.intervals();
+    let tones: Vec<i32> = intervals.iter().map(|i| pitch_class(root, *i)).collect();
+    let mut required: Vec<i32> = intervals
+        .iter()
+        .filter(|i| **i != PERFECT_FIFTH)
+        .map(|i| pitch_class(root, *i))
+        .collect();
+    let bass = structure.bass().map(i32::from);
+    if let Some(bass) = bass {
+        if !required.contains(&bass) {
+            required.push(bass);
+        }
+    }
+    // Drop the root if there are not enough strings to play all required tones
+    if required.len() > strings.len() {
+        required.retain(|t| *t != i32::from(root) || Some(*t) == bass);
+    }
+    if required.len() > strings.len() {
+        return None;
+    }
+    let lowest_note = if instrument.has_bass_string() {
+        Some(bass.unwrap_or_else(|| i32::from(root)))
+    } else {
+        None
+    };
+
+    let search = Search {
+        strings,
+        tones: &tones,
+        required: &required,
+        lowest_note,
+        max_muted: instrument.max_muted_strings(),
+        fifth: pitch_class(root, PERFECT_FIFTH),
+    };
+
+    let mut best: Option<(u32, Vec<Option<u8>>)> = None;
+    for base in 1..=MAX_BASE_FRET {
+        let mut shape = Vec::with_capacity(strings.len());
+        search.visit(base, &mut shape, &mut best);
+    }
+
+    best.map(|(_, shape)| shape)
+}
+
+fn pitch_class(root: Note, interval: u8) -> i32 {
+    i32::from(root.transpose(interval as isize))
+}
+
+struct Search<'a> {
+    strings: &'a [Note],
+    tones: &'a [i32],
+    required: &'a [i32],
+    lowest_note: Option<i32>,
+    max_muted: usize,
+    fifth: i32,
+}
+
+impl<'a> Search<'a> {
+    /// Recursively build all shapes within the frets `base..base + FRET_SPAN`
+    fn visit(
+        &self,
+        base: u8,
+        shape: &mut Vec<Option<u8>>,
+        best: &mut Option<(u32, Vec<Option<u8>>)>,
+    ) {
+        let index = shape.len();
+        if index == self.strings.len() {
+            if let Some(score) = self.score(shape) {
+                if best.as_ref().is_none_or(|(s, _)| score < *s) {
+                    *best = Some((score, shape.clone()));
+                }
+            }
+            return;
+        }
+
+        let string = self.strings[index];
+        let all_muted_so_far = shape.iter().all(Option::is_none);
+        // Strings may only be muted below the first played string
+        if all_muted_so_far && index < self.max_muted {
+            shape.push(None);
+            self.visit(base, shape, best);
+            shape.pop();
+        }
+
+        let frets = std::iter::once(0).chain(base..base + FRET_SPAN);
+        for fret in frets {
+            let note = i32::from(string.transpose(fret as isize));
+            if !self.tones.contains(&note) {
+                continue;
+            }
+            if all_muted_so_far && self.lowest_note.is_some_and(|l| l != note) {
+                continue;
+            }
+            shape.push(Some(fret));
+            self.visit(base, shape, best);
+            shape.pop();
+        }
+    }
+
+    /// Return the score of a complete shape (lower is better) or `None` if it is not playable
+    fn score(&self, shape: &[Option<u8>]) -> Option<u32> {
+        let notes: Vec<i32> = self
+            .strings
+            .iter()
+            .zip(shape)
+            .filter_map(|(string, fret)| fret.map(|f| i32::from(string.transpose(f as isize))))
+            .collect();
+        if !self.required.iter().all(|t| notes.contains(t)) {
+            return None;
+        }
+
+        let fretted: Vec<u8> = shape.iter().flatten().copied().filter(|f| *f > 0).collect();
+        let position = fretted.iter().min().copied().unwrap_or(0);
+        // Strings on the lowest fret can be played as a barre with a single finger
+        let barre_strings = fretted.iter().filter(|f| **f == position).count();
+        let fingers = if barre_strings > 1 {
+            fretted.len() - barre_strings + 1
+        } else {
+            fretted.len()
+        };
+        if fingers > MAX_FINGERS {
+            return None;
+        }
+
+        let muted = shape.iter().filter(|f| f.is_none()).count() as u32;
+        let missing_fifth =
+            u32::from(!notes.contains(&self.fifth) && self.tones.contains(&self.fifth));
+
+        Some(position as u32 * 2 + muted * 3 + fingers as u32 + missing_fifth)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::chord::Chords;
+    use crate::models::meta::BNotation;
+
+    use super::*;
+
+    fn shape(chord: &str, instrument: Instrument) -> Option<Vec<Option<u8>>> {
+        let structure = Chords::try_from(chord, BNotation::B)
+            .unwrap()
+            .structure()
+            .unwrap();
+
+        find_shape(&structure, instrument.standard_tuning(), instrument)
+    }
+
+    #[test]
+    fn test_find_shape_guitar() {
+        assert_eq!(
+            shape("C", Instrument::Guitar),
+            Some(vec![None, Some(3), Some(2), Some(0), Some(1), Some(0)])
+        );
+        assert_eq!(
+            shape("Am", Instrument::Guitar),
+            Some(vec![None, Some(0), Some(2), Some(2), Some(1), Some(0)])
+        );
+        // The bass note must be the lowest sounding note
+        assert_eq!(
+            shape("Bm7/A", Instrument::Guitar),
+            Some(vec![Some(5), Some(0), Some(0), Some(2), Some(0), Some(2)])
+        );
+    }
+
+    #[test]
+    fn test_find_shape_ukulele() {
+        let c = shape("C", Instrument::Ukulele).unwrap();
+        assert_eq!(c, vec![Some(0), Some(0), Some(0), Some(3)]);
+        assert!(shape("C9", Instrument::Ukulele).is_some());
+    }
+}
@@ -0,0 +1,165 @@
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::chord::Note;
+use crate::modification::transposition::TransposableTrait;
+
+/// Fretted instrument to draw chord diagrams for
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Deserialize, Serialize)]
+pub enum Instrument {
+    #[default]
+    Guitar,
+    Ukulele,
+}
+
+impl Instrument {
+    /// Open string notes in standard tuning, ordered from the lowest to the highest string
+    pub fn standard_tuning(&self) -> &'static [Note] {
+        match self {
+            Self::Guitar => &[Note::E, Note::A, Note::D, Note::G, Note::B, Note::E],
+            Self::Ukulele => &[Note::G, Note::C, Note::E, Note::A],
+        }
+    }
+
+    /// Return if the first string is the lowest sounding string
+    ///
+    /// The ukulele's first string is tuned an octave up (re-entrant tuning), so chord shapes
+    /// for it do not need to start with the root or bass note
+    pub fn has_bass_string(&self) -> bool {
+        match self {
+            Self::Guitar => true,
+            Self::Ukulele => false,
+        }
+    }
+
+    /// Maximum number of strings that may be muted in a chord shape
+    pub(crate) fn max_muted_strings(&self) -> usize {
+        match self {
+            Self::Guitar => 2,
+            Self::Ukulele => 0,
+        }
+    }
+}
+
+impl Display for Instrument {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Guitar => f.write_str("Guitar"),
+            Self::Ukulele => f.write_str("Ukulele"),
+        }
+    }
+}
+
+impl TryFrom<&str> for Instrument {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::from_str(value)
+    }
+}
+
+impl FromStr for Instrument {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "guitar" => Ok(Self::Guitar),
+            "ukulele" | "uke" => Ok(Self::Ukulele),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Tuning of the instrument's strings
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Deserialize, Serialize)]
+pub enum Tuning {
+    #[default]
+    Standard,
+    /// All strings tuned down a semitone
+    HalfStepDown,
+    /// All strings tuned down a whole step
+    WholeStepDown,
+    /// The first string tuned down a whole step (e.g. `Drop D` on a guitar)
+    Drop,
+}
+
+impl Tuning {
+    /// Open string notes of the `instrument` in this tuning, ordered from the first string
+    pub fn strings(&self, instrument: Instrument) -> Vec<Note> {
+        let standard = instrument.standard_tuning().iter().copied();
+        match self {
+            Self::Standard => standard.collect(),
+            Self::HalfStepDown => standard.map(|n| n.transpose(-1)).collect(),
+            Self::WholeStepDown => standard.map(|n| n.transpose(-2)).collect(),
+            Self::Drop => standard
+                .enumerate()
+                .map(|(i, n)| if i == 0 { n.transpose(-2) } else { n })
+                .collect(),
+        }
+    }
+}
+
+impl Display for Tuning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Standard => f.write_str("Standard"),
+            Self::HalfStepDown => f.write_str("Half step down"),
+            Self::WholeStepDown => f.write_str("Whole step down"),
+            Self::Drop => f.write_str("Drop"),
+        }
+    }
+}
+
+impl TryFrom<&str> for Tuning {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::from_str(value)
+    }
+}
+
+impl FromStr for Tuning {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().replace(['-', '_', ' '], "").as_str() {
+            "standard" => Ok(Self::Standard),
+            "halfstepdown" => Ok(Self::HalfStepDown),
+            "wholestepdown" => Ok(Self::WholeStepDown),
+            "drop" | "dropd" => Ok(Self::Drop),
+            _ => Err(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strings() {
+        assert_eq!(
+            Tuning::Standard.strings(Instrument::Guitar),
+            vec![Note::E, Note::A, Note::D, Note::G, Note::B, Note::E]
+        );
+        assert_eq!(
+            Tuning::Drop.strings(Instrument::Guitar),
+            vec![Note::D, Note::A, Note::D, Note::G, Note::B, Note::E]
+        );
+        assert_eq!(
+            Tuning::HalfStepDown.strings(Instrument::Ukulele),
+            vec![Note::Fis, Note::B, Note::Dis, Note::Gis]
+        );
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(Instrument::from_str("Ukulele"), Ok(Instrument::Ukulele));
+        assert_eq!(Instrument::from_str("banjo"), Err(()));
+        assert_eq!(Tuning::from_str("drop-d"), Ok(Tuning::Drop));
+        assert_eq!(Tuning::from_str("half step down"), Ok(Tuning::HalfStepDown));
+    }
+}
@@ -0,0 +1,271 @@
+//! Chord diagrams show how to play a chord on a fretted instrument
+//!
+//! Shapes are looked up in a database of common open chords first. Chords not found there (or
+//! instruments in other tunings) are searched by trying all playable fret combinations
+use std::fmt::Write;
+
+use crate::html::escape::Escape;
+use crate::models::chord::fmt::Formatting;
+use crate::models::chord::{Chords, NoteDisplay};
+use crate::models::meta::{BNotation, SemitoneNotation};
+
+pub use self::instrument::{Instrument, Tuning};
+
+mod fingering;
+mod instrument;
+mod shape_database;
+
+/// Number of frets drawn in a diagram
+const DIAGRAM_FRETS: u8 = 5;
+const STRING_SPACING: u32 = 12;
+const FRET_SPACING: u32 = 14;
+const MARGIN_LEFT: u32 = 18;
+const MARGIN_TOP: u32 = 16;
+const MARGIN_RIGHT: u32 = 6;
+const MARGIN_BOTTOM: u32 = 4;
+
+/// Fingering of a chord on a fretted instrument
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChordDiagram {
+    name: String,
+    frets: Vec<Option<u8>>,
+}
+
+impl ChordDiagram {
+    /// Create a new diagram with the frets listed from the first string (`None` for muted strings)
+    pub fn new<S: Into<String>>(name: S, frets: Vec<Option<u8>>) -> Self {
+        Self {
+            name: name.into(),
+            frets,
+        }
+    }
+
+    /// Build the diagram for the chords on the given instrument and tuning
+    ///
+    /// Returns `None` if the chord can not be analyzed or no playable shape was found
+    pub fn for_chords(
+        chords: &Chords,
+        instrument: Instrument,
+        tuning: Tuning,
+        formatting: Formatting,
+    ) -> Option<Self> {
+        let structure = chords.structure().ok()?;
+        let lookup_name = structure.clone().with_bass(None).note_format(Formatting {
+            b_notation: BNotation::B,
+            semitone_notation: SemitoneNotation::Sharp,
+            ..Formatting::default()
+        });
+        let bass_is_root = structure.bass().is_none_or(|b| b == structure.root());
+
+        let database_shape = if tuning == Tuning::Standard && bass_is_root {
+            shape_database::lookup(instrument, &lookup_name)
+        } else {
+            None
+        };
+        let frets = match database_shape {
+            Some(frets) => frets,
+            None => fingering::find_shape(&structure, &tuning.strings(instrument), instrument)?,
+        };
+
+        Some(Self::new(
+            chords.note_format(formatting.with_key(None)),
+            frets,
+        ))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Frets of the strings from the first string (`None` for muted strings)
+    pub fn frets(&self) -> &[Option<u8>] {
+        &self.frets
+    }
+
+    /// Return the fret displayed at the top of the diagram
+    pub fn base_fret(&self) -> u8 {
+        let fretted = self.frets.iter().flatten().filter(|f| **f > 0);
+        match fretted.clone().max() {
+            Some(max) if *max > DIAGRAM_FRETS => *fretted.min().unwrap_or(&1),
+            _ => 1,
+        }
+    }
+
+    /// Render the diagram as SVG image
+    pub fn to_svg(&self) -> String {
+        let string_count = self.frets.len() as u32;
+        let base_fret = self.base_fret();
+        let grid_width = string_count.saturating_sub(1) * STRING_SPACING;
+        let grid_height = DIAGRAM_FRETS as u32 * FRET_SPACING;
+        let width = MARGIN_LEFT + grid_width + MARGIN_RIGHT;
+        let height = MARGIN_TOP + grid_height + MARGIN_BOTTOM;
+        let string_x = |i: u32| MARGIN_LEFT + i * STRING_SPACING;
+
+        let mut svg = format!(
+            r#"<svg class="chordr-chord-diagram-svg" xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" role="img" aria-label="{}">"#,
+            Escape(&self.name),
+            w = width,
+            h = height
+        );
+
+        // Nut or the number of the first fret
+        if base_fret == 1 {
+            let _ = write!(
+                svg,
+                r#"<line x1="{}" y1="{y}" x2="{}" y2="{y}" stroke="currentColor" stroke-width="3"/>"#,
+                string_x(0),
+                string_x(string_count - 1),
+                y = MARGIN_TOP
+            );
+        } else {
+            let _ = write!(
+                svg,
+                r#"<text x="{}" y="{}" font-size="9" text-anchor="end">{}</text>"#,
+                MARGIN_LEFT - 4,
+                MARGIN_TOP + FRET_SPACING / 2 + 3,
+                base_fret
+            );
+        }
+        for fret in 0..=DIAGRAM_FRETS as u32 {
+            let _ = write!(
+                svg,
+                r#"<line x1="{}" y1="{y}" x2="{}" y2="{y}" stroke="currentColor"/>"#,
+                string_x(0),
+                string_x(string_count - 1),
+                y = MARGIN_TOP + fret * FRET_SPACING
+            );
+        }
+        for (i, fret) in self.frets.iter().enumerate() {
+            let x = string_x(i as u32);
+            let _ = write!(
+                svg,
+                r#"<line x1="{x}" y1="{}" x2="{x}" y2="{}" stroke="currentColor"/>"#,
+                MARGIN_TOP,
+                MARGIN_TOP + grid_height,
+                x = x
+            );
+            match fret {
+                None => {
+                    let _ = write!(
+                        svg,
+                        r#"<text x="{}" y="{}" font-size="9" text-anchor="middle">×</text>"#,
+                        x,
+                        MARGIN_TOP - 4
+                    );
+                }
+                Some(0) => {
+                    let _ = write!(
+                        svg,
+                        r#"<circle cx="{}" cy="{}" r="3" fill="none" stroke="currentColor"/>"#,
+                        x,
+                        MARGIN_TOP - 7
+                    );
+                }
+                Some(f) => {
+                    let row = (*f - base_fret) as u32;
+                    let _ = write!(
+                        svg,
+                        r#"<circle cx="{}" cy="{}" r="4" fill="currentColor"/>"#,
+                        x,
+                        MARGIN_TOP + row * FRET_SPACING + FRET_SPACING / 2
+                    );
+                }
+            }
+        }
+        svg.push_str("</svg>");
+
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::format::Format;
+
+    use super::*;
+
+    fn diagram(chord: &str, instrument: Instrument, tuning: Tuning) -> Option<ChordDiagram> {
+        ChordDiagram::for_chords(
+            &Chords::try_from(chord, BNotation::B).unwrap(),
+            instrument,
+            tuning,
+            Formatting::with_format(Format::HTML),
+        )
+    }
+
+    #[test]
+    fn test_for_chords_from_database() {
+        let d = diagram("Bbm", Instrument::Ukulele, Tuning::Standard);
+        assert!(d.is_some());
+
+        let d = diagram("Bb", Instrument::Ukulele, Tuning::Standard).unwrap();
+        assert_eq!(d.name(), "A#");
+        assert_eq!(d.frets(), &[Some(3), Some(2), Some(1), Some(1)]);
+
+        let d = diagram("Am7", Instrument::Guitar, Tuning::Standard).unwrap();
+        assert_eq!(
+            d.frets(),
+            &[None, Some(0), Some(2), Some(0), Some(1), Some(0)]
+        );
+    }
+
+    #[test]
+    fn test_for_chords_slash_chord() {
+        let d = diagram("Bm7/A", Instrument::Guitar, Tuning::Standard).unwrap();
+
+        assert_eq!(d.name(), "Bm7/A");
+        // The lowest string plays the bass note `A`
+        assert_eq!(d.frets()[0], Some(5));
+    }
+
+    #[test]
+    fn test_for_chords_drop_tuning() {
+        let d = diagram("D", Instrument::Guitar, Tuning::Drop).unwrap();
+
+        assert_eq!(d.frets()[0], Some(0));
+    }
+
+    #[test]
+    fn test_for_chords_invalid() {
+        assert!(diagram("C/Em", Instrument::Guitar, Tuning::Standard).is_none());
+    }
+
+    #[test]
+    fn test_base_fret() {
+        assert_eq!(
+            ChordDiagram::new("C", vec![None, Some(3), Some(2), Some(0), Some(1), Some(0)])
+                .base_fret(),
+            1
+        );
+        assert_eq!(
+            ChordDiagram::new("C", vec![None, Some(3), Some(5), Some(5), Some(5), Some(3)])
+                .base_fret(),
+            1
+        );
+        assert_eq!(
+            ChordDiagram::new(
+                "G",
+                vec![None, Some(10), Some(12), Some(12), Some(12), Some(10)]
+            )
+            .base_fret(),
+            10
+        );
+    }
+
+    #[test]
+    fn test_to_svg() {
+        let svg = ChordDiagram::new(
+            "Am",
+            vec![None, Some(0), Some(2), Some(2), Some(1), Some(0)],
+        )
+        .to_svg();
+
+        assert!(svg.starts_with(r#"<svg class="chordr-chord-diagram-svg""#));
+        assert!(svg.ends_with("</svg>"));
+        assert!(svg.contains(r#"aria-label="Am""#));
+        assert_eq!(svg.matches("×").count(), 1);
+        assert_eq!(svg.matches(r#"fill="none""#).count(), 2);
+        assert_eq!(svg.matches(r#"fill="currentColor""#).count(), 3);
+        assert!(svg.contains(r#"stroke-width="3""#));
+    }
+}
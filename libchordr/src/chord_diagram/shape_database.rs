@@ -0,0 +1,147 @@
+use super::instrument::Instrument;
+
+/// Common guitar chord shapes in standard tuning
+///
+/// The frets are listed from the first (lowest) string, `x` marks a muted string
+const GUITAR_SHAPES: &[(&str, &str)] = &[
+    ("C", "x32010"),
+    ("Cmaj7", "x32000"),
+    ("C7", "x32310"),
+    ("Cadd9", "x32030"),
+    ("D", "xx0232"),
+    ("Dm", "xx0231"),
+    ("D7", "xx0212"),
+    ("Dm7", "xx0211"),
+    ("Dmaj7", "xx0222"),
+    ("Dsus2", "xx0230"),
+    ("Dsus4", "xx0233"),
+    ("E", "022100"),
+    ("Em", "022000"),
+    ("E7", "020100"),
+    ("Em7", "022030"),
+    ("Emaj7", "021100"),
+    ("Esus4", "022200"),
+    ("F", "133211"),
+    ("Fmaj7", "xx3210"),
+    ("F#m", "244222"),
+    ("G", "320003"),
+    ("G7", "320001"),
+    ("Gmaj7", "320002"),
+    ("A", "x02220"),
+    ("Am", "x02210"),
+    ("A7", "x02020"),
+    ("Am7", "x02010"),
+    ("Amaj7", "x02120"),
+    ("Asus2", "x02200"),
+    ("Asus4", "x02230"),
+    ("B7", "x21202"),
+    ("Bm", "x24432"),
+    ("Bm7", "x20202"),
+];
+
+/// Common ukulele chord shapes in standard (`GCEA`) tuning
+const UKULELE_SHAPES: &[(&str, &str)] = &[
+    ("C", "0003"),
+    ("Cm", "0333"),
+    ("C7", "0001"),
+    ("Cmaj7", "0002"),
+    ("D", "2220"),
+    ("Dm", "2210"),
+    ("D7", "2223"),
+    ("Dm7", "2213"),
+    ("E", "1402"),
+    ("Em", "0432"),
+    ("E7", "1202"),
+    ("F", "2010"),
+    ("Fm", "1013"),
+    ("F7", "2313"),
+    ("G", "0232"),
+    ("Gm", "0231"),
+    ("G7", "0212"),
+    ("Gmaj7", "0222"),
+    ("A", "2100"),
+    ("Am", "2000"),
+    ("A7", "0100"),
+    ("Am7", "0000"),
+    ("A#", "3211"),
+    ("B", "4322"),
+    ("Bm", "4222"),
+    ("B7", "2322"),
+];
+
+/// Look up the shape of the chord with the given normalized name (sharp notation, e.g. `A#m7`)
+pub(crate) fn lookup(instrument: Instrument, name: &str) -> Option<Vec<Option<u8>>> {
+    let shapes = match instrument {
+        Instrument::Guitar => GUITAR_SHAPES,
+        Instrument::Ukulele => UKULELE_SHAPES,
+    };
+
+    shapes
+        .iter()
+        .find(|(shape_name, _)| *shape_name == name)
+        .map(|(_, frets)| parse_frets(frets))
+}
+
+fn parse_frets(frets: &str) -> Vec<Option<u8>> {
+    frets
+        .chars()
+        .map(|c| c.to_digit(10).map(|f| f as u8))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::chord::fmt::Formatting;
+    use crate::models::chord::{ChordStructure, NoteDisplay};
+    use crate::models::meta::BNotation;
+    use crate::modification::transposition::TransposableTrait;
+
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        assert_eq!(
+            lookup(Instrument::Guitar, "C"),
+            Some(vec![None, Some(3), Some(2), Some(0), Some(1), Some(0)])
+        );
+        assert_eq!(
+            lookup(Instrument::Ukulele, "C"),
+            Some(vec![Some(0), Some(0), Some(0), Some(3)])
+        );
+        assert_eq!(lookup(Instrument::Guitar, "C13"), None);
+    }
+
+    /// Verify that all shapes of the database only contain the chord's tones
+    #[test]
+    fn test_shapes_match_chords() {
+        for (instrument, shapes) in [
+            (Instrument::Guitar, GUITAR_SHAPES),
+            (Instrument::Ukulele, UKULELE_SHAPES),
+        ] {
+            let strings = instrument.standard_tuning();
+            for (name, frets) in shapes {
+                let chord = crate::models::chord::Chord::try_from(name, BNotation::B).unwrap();
+                let structure = ChordStructure::try_from_chord(&chord).unwrap();
+                assert_eq!(
+                    &structure.note_format(Formatting::default()),
+                    name,
+                    "Chord name {} is not normalized",
+                    name
+                );
+                let tones: Vec<i32> = structure
+                    .intervals()
+                    .iter()
+                    .map(|i| i32::from(structure.root().transpose(*i as isize)))
+                    .collect();
+                let frets = parse_frets(frets);
+                assert_eq!(frets.len(), strings.len(), "Invalid shape for {}", name);
+                for (string, fret) in strings.iter().zip(frets) {
+                    if let Some(fret) = fret {
+                        let note = i32::from(string.transpose(fret as isize));
+                        assert!(tones.contains(&note), "Invalid note in shape for {}", name);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use crate::models::chord::fmt::{Formatting, NoteDisplay};
+use crate::models::meta::MetaTrait;
+use crate::modification::transposition::TransposableTrait;
+
+/// Helper to render chords according to the capo settings of a `Formatting`
+///
+/// With a capo on fret `n` the guitarist plays the chord shapes `n` semitones below the
+/// concert-pitch chords written in the song
+pub(crate) struct CapoRenderer {
+    fret: Option<u8>,
+    show_concert_pitch: bool,
+    formatting: Formatting,
+}
+
+impl CapoRenderer {
+    pub fn new(meta: &dyn MetaTrait, formatting: Formatting) -> Self {
+        match formatting.capo {
+            Some(capo) => Self {
+                fret: capo.effective_fret(meta.capo().as_deref()),
+                show_concert_pitch: capo.show_concert_pitch,
+                formatting,
+            },
+            None => Self {
+                fret: None,
+                show_concert_pitch: false,
+                formatting,
+            },
+        }
+    }
+
+    /// Return if the chords are rendered as the shapes to play with a capo
+    pub fn is_active(&self) -> bool {
+        self.fret.is_some()
+    }
+
+    /// Return the chord shapes to play (the chords themselves if no capo is used)
+    pub fn shape<T: NoteDisplay + TransposableTrait + Clone>(&self, chords: &T) -> String {
+        match self.fret {
+            Some(fret) => {
+                let semitones = -(fret as isize);
+                // The key must be transposed as well to keep relative chord notations intact
+                let formatting = self
+                    .formatting
+                    .with_key(self.formatting.key.map(|key| key.transpose(semitones)));
+
+                chords.clone().transpose(semitones).note_format(formatting)
+            }
+            None => chords.note_format(self.formatting),
+        }
+    }
+
+    /// Return the chords transposed to the shapes to play (the chords themselves if no capo is used)
+    pub fn shape_chords<T: TransposableTrait + Clone>(&self, chords: &T) -> T {
+        match self.fret {
+            Some(fret) => chords.clone().transpose(-(fret as isize)),
+            None => chords.clone(),
+        }
+    }
+
+    /// Return the concert-pitch chords if they should be displayed alongside the shapes
+    pub fn concert_pitch<T: NoteDisplay>(&self, chords: &T) -> Option<String> {
+        if self.fret.is_some() && self.show_concert_pitch {
+            Some(chords.note_format(self.formatting))
+        } else {
+            None
+        }
+    }
+
+    /// Return the chord shapes followed by the concert-pitch chords in parentheses (e.g. `G (A)`)
+    pub fn format<T: NoteDisplay + TransposableTrait + Clone>(&self, chords: &T) -> String {
+        match self.concert_pitch(chords) {
+            Some(concert_pitch) => format!("{} ({})", self.shape(chords), concert_pitch),
+            None => self.shape(chords),
+        }
+    }
+}
+
+/// Source of a song in A played with a capo on the 2nd fret (the chord shapes are in G)
+#[cfg(test)]
+pub(super) const TEST_SONG_W_CAPO: &str =
+    "# Song\nKey: A\nCapo: 2\n\n## Verse\n[A]Swing [E7/G#]low\n";
+
+/// Convert [`TEST_SONG_W_CAPO`] to `format` with the given capo settings
+#[cfg(test)]
+pub(super) fn convert_test_song_w_capo(
+    format: crate::format::Format,
+    capo: Option<crate::models::chord::fmt::CapoFormatting>,
+) -> String {
+    use crate::converter::Converter;
+
+    let result = crate::helper::parse_content(TEST_SONG_W_CAPO.as_bytes()).unwrap();
+    Converter::get_converter(format)
+        .convert(
+            result.node_as_ref(),
+            result.meta_as_ref(),
+            Formatting {
+                capo,
+                ..Formatting::with_format(format)
+            },
+        )
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::chord::fmt::CapoFormatting;
+    use crate::models::chord::{Chord, Chords};
+    use crate::models::meta::BNotation;
+    use crate::parser::MetaInformation;
+
+    use super::*;
+
+    fn formatting(capo: Option<CapoFormatting>) -> Formatting {
+        Formatting {
+            capo,
+            ..Formatting::default()
+        }
+    }
+
+    fn meta_with_capo(capo: &str) -> MetaInformation {
+        MetaInformation {
+            capo: Some(capo.to_owned()),
+            ..MetaInformation::default()
+        }
+    }
+
+    #[test]
+    fn test_without_capo() {
+        let renderer = CapoRenderer::new(&meta_with_capo("2"), formatting(None));
+        let chord = Chord::try_from("A7", BNotation::B).unwrap();
+
+        assert!(!renderer.is_active());
+        assert_eq!(renderer.shape(&chord), "A7");
+        assert_eq!(renderer.concert_pitch(&chord), None);
+        assert_eq!(renderer.format(&chord), "A7");
+    }
+
+    #[test]
+    fn test_capo_from_meta() {
+        let renderer = CapoRenderer::new(
+            &meta_with_capo("2"),
+            formatting(Some(CapoFormatting::new(None, false))),
+        );
+        let chords = Chords::try_from("A7/C#", BNotation::B).unwrap();
+
+        assert!(renderer.is_active());
+        assert_eq!(renderer.shape(&chords), "G7/B");
+        assert_eq!(renderer.concert_pitch(&chords), None);
+        assert_eq!(renderer.format(&chords), "G7/B");
+    }
+
+    #[test]
+    fn test_capo_from_formatting_w_concert_pitch() {
+        let renderer = CapoRenderer::new(
+            &meta_with_capo("2"),
+            formatting(Some(CapoFormatting::new(Some(3), true))),
+        );
+        let chord = Chord::try_from("C", BNotation::B).unwrap();
+
+        assert_eq!(renderer.shape(&chord), "A");
+        assert_eq!(renderer.concert_pitch(&chord), Some("C".to_owned()));
+        assert_eq!(renderer.format(&chord), "A (C)");
+    }
+
+    #[test]
+    fn test_capo_without_fret() {
+        let renderer = CapoRenderer::new(
+            &MetaInformation::default(),
+            formatting(Some(CapoFormatting::new(None, true))),
+        );
+        let chord = Chord::try_from("C", BNotation::B).unwrap();
+
+        assert_eq!(renderer.format(&chord), "C");
+    }
+
+    #[test]
+    fn test_capo_w_relative_notation() {
+        let formatting = Formatting {
+            chord_notation: crate::models::meta::ChordNotation::Nashville,
+            ..formatting(Some(CapoFormatting::new(None, true)))
+        }
+        .with_key(Some(crate::models::chord::Note::A));
+        let renderer = CapoRenderer::new(&meta_with_capo("2"), formatting);
+        let chord = Chord::try_from("E7", BNotation::B).unwrap();
+
+        assert_eq!(renderer.format(&chord), "57 (57)");
+    }
+}
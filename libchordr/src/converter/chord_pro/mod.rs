@@ -0,0 +1,396 @@
+use crate::error::Result;
+use crate::models::chord::fmt::*;
+use crate::models::chord::Chords;
+use crate::models::meta::{ChordNotation, MetaTrait};
+use crate::models::structure::{ReferenceResolver, Section, SectionProvider, SectionType};
+use crate::parser::Node;
+use crate::tokenizer::Token;
+
+use super::ConverterTrait;
+
+/// Converter to build [ChordPro](https://www.chordpro.org/) files
+///
+/// The output can be read by applications like OnSong or SongbookPro
+///
+/// References to a chorus (`> Chorus`) are written as `{chorus}` directives, all other quotes
+/// become `{comment}` directives. Because Chorddown only knows quotes, a `{comment}` naming a
+/// chorus is written as `{chorus}` as well
+pub struct ChordProConverter {}
+
+impl ConverterTrait for ChordProConverter {
+    fn convert(&self, node: &Node, meta: &dyn MetaTrait, formatting: Formatting) -> Result<String> {
+        // ChordPro readers only understand letter chords
+        let formatting = Formatting {
+            chord_notation: ChordNotation::Letter,
+            ..formatting
+        };
+        let sections = node.get_sections();
+        let output = format!(
+            "{}\n\n{}",
+            self.build_meta(meta, formatting),
+            self.build_node(node, &sections, formatting)?
+        );
+        Ok(cleanup_output(&output))
+    }
+}
+
+impl ChordProConverter {
+    fn build_node(
+        &self,
+        node: &Node,
+        sections: &[Section],
+        formatting: Formatting,
+    ) -> Result<String> {
+        match node {
+            Node::ChordTextPair {
+                chords,
+                text,
+                last_in_line: _,
+                span: _,
+            } => Ok(format!(
+                "{}{}",
+                self.build_string_for_chords(chords, formatting),
+                self.build_token(text),
+            )),
+            Node::ChordStandalone(chords, _) => {
+                Ok(self.build_string_for_chords(chords, formatting))
+            }
+            Node::Text(text) => Ok(self.build_token(text)),
+            Node::Document(children) => {
+                Ok(self.build_string_for_children(children, sections, formatting))
+            }
+            Node::Headline(_) => {
+                // Headlines are rendered as part of the section directives
+                Ok(String::new())
+            }
+            Node::Quote(token) => Ok(self.build_token(token)),
+            Node::Meta(_) => {
+                // Metadata must have already been rendered
+                Ok(String::new())
+            }
+            Node::Newline => Ok("\n".to_owned()),
+            Node::Section {
+                head,
+                children,
+                section_type,
+                span: _,
+            } => match self.build_chorus_reference(node, sections) {
+                Some(directive) => Ok(directive),
+                None => self.build_section(head, children, *section_type, sections, formatting),
+            },
+        }
+    }
+
+    fn build_section(
+        &self,
+        head: &Node,
+        children: &[Node],
+        section_type: SectionType,
+        sections: &[Section],
+        formatting: Formatting,
+    ) -> Result<String> {
+        let content = self.build_string_for_children(children, sections, formatting);
+        let (level, label) = match head {
+            Node::Headline(Token::Headline { level, text, .. }) => (*level, text.as_str()),
+            _ => {
+                return Ok(format!(
+                    "{}{}",
+                    self.build_node(head, sections, formatting)?,
+                    content
+                ))
+            }
+        };
+
+        // The title has already been rendered as part of the metadata
+        if level == 1 {
+            return Ok(content);
+        }
+
+        let (start, end) = match section_type {
+            SectionType::Chorus => ("start_of_chorus", "end_of_chorus"),
+            SectionType::Bridge => ("start_of_bridge", "end_of_bridge"),
+            SectionType::Verse | SectionType::Reference => ("start_of_verse", "end_of_verse"),
+        };
+
+        Ok(format!(
+            "{{{}: {}}}\n{}\n{{{}}}\n\n",
+            start,
+            label,
+            content.trim_matches('\n'),
+            end
+        ))
+    }
+
+    /// Return the `{chorus}` directive if the Section node is a reference to a chorus
+    ///
+    /// `{chorus}` is read as the reference `> Chorus`, so a reference labeled "Chorus" is always
+    /// written as `{chorus}` (even if the song contains no chorus)
+    fn build_chorus_reference(&self, node: &Node, sections: &[Section]) -> Option<String> {
+        let reference = SectionProvider::get_section(node).filter(|s| s.is_reference)?;
+        if reference.title == "Chorus" {
+            return Some("{chorus}\n".to_owned());
+        }
+
+        ReferenceResolver::new()
+            .resolve_reference(&reference, sections)
+            .filter(|target| target.section_type == SectionType::Chorus)
+            .map(|_| format!("{{chorus: {}}}\n", reference.title))
+    }
+
+    fn build_token(&self, token: &Token) -> String {
+        match token {
+            Token::Literal(c) => c.to_owned(),
+            Token::Quote(c) => format!("{{comment: {}}}\n", c),
+            Token::Headline { .. } => unreachable!(),
+            Token::Chord(_) => unreachable!(),
+            Token::Newline => unreachable!(),
+            Token::Meta(_) => unreachable!(),
+        }
+    }
+
+    fn build_meta(&self, meta: &dyn MetaTrait, formatting: Formatting) -> String {
+        let mut buffer: Vec<String> = vec![];
+        if let Some(v) = meta.title() {
+            buffer.push(format!("{{title: {}}}", v))
+        }
+        if let Some(v) = meta.subtitle() {
+            buffer.push(format!("{{subtitle: {}}}", v))
+        }
+        if let Some(v) = meta.original_title() {
+            buffer.push(format!("{{meta: original-title {}}}", v))
+        }
+        if let Some(v) = meta.alternative_title() {
+            buffer.push(format!("{{meta: alternative-title {}}}", v))
+        }
+        if let Some(v) = meta.artist() {
+            buffer.push(format!("{{artist: {}}}", v))
+        }
+        if let Some(v) = meta.composer() {
+            buffer.push(format!("{{composer: {}}}", v))
+        }
+        if let Some(v) = meta.lyricist() {
+            buffer.push(format!("{{lyricist: {}}}", v))
+        }
+        if let Some(v) = meta.copyright() {
+            buffer.push(format!("{{copyright: {}}}", v))
+        }
+        if let Some(v) = meta.album() {
+            buffer.push(format!("{{album: {}}}", v))
+        }
+        if let Some(v) = meta.year() {
+            buffer.push(format!("{{year: {}}}", v))
+        }
+        if let Some(v) = meta.key() {
+            buffer.push(format!("{{key: {}}}", v.note_format(formatting)))
+        }
+        if let Some(v) = meta.time() {
+            buffer.push(format!("{{time: {}}}", v))
+        }
+        if let Some(v) = meta.tempo() {
+            buffer.push(format!("{{tempo: {}}}", v))
+        }
+        if let Some(v) = meta.duration() {
+            buffer.push(format!("{{duration: {}}}", v))
+        }
+        if let Some(v) = meta.capo() {
+            buffer.push(format!("{{capo: {}}}", v))
+        }
+        if let Some(v) = meta.ccli_song_id() {
+            buffer.push(format!("{{ccli: {}}}", v))
+        }
+        buffer.join("\n")
+    }
+
+    fn build_string_for_chords(&self, chords: &Chords, formatting: Formatting) -> String {
+        format!("[{}]", chords.note_format(formatting))
+    }
+
+    fn build_string_for_children(
+        &self,
+        children: &[Node],
+        sections: &[Section],
+        formatting: Formatting,
+    ) -> String {
+        children
+            .iter()
+            .filter_map(|n| self.build_node(n, sections, formatting).ok())
+            .collect::<Vec<String>>()
+            .join("")
+    }
+}
+
+fn cleanup_output(output: &str) -> String {
+    format!("{}\n", remove_double_blank_lines(output).trim())
+}
+
+fn remove_double_blank_lines(input: &str) -> String {
+    if input.contains("\n\n\n") {
+        remove_double_blank_lines(&input.replace("\n\n\n", "\n\n"))
+    } else {
+        input.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::format::Format;
+    use crate::helper::{parse_content_for_file_type, transpose_content};
+    use crate::models::file_type::FileType;
+    use crate::models::meta::SemitoneNotation;
+    use crate::parser::MetaInformation;
+    use crate::test_helpers::get_test_ast;
+    use crate::test_helpers::get_test_metadata;
+
+    use super::*;
+
+    #[test]
+    fn test_convert() {
+        let converter = ChordProConverter {};
+        let result = converter.convert(
+            &get_test_ast(),
+            &MetaInformation::default(),
+            Formatting::with_format(Format::ChordPro),
+        );
+
+        assert!(result.is_ok());
+        let source = result.unwrap();
+
+        assert_eq!(
+            source,
+            r"{start_of_chorus: Chorus}
+Swing [D]low, sweet [G]chari[D]ot,
+Comin’ for to carry me [A7]home.
+Swing [D7]low, sweet [G]chari[D]ot,
+Comin’ for to [A7]carry me [D]home.
+{end_of_chorus}
+
+{start_of_verse: Verse 1}
+I [D]looked over Jordan, and [G]what did I [D]see,
+Comin’ for to carry me [A7]home.
+A [D]band of angels [G]comin’ after [D]me,
+Comin’ for to [A7]carry me [D]home.
+{end_of_verse}
+
+{chorus}
+"
+        );
+    }
+
+    #[test]
+    fn test_convert_w_metadata() {
+        let converter = ChordProConverter {};
+        let result = converter.convert(
+            &get_test_ast(),
+            &get_test_metadata(),
+            Formatting::with_format(Format::ChordPro),
+        );
+
+        assert!(result.is_ok());
+        let source = result.unwrap();
+
+        assert!(source.starts_with(
+            r"{title: Great new song}
+{subtitle: Originally known as 'Swing low sweet chariot'}
+{artist: Me}
+{composer: Wallace Willis}
+{lyricist: Wallace Willis}
+{year: 1865}
+{capo: 1}
+
+{start_of_chorus: Chorus}
+"
+        ));
+    }
+
+    #[test]
+    fn test_convert_transposed_w_formatting() {
+        let content = "# Song\nKey: C\n\n## Verse\n[C]A [Bb]B [F#m7]C\n";
+        let result = transpose_content(content.as_bytes(), FileType::Chorddown, 1).unwrap();
+        let formatting = Formatting {
+            semitone_notation: SemitoneNotation::Flat,
+            ..Formatting::with_format(Format::ChordPro)
+        };
+        let source = ChordProConverter {}
+            .convert(result.node_as_ref(), result.meta_as_ref(), formatting)
+            .unwrap();
+
+        assert_eq!(
+            source,
+            r"{title: Song}
+{key: Db}
+
+{start_of_verse: Verse}
+[Db]A [B]B [Gm7]C
+{end_of_verse}
+"
+        );
+    }
+
+    #[test]
+    fn test_convert_ignores_nashville_notation() {
+        let content = "# Song\nKey: G\n\n## Verse\n[G]Swing [D/F#]low, [Em]sweet [C]chariot\n";
+        let result = transpose_content(content.as_bytes(), FileType::Chorddown, 0).unwrap();
+        let formatting = Formatting {
+            chord_notation: ChordNotation::Nashville,
+            ..Formatting::with_format(Format::ChordPro)
+        };
+        let source = ChordProConverter {}
+            .convert(result.node_as_ref(), result.meta_as_ref(), formatting)
+            .unwrap();
+
+        assert_eq!(
+            source,
+            r"{title: Song}
+{key: G}
+
+{start_of_verse: Verse}
+[G]Swing [D/F#]low, [Em]sweet [C]chariot
+{end_of_verse}
+"
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let convert = |content: &str| {
+            let result =
+                parse_content_for_file_type(content.as_bytes(), FileType::ChordPro).unwrap();
+            ChordProConverter {}
+                .convert(
+                    result.node_as_ref(),
+                    result.meta_as_ref(),
+                    Formatting::with_format(Format::ChordPro),
+                )
+                .unwrap()
+        };
+        let content = include_str!("../../../tests/resources/swing_low_sweet_chariot.cho");
+        let source = convert(content);
+
+        assert!(source.starts_with("{title: Swing Low Sweet Chariot}\n\n{start_of_chorus: Chorus}"));
+        assert_eq!(convert(&source), source);
+    }
+
+    #[test]
+    fn test_round_trip_through_chorddown() {
+        let content = "{title: Song}\n\n{start_of_chorus: Refrain}\n[G]Sing\n{end_of_chorus}\n\n{comment: Slowly}\n\n{chorus: Refrain}\n\n{chorus}\n";
+        let result = parse_content_for_file_type(content.as_bytes(), FileType::ChordPro).unwrap();
+        let chorddown = crate::converter::Converter::get_converter(Format::Chorddown)
+            .convert(
+                result.node_as_ref(),
+                result.meta_as_ref(),
+                Formatting::with_format(Format::Chorddown),
+            )
+            .unwrap();
+        let result =
+            parse_content_for_file_type(chorddown.as_bytes(), FileType::Chorddown).unwrap();
+        let source = ChordProConverter {}
+            .convert(
+                result.node_as_ref(),
+                result.meta_as_ref(),
+                Formatting::with_format(Format::ChordPro),
+            )
+            .unwrap();
+        assert!(chorddown.contains("> Slowly\n\n> Refrain\n\n> Chorus\n"));
+        assert_eq!(source, content);
+    }
+}
@@ -1,43 +1,84 @@
+use crate::converter::capo::CapoRenderer;
 use crate::error::Result;
 use crate::models::chord::fmt::*;
 use crate::models::chord::Chords;
-use crate::models::meta::MetaTrait;
+use crate::models::meta::{BNotation, ChordNotation, MetaTrait};
 use crate::parser::Node;
 use crate::tokenizer::Token;
 
 use super::ConverterTrait;
 
-pub struct ChorddownConverter {}
+/// Converter to build Chorddown files
+///
+/// With a capo the chord shapes are written and the `Capo` meta information is dropped, so the
+/// result can be read again. `CapoFormatting::show_concert_pitch` is ignored
+#[derive(Default)]
+pub struct ChorddownConverter {
+    normalize_layout: bool,
+}
 
 impl ConverterTrait for ChorddownConverter {
     fn convert(&self, node: &Node, meta: &dyn MetaTrait, formatting: Formatting) -> Result<String> {
+        // The tokenizer only understands letter chords
+        let formatting = Formatting {
+            chord_notation: ChordNotation::Letter,
+            ..formatting
+        };
+        let meta_block = self.build_meta(meta, formatting);
+        let meta_block = if self.normalize_layout && !meta_block.is_empty() {
+            meta_block + "\n"
+        } else {
+            meta_block
+        };
         let output = format!(
             "{}{}{}",
             self.build_title(meta),
-            self.build_meta(meta, formatting),
-            self.build_node(node, formatting)?
+            meta_block,
+            self.build_node(node, meta, formatting)?
         );
         Ok(cleanup_output(&output))
     }
 }
 
 impl ChorddownConverter {
-    fn build_node<'a>(&'a self, node: &'a Node, formatting: Formatting) -> Result<String> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a converter writing the layout of `chordr fmt`
+    ///
+    /// The lines of inline meta information are removed (instead of leaving blank lines behind)
+    /// and other lines of the title section stay in the header block
+    pub(crate) fn with_normalized_layout() -> Self {
+        Self {
+            normalize_layout: true,
+        }
+    }
+
+    fn build_node<'a>(
+        &'a self,
+        node: &'a Node,
+        meta: &dyn MetaTrait,
+        formatting: Formatting,
+    ) -> Result<String> {
         match node {
             Node::ChordTextPair {
                 chords,
                 text,
                 last_in_line: _,
+                span: _,
             } => Ok(format!(
                 "{}{}",
-                self.build_string_for_chords(chords, formatting),
+                self.build_string_for_chords(chords, meta, formatting),
                 self.build_token(text),
             )),
-            Node::ChordStandalone(chord) => {
-                Ok(self.build_column(self.build_string_for_chords(chord, formatting), ""))
+            Node::ChordStandalone(chord, _) => {
+                Ok(self.build_column(self.build_string_for_chords(chord, meta, formatting), ""))
             }
             Node::Text(text) => Ok(self.build_token(text)),
-            Node::Document(children) => Ok(self.build_string_for_children(children, formatting)),
+            Node::Document(children) => {
+                Ok(self.build_string_for_children(children, meta, formatting))
+            }
             Node::Headline(token) => Ok(self.build_token(token)),
             Node::Quote(token) => Ok(self.build_token(token)),
             Node::Meta(_) => {
@@ -49,11 +90,19 @@ impl ChorddownConverter {
                 head,
                 children,
                 section_type: _,
-            } => Ok(format!(
-                "{}{}\n",
-                self.build_node(head, formatting)?,
-                self.build_string_for_children(children, formatting)
-            )),
+                span: _,
+            } => {
+                let children = match children.split_first() {
+                    // The line break of the title has already been rendered with the meta block
+                    Some((Node::Newline, rest)) if self.normalize_layout && is_title(head) => rest,
+                    _ => &children[..],
+                };
+                Ok(format!(
+                    "{}{}\n",
+                    self.build_node(head, meta, formatting)?,
+                    self.build_string_for_children(children, meta, formatting)
+                ))
+            }
         }
     }
 
@@ -86,6 +135,7 @@ impl ChorddownConverter {
     }
 
     fn build_meta(&self, meta: &dyn MetaTrait, formatting: Formatting) -> String {
+        let capo_renderer = CapoRenderer::new(meta, formatting);
         let mut buffer = String::new();
         if let Some(v) = meta.subtitle() {
             buffer.push_str("Subtitle: ");
@@ -134,6 +184,11 @@ impl ChorddownConverter {
         }
         if let Some(v) = meta.key() {
             buffer.push_str("Key: ");
+            buffer.push_str(&capo_renderer.shape(&v));
+            buffer.push('\n')
+        }
+        if let Some(v) = meta.original_key() {
+            buffer.push_str("Original Key: ");
             buffer.push_str(&v.note_format(formatting));
             buffer.push('\n')
         }
@@ -152,7 +207,8 @@ impl ChorddownConverter {
             buffer.push_str(&v);
             buffer.push('\n')
         }
-        if let Some(v) = meta.capo() {
+        // If a capo is applied the shapes are written and the song must not be capoed again
+        if let Some(v) = meta.capo().filter(|_| !capo_renderer.is_active()) {
             buffer.push_str("Capo: ");
             buffer.push_str(&v);
             buffer.push('\n')
@@ -162,24 +218,49 @@ impl ChorddownConverter {
             buffer.push_str(&v);
             buffer.push('\n')
         }
-        //        meta.b_notation()  // -> BNotation;
+        if meta.b_notation() != BNotation::default() {
+            buffer.push_str("B-Notation: ");
+            buffer.push_str(meta.b_notation().as_str());
+            buffer.push('\n')
+        }
+        let tags = meta.tags();
+        if !tags.is_empty() {
+            buffer.push_str("Tags: ");
+            buffer.push_str(&tags.to_string());
+            buffer.push('\n')
+        }
         buffer.trim_end().to_string()
     }
 
-    fn build_string_for_chords(&self, chords: &Chords, formatting: Formatting) -> String {
-        format!("[{}]", chords.note_format(formatting))
+    fn build_string_for_chords(
+        &self,
+        chords: &Chords,
+        meta: &dyn MetaTrait,
+        formatting: Formatting,
+    ) -> String {
+        format!("[{}]", CapoRenderer::new(meta, formatting).shape(chords))
     }
 
     fn build_string_for_children<'a>(
         &'a self,
         children: &'a [Node],
+        meta: &dyn MetaTrait,
         formatting: Formatting,
     ) -> String {
-        children
-            .iter()
-            .filter_map(|n| self.build_node(n, formatting).ok())
-            .collect::<Vec<String>>()
-            .join("")
+        let mut output = String::new();
+        let mut nodes = children.iter().peekable();
+        while let Some(node) = nodes.next() {
+            if self.normalize_layout && matches!(node, Node::Meta(_)) {
+                // Metadata has already been rendered, so the whole line is removed
+                nodes.next_if(|n| matches!(n, Node::Newline));
+                continue;
+            }
+            if let Ok(part) = self.build_node(node, meta, formatting) {
+                output.push_str(&part);
+            }
+        }
+
+        output
     }
 
     fn build_column<S1: Into<String>, S2: Into<String>>(&self, chord: S1, lyric: S2) -> String {
@@ -190,6 +271,10 @@ impl ChorddownConverter {
     }
 }
 
+fn is_title(node: &Node) -> bool {
+    matches!(node, Node::Headline(Token::Headline { level: 1, .. }))
+}
+
 fn cleanup_output(output: &str) -> String {
     format!("{}\n", remove_double_blank_lines(output).trim())
 }
@@ -211,11 +296,15 @@ mod tests {
         get_test_ast, get_test_ast_w_inline_metadata, get_test_ast_with_quote,
     };
 
+    use crate::converter::capo::{convert_test_song_w_capo, TEST_SONG_W_CAPO};
+    use crate::models::chord::fmt::CapoFormatting;
+    use crate::models::meta::ChordNotation;
+
     use super::*;
 
     #[test]
     fn test_convert() {
-        let converter = ChorddownConverter {};
+        let converter = ChorddownConverter::new();
         let result = converter.convert(
             &get_test_ast(),
             &MetaInformation::default(),
@@ -247,7 +336,7 @@ Comin’ for to [A7]carry me [D]home.
 
     #[test]
     fn test_convert_w_metadata() {
-        let converter = ChorddownConverter {};
+        let converter = ChorddownConverter::new();
         let result = converter.convert(
             &get_test_ast(),
             &get_test_metadata(),
@@ -267,6 +356,7 @@ Composer: Wallace Willis
 Lyricist: Wallace Willis
 Year: 1865
 Capo: 1
+Tags: #oldie #Jordan
 
 ##! Chorus
 Swing [D]low, sweet [G]chari[D]ot,
@@ -287,7 +377,7 @@ Comin’ for to [A7]carry me [D]home.
 
     #[test]
     fn test_convert_w_inline_metadata() {
-        let converter = ChorddownConverter {};
+        let converter = ChorddownConverter::new();
         let ast = get_test_ast_w_inline_metadata();
         let result = converter.convert(
             &ast,
@@ -308,6 +398,7 @@ Composer: Wallace Willis
 Lyricist: Wallace Willis
 Year: 1865
 Capo: 1
+Tags: #oldie #Jordan
 
 ##! Chorus
 Swing [D]low, sweet [G]chari[D]ot.
@@ -315,9 +406,36 @@ Swing [D]low, sweet [G]chari[D]ot.
         );
     }
 
+    #[test]
+    fn test_convert_w_normalized_layout() {
+        let parser_result = crate::helper::parse_content(
+            "# Song\nKey: G\nArrangement: Band\n## Verse\nTempo: 90\n[G]La\n".as_bytes(),
+        )
+        .unwrap();
+        let convert = |converter: ChorddownConverter| {
+            converter
+                .convert(
+                    parser_result.node_as_ref(),
+                    parser_result.meta_as_ref(),
+                    Formatting::with_format(Format::Chorddown),
+                )
+                .unwrap()
+        };
+
+        // The lines of the inline meta information are left blank
+        assert_eq!(
+            convert(ChorddownConverter::new()),
+            "# Song\nKey: G\nTempo: 90\n\nArrangement: Band\n\n## Verse\n\n[G]La\n"
+        );
+        assert_eq!(
+            convert(ChorddownConverter::with_normalized_layout()),
+            "# Song\nKey: G\nTempo: 90\nArrangement: Band\n\n## Verse\n[G]La\n"
+        );
+    }
+
     #[test]
     fn test_convert_w_content_after_quote() {
-        let converter = ChorddownConverter {};
+        let converter = ChorddownConverter::new();
         let ast = get_test_ast_with_quote();
         let result = converter.convert(
             &ast,
@@ -337,4 +455,44 @@ Swing [D]low, sweet [G]chari[D]ot.
 "#
         );
     }
+
+    #[test]
+    fn test_convert_w_capo() {
+        let convert = |capo| convert_test_song_w_capo(Format::Chorddown, Some(capo));
+
+        // The concert-pitch chords are never written
+        assert_eq!(
+            convert(CapoFormatting::new(None, true)),
+            "# Song\nKey: G\n\n## Verse\n[G]Swing [D7/F#]low\n"
+        );
+        assert_eq!(
+            convert(CapoFormatting::new(Some(0), false)),
+            TEST_SONG_W_CAPO
+        );
+    }
+
+    #[test]
+    fn test_convert_ignores_nashville_notation() {
+        let content = "# Song\nKey: G\n\n## Verse\n[G]Swing [D/F#]low, [Em]sweet [C]chariot\n";
+        let result = crate::helper::parse_content_for_file_type(
+            content.as_bytes(),
+            crate::models::file_type::FileType::Chorddown,
+        )
+        .unwrap();
+        let source = ChorddownConverter::new()
+            .convert(
+                result.node_as_ref(),
+                result.meta_as_ref(),
+                Formatting {
+                    chord_notation: ChordNotation::Nashville,
+                    ..Formatting::with_format(Format::Chorddown)
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            source,
+            "# Song\nKey: G\n\n## Verse\n[G]Swing [D/F#]low, [Em]sweet [C]chariot\n"
+        );
+    }
 }
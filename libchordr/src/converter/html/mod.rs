@@ -18,14 +18,17 @@ impl HtmlConverter {
         tag_builder: &TagProvider,
         formatting: Formatting,
     ) -> Result<String> {
-        let tag = tag_builder.build_tag_for_node(node, meta, formatting);
+        let key = meta.key().map(|key| key.root());
+        let tag = tag_builder.build_tag_for_node(node, meta, formatting.with_key(key));
+        let chord_diagrams = tag_builder.build_tag_for_chord_diagrams(node, meta, formatting);
 
         Ok(format!(
             r#"<div id="chordr">
-{}
+{}{}
 {}
 </div>"#,
             tag,
+            chord_diagrams,
             self.format_meta(meta, formatting)
         ))
     }
@@ -97,6 +100,11 @@ impl ConverterTrait for HtmlConverter {
 mod tests {
     use crate::test_helpers::get_test_tokens;
 
+    use crate::chord_diagram::{Instrument, Tuning};
+    use crate::converter::capo::convert_test_song_w_capo;
+    use crate::models::chord::fmt::{CapoFormatting, ChordDiagramFormatting};
+    use crate::models::meta::ChordNotation;
+
     use super::*;
 
     #[test]
@@ -116,4 +124,85 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), content.trim())
     }
+
+    #[test]
+    fn test_convert_w_capo() {
+        let convert = |capo| convert_test_song_w_capo(Format::HTML, capo);
+
+        let source = convert(Some(CapoFormatting::new(Some(2), false)));
+        assert!(source.contains("<span class='chordr-chord' data-chord='G'>G</span>"));
+        assert!(source.contains("data-chord='D7/F#'"));
+        assert!(!source.contains("chordr-chord-concert-pitch"));
+
+        let source = convert(Some(CapoFormatting::new(None, true)));
+        assert!(source.contains(
+            "<span class='chordr-chord' data-chord='G'>G</span><span class='chordr-chord-concert-pitch'>A</span>"
+        ));
+
+        let source = convert(None);
+        assert!(source.contains("<span class='chordr-chord' data-chord='A'>A</span>"));
+    }
+
+    #[test]
+    fn test_convert_w_roman_notation() {
+        let content = "# Song\nKey: G\n\n## Verse\n[G]Swing [Em]low\n";
+        let result = crate::helper::parse_content_for_file_type(
+            content.as_bytes(),
+            crate::models::file_type::FileType::Chorddown,
+        )
+        .unwrap();
+        let source = HtmlConverter {}
+            .convert(
+                result.node_as_ref(),
+                result.meta_as_ref(),
+                Formatting {
+                    chord_notation: ChordNotation::Roman,
+                    ..Formatting::with_format(Format::HTML)
+                },
+            )
+            .unwrap();
+
+        assert!(source.contains("<span class='chordr-chord' data-chord='I'>I</span>"));
+        assert!(source.contains("<span class='chordr-chord' data-chord='vi'>vi</span>"));
+        assert!(source.contains("<span class='meta-value -key'>G</span>"));
+    }
+
+    #[test]
+    fn test_convert_w_chord_diagrams() {
+        let content = "# Song\nCapo: 2\n\n## Verse\n[A]Swing [E7/G#]low, [A]sweet\n";
+        let result = crate::helper::parse_content_for_file_type(
+            content.as_bytes(),
+            crate::models::file_type::FileType::Chorddown,
+        )
+        .unwrap();
+        let convert = |formatting: Formatting| {
+            HtmlConverter {}
+                .convert(result.node_as_ref(), result.meta_as_ref(), formatting)
+                .unwrap()
+        };
+
+        let source = convert(Formatting::with_format(Format::HTML));
+        assert!(!source.contains("chord-diagram"));
+
+        let source = convert(Formatting {
+            chord_diagrams: Some(ChordDiagramFormatting::default()),
+            ..Formatting::with_format(Format::HTML)
+        });
+        assert!(source.contains("<section class='chord-diagrams'>"));
+        assert_eq!(source.matches("<figure class='chord-diagram'>").count(), 2);
+        assert!(source.contains("<figcaption>A</figcaption>"));
+        assert!(source.contains("<figcaption>E7/G#</figcaption>"));
+
+        // With a capo the diagrams show the chord shapes
+        let source = convert(Formatting {
+            chord_diagrams: Some(ChordDiagramFormatting::new(
+                Instrument::Ukulele,
+                Tuning::Standard,
+            )),
+            capo: Some(CapoFormatting::new(None, false)),
+            ..Formatting::with_format(Format::HTML)
+        });
+        assert!(source.contains("<figcaption>G</figcaption>"));
+        assert!(source.contains("<figcaption>D7/F#</figcaption>"));
+    }
 }
@@ -1,4 +1,7 @@
+use crate::chord_diagram::ChordDiagram;
+use crate::converter::capo::CapoRenderer;
 use crate::html::content::Content;
+use crate::html::escape::Escape;
 use crate::html::tag::Tag;
 use crate::html::tag_builder::TagBuilder;
 use crate::models::chord::fmt::Formatting;
@@ -25,13 +28,15 @@ impl TagProvider {
                 chords,
                 text,
                 last_in_line,
+                span: _,
             } => self.build_column(
-                self.build_tag_for_chords(chords, formatting),
+                self.build_tag_for_chords(chords, meta, formatting),
                 self.build_tag_for_chord_text_token(text, formatting, *last_in_line),
             ),
-            Node::ChordStandalone(chord) => {
-                self.build_column(self.build_tag_for_chords(chord, formatting), Tag::blank())
-            }
+            Node::ChordStandalone(chord, _) => self.build_column(
+                self.build_tag_for_chords(chord, meta, formatting),
+                Tag::blank(),
+            ),
             Node::Text(text) => {
                 self.build_column(Tag::blank(), self.build_tag_for_token(text, formatting))
             }
@@ -48,10 +53,67 @@ impl TagProvider {
                 head,
                 children,
                 section_type,
+                span: _,
             } => self.build_tag_for_section(head, children, section_type, meta, formatting),
         }
     }
 
+    /// Build the block of diagrams for all chords used in the song
+    ///
+    /// A blank `Tag` is returned if chord diagrams are disabled in the `formatting`
+    pub fn build_tag_for_chord_diagrams(
+        &self,
+        node: &Node,
+        meta: &dyn MetaTrait,
+        formatting: Formatting,
+    ) -> Tag {
+        let diagram_formatting = match formatting.chord_diagrams {
+            Some(d) => d,
+            None => return Tag::blank(),
+        };
+        let capo_renderer = CapoRenderer::new(meta, formatting);
+        // Keep the distinct chords in the order of their first occurrence
+        let mut chords: Vec<&Chords> = vec![];
+        for c in node.chords() {
+            if !chords.contains(&c) {
+                chords.push(c)
+            }
+        }
+
+        let mut names: Vec<String> = vec![];
+        let mut figures = String::new();
+        for chords in chords {
+            let diagram = match ChordDiagram::for_chords(
+                &capo_renderer.shape_chords(chords),
+                diagram_formatting.instrument,
+                diagram_formatting.tuning,
+                formatting,
+            ) {
+                Some(d) => d,
+                None => continue,
+            };
+            if names.iter().any(|n| n == diagram.name()) {
+                continue;
+            }
+            figures.push_str(&format!(
+                "<figure class='chord-diagram'>{}<figcaption>{}</figcaption></figure>",
+                diagram.to_svg(),
+                Escape(diagram.name())
+            ));
+            names.push(diagram.name().to_owned());
+        }
+
+        if figures.is_empty() {
+            Tag::blank()
+        } else {
+            TagBuilder::new()
+                .set_tag_name("section")
+                .set_class_name("chord-diagrams")
+                .set_content(Content::Raw(figures))
+                .build()
+        }
+    }
+
     fn build_tag_for_meta(
         &self,
         metadata_token: &Meta,
@@ -129,14 +191,31 @@ impl TagProvider {
         }
     }
 
-    fn build_tag_for_chords(&self, chords: &Chords, formatting: Formatting) -> Tag {
-        let formatted_chords = chords.note_format(formatting);
-
-        Tag::span_with_chord(
+    fn build_tag_for_chords(
+        &self,
+        chords: &Chords,
+        meta: &dyn MetaTrait,
+        formatting: Formatting,
+    ) -> Tag {
+        let capo_renderer = CapoRenderer::new(meta, formatting);
+        let formatted_chords = capo_renderer.shape(chords);
+        let chord_tag = Tag::span_with_chord(
             Content::from_string(formatted_chords.clone()),
             Some("chordr-chord"),
             formatted_chords,
-        )
+        );
+
+        match capo_renderer.concert_pitch(chords) {
+            Some(concert_pitch) => Tag::raw(format!(
+                "{}{}",
+                chord_tag,
+                Tag::span(
+                    Content::from_string(concert_pitch),
+                    Some("chordr-chord-concert-pitch")
+                )
+            )),
+            None => chord_tag,
+        }
     }
 
     fn build_tag_for_children<'a>(
@@ -193,10 +272,12 @@ fn get_song_metadata_content(
         Meta::Copyright(_) => song_metadata.copyright(),
         Meta::Album(_) => song_metadata.album(),
         Meta::Year(_) => song_metadata.year(),
-        Meta::Key(_) => song_metadata.key().map(|c| c.note_format(formatting)),
+        Meta::Key(_) => song_metadata
+            .key()
+            .map(|c| CapoRenderer::new(song_metadata, formatting.with_key(None)).format(&c)),
         Meta::OriginalKey(_) => song_metadata
             .original_key()
-            .map(|c| c.note_format(formatting)),
+            .map(|c| c.note_format(formatting.with_key(None))),
         Meta::Time(_) => song_metadata.time(),
         Meta::Tempo(_) => song_metadata.tempo(),
         Meta::Duration(_) => song_metadata.duration(),
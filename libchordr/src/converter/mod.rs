@@ -1,4 +1,5 @@
-use self::chorddown::ChorddownConverter;
+use self::chord_pro::ChordProConverter;
+pub(crate) use self::chorddown::ChorddownConverter;
 use self::html::HtmlConverter;
 #[cfg(feature = "pdf")]
 use self::pdf::PdfConverter;
@@ -9,6 +10,8 @@ use crate::models::chord::fmt::Formatting;
 use crate::models::song_meta_trait::SongMetaTrait;
 use crate::prelude::*;
 
+mod capo;
+mod chord_pro;
 mod chorddown;
 mod html;
 #[cfg(feature = "pdf")]
@@ -40,11 +43,12 @@ impl Converter {
     pub fn get_converter(format: Format) -> Box<dyn ConverterTrait> {
         match format {
             Format::HTML => Box::new(HtmlConverter {}),
-            Format::Chorddown => Box::new(ChorddownConverter {}),
+            Format::Chorddown => Box::new(ChorddownConverter::new()),
+            Format::ChordPro => Box::new(ChordProConverter {}),
             Format::SongBeamer => Box::new(SongBeamerConverter {}),
             Format::Text => Box::new(TextConverter {}),
             #[cfg(feature = "pdf")]
-            Format::PDF => Box::new(PdfConverter {}),
+            Format::PDF => Box::new(PdfConverter::new()),
         }
     }
 }
@@ -56,6 +60,15 @@ impl ConverterTrait for Converter {
         meta: &dyn SongMetaTrait,
         formatting: Formatting,
     ) -> Result<String> {
+        // Spell the chords according to the key signature of the (transposed) key
+        let formatting = match meta.key() {
+            Some(key) if formatting.semitone_notation_for_key => Formatting {
+                semitone_notation: SemitoneNotation::for_key(&key),
+                ..formatting
+            },
+            _ => formatting,
+        };
+
         Converter::get_converter(formatting.format).convert(node, meta, formatting)
     }
 }
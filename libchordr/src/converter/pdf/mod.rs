@@ -1,18 +1,16 @@
 use crate::converter::ConverterTrait;
-use crate::prelude::*;
-
 use crate::error::Result;
 use crate::models::chord::fmt::Formatting;
-use crate::models::meta::meta_trait::SongMetaTrait;
-use crate::pdf::coordinates::Coordinates;
+use crate::models::meta::MetaTrait;
+use crate::parser::Node;
 use crate::pdf::pdf_builder::PdfBuilder;
-use crate::pdf::styles::{Style, Styles};
-use printpdf::*;
-use std::fs::File;
-use std::io::BufWriter;
-
-type BuildResult = Result<()>;
+use crate::pdf::styles::Styles;
 
+/// Converter to build PDF documents
+///
+/// The chords are printed above the lyrics. Only the standard PDF fonts are used and all
+/// non-ASCII characters are escaped, so the output is a valid `String`
+#[derive(Default)]
 pub struct PdfConverter {}
 
 impl PdfConverter {
@@ -21,53 +19,22 @@ impl PdfConverter {
     }
 }
 
-impl Default for PdfConverter {
-    fn default() -> Self {
-        Self {}
-    }
-}
-
 impl ConverterTrait for PdfConverter {
-    fn convert(
-        &self,
-        node: &Node,
-        meta: &dyn SongMetaTrait,
-        formatting: Formatting,
-    ) -> Result<String> {
-        //        let (doc, page1, layer1) = PdfDocument::new("PDF_Document_title", Mm(210.0), Mm(297.0), "Layer 1");
-        ////        let (page2, layer1) = doc.add_page(Mm(10.0), Mm(250.0), "Page 2, Layer 1");
-        //        let current_layer = doc.get_page(page1).get_layer(layer1);
-        //
-        //        let font = doc.add_builtin_font(BuiltinFont::TimesBoldItalic).unwrap();
-        ////        let font = doc.add_external_font(File::open("assets/fonts/RobotoMedium.ttf").unwrap()).unwrap();
-        ////        let font2 = doc.add_external_font(File::open("assets/fonts/RobotoMedium.ttf").unwrap()).unwrap();
-        //
-        //
-        //// text, font size, x from left edge, y from top edge, font
-        //        let stylesheets = Styles {
-        //            page
-        //            chorus: Style::new(font.clone(), Mm(10.0), 10),
-        //            verse: Style::new(font.clone(), Mm(10.0), 10),
-        //            headline: Style::new(font.clone(), Mm(10.0), 18),
-        //        };
-        //
-        //        let mut builder = PdfBuilder::new(Coordinates::new(Mm(0.0), Mm(0.0)), stylesheets, doc, current_layer);
-        //
-        //        let doc = builder.build_for_node(node).unwrap();
-        //
-        //
-        //        doc.save(&mut BufWriter::new(File::create("test_working.pdf").unwrap())).unwrap();
-        //
-        //        println!("{:?}", ::std::env::current_dir());
-        Ok(String::new())
+    fn convert(&self, node: &Node, meta: &dyn MetaTrait, formatting: Formatting) -> Result<String> {
+        let title = meta.title().unwrap_or_default();
+        let mut builder = PdfBuilder::new(title, Styles::default());
+        builder.add_song(node, meta, formatting);
+
+        Ok(builder.build().render())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::parser::MetaInformation;
-    use crate::test_helpers::get_test_tokens;
+    use crate::format::Format;
+    use crate::parser::{MetaInformation, Parser, ParserTrait};
+    use crate::test_helpers::{get_test_ast, get_test_metadata, get_test_tokens};
 
     #[test]
     fn test_convert() {
@@ -79,5 +46,52 @@ mod tests {
         );
 
         assert!(result.is_ok());
+        let output = result.unwrap();
+        assert!(output.starts_with("%PDF-1.4\n"));
+        assert!(output.ends_with("%%EOF\n"));
+        assert!(output.contains("(Swing low, sweet chariot,) Tj"));
+        assert!(output.contains("(      D          G    D) Tj"));
+    }
+
+    #[test]
+    fn test_convert_w_metadata() {
+        let output = PdfConverter::new()
+            .convert(
+                &get_test_ast(),
+                &get_test_metadata(),
+                Formatting::with_format(Format::PDF),
+            )
+            .unwrap();
+
+        assert!(output.contains("/Title (Great new song)"));
+        assert!(output.contains("/F2 20.00 Tf 50.00 771.89 Td (Great new song) Tj"));
+        assert!(output
+            .contains("(Artist: Me   Composer: Wallace Willis   Lyricist: Wallace Willis) Tj"));
+        assert!(output.contains("/Count 1"));
+    }
+
+    #[test]
+    fn test_convert_page_break_keeps_sections_together() {
+        let verse = "## Verse\n[C]Line one\nLine two\nLine three\nLine four\n\n";
+        let content = format!("# Long song\n\n{}", verse.repeat(20));
+        let result = crate::helper::parse_content(content.as_bytes()).unwrap();
+        let output = PdfConverter::new()
+            .convert(
+                result.node_as_ref(),
+                result.meta_as_ref(),
+                Formatting::with_format(Format::PDF),
+            )
+            .unwrap();
+
+        assert!(output.contains("/Count 3"));
+        // Every page must start with a section headline
+        for page in output.split("\nstream\n").skip(1) {
+            let first_line = page.lines().next().unwrap();
+            assert!(
+                first_line.ends_with("(Verse) Tj ET") || first_line.contains("(Long song)"),
+                "{}",
+                first_line
+            );
+        }
     }
 }
@@ -35,6 +35,7 @@ impl SongBeamerConverter {
                 chords: _,
                 text,
                 last_in_line: _,
+                span: _,
             } => Some(self.build_text(text)),
             Node::Text(text) => Some(self.build_text(text)),
             Node::Document(children) => self.build_content_for_children(children),
@@ -43,6 +44,7 @@ impl SongBeamerConverter {
                 head: _,
                 children,
                 section_type: _,
+                span: _,
             } => {
                 let head =
                     SectionProvider::get_section(node).map_or("".to_string(), |s| s.title + "\n");
@@ -51,7 +53,7 @@ impl SongBeamerConverter {
             }
 
             Node::Headline(_) => None,
-            Node::ChordStandalone(_) => None,
+            Node::ChordStandalone(_, _) => None,
             Node::Quote(_) => None,
             Node::Meta(_) => {
                 // Meta data must have been already output
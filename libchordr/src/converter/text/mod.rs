@@ -1,8 +1,8 @@
 use std::ops::Add;
 
+use crate::converter::capo::CapoRenderer;
 use crate::error::Result;
 use crate::models::chord::fmt::Formatting;
-use crate::models::chord::NoteDisplay;
 use crate::models::meta::MetaTrait;
 use crate::parser::Node;
 use crate::tokenizer::Token;
@@ -30,6 +30,7 @@ impl TextConverter {
                 chords: _,
                 text,
                 last_in_line: _,
+                span: _,
             } => Ok(self.build_text(text)),
             Node::Text(text) => Ok(self.build_text(text)),
             Node::Document(children) => Ok(self.build_tag_for_children(children)),
@@ -38,10 +39,11 @@ impl TextConverter {
                 head: _,
                 children,
                 section_type: _,
+                span: _,
             } => Ok(self.build_tag_for_children(children)),
 
             Node::Headline(_) => Ok(String::new()),
-            Node::ChordStandalone(_) => Ok(String::new()),
+            Node::ChordStandalone(_, _) => Ok(String::new()),
             Node::Quote(_) => Ok(String::new()),
             Node::Meta(_) => {
                 // Meta data must have been already output
@@ -91,7 +93,10 @@ impl TextConverter {
             buffer.push(format!("Year: {}", v))
         }
         if let Some(v) = meta.key() {
-            buffer.push(format!("Key: {}", v.note_format(formatting)))
+            buffer.push(format!(
+                "Key: {}",
+                CapoRenderer::new(meta, formatting).format(&v)
+            ))
         }
         if let Some(v) = meta.time() {
             buffer.push(format!("Time: {}", v))
@@ -144,6 +149,9 @@ mod tests {
         get_test_ast, get_test_ast_w_inline_metadata, get_test_ast_with_quote,
     };
 
+    use crate::converter::capo::convert_test_song_w_capo;
+    use crate::models::chord::fmt::CapoFormatting;
+
     use super::*;
 
     #[test]
@@ -255,4 +263,11 @@ Swing low, sweet chariot.
 "#
         );
     }
+
+    #[test]
+    fn test_convert_w_capo() {
+        let source = convert_test_song_w_capo(Format::Text, Some(CapoFormatting::new(None, true)));
+
+        assert!(source.starts_with("Song\nKey: G (A)\nCapo: 2\n"));
+    }
 }
@@ -3,7 +3,9 @@ use std::fmt::{Display, Error as FmtError, Formatter};
 use std::path::PathBuf;
 
 use crate::models::meta::b_notation::NotationError;
+use crate::models::meta::chord_notation::ChordNotationError;
 use crate::models::meta::semitone_notation::SemitoneNotationError;
+use crate::tokenizer::Span;
 
 /// Shorthand for chord library results
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -12,6 +14,7 @@ pub type Result<T, E = Error> = std::result::Result<T, E>;
 #[derive(Debug)]
 pub struct Error {
     inner: Kind,
+    span: Option<Span>,
 }
 
 #[doc(hidden)]
@@ -36,6 +39,10 @@ impl Error {
         Error::new(Kind::Chord(description.into()))
     }
 
+    pub fn catalog_error<S: Into<String>>(description: S) -> Self {
+        Self::new(Kind::Catalog(description.into()))
+    }
+
     pub fn setlist_error<S: Into<String>>(description: S) -> Self {
         Error::new(Kind::Setlist(description.into()))
     }
@@ -79,6 +86,10 @@ impl Error {
         Self::new(Kind::SemitoneNotation(error))
     }
 
+    pub fn chord_notation_error(error: ChordNotationError) -> Self {
+        Self::new(Kind::ChordNotation(error))
+    }
+
     pub fn parse_int_error(error: std::num::ParseIntError) -> Self {
         Self::new(Kind::ParseInt(error))
     }
@@ -88,13 +99,34 @@ impl Error {
     }
 
     fn new(kind: Kind) -> Self {
-        Error { inner: kind }
+        Error {
+            inner: kind,
+            span: None,
+        }
+    }
+}
+
+impl Error {
+    /// Attach the location in the source that caused the error
+    pub fn with_span(self, span: Span) -> Self {
+        Self {
+            span: Some(span),
+            ..self
+        }
+    }
+
+    /// Return the location in the source that caused the error (if known)
+    pub fn span(&self) -> Option<Span> {
+        self.span
     }
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
-        write!(f, "{}", self.inner)
+        match self.span {
+            Some(span) => write!(f, "{} ({})", self.inner, span),
+            None => write!(f, "{}", self.inner),
+        }
     }
 }
 
@@ -122,6 +154,12 @@ impl From<SemitoneNotationError> for Error {
     }
 }
 
+impl From<ChordNotationError> for Error {
+    fn from(error: ChordNotationError) -> Self {
+        Self::new(Kind::ChordNotation(error))
+    }
+}
+
 impl From<std::num::ParseIntError> for Error {
     fn from(error: std::num::ParseIntError) -> Self {
         Self::new(Kind::ParseInt(error))
@@ -135,6 +173,7 @@ enum Kind {
     CatalogBuilderFatal(String, PathBuf),
     FileType(String),
     Chord(String),
+    Catalog(String),
     Setlist(String),
     Unknown(String),
     InvalidUsername(String, String),
@@ -143,6 +182,7 @@ enum Kind {
     Io(std::io::Error),
     Notation(NotationError),
     SemitoneNotation(SemitoneNotationError),
+    ChordNotation(ChordNotationError),
     ParseInt(std::num::ParseIntError),
 }
 
@@ -158,6 +198,7 @@ impl Display for Kind {
             }
             Kind::FileType(s) => f.write_str(s),
             Kind::Chord(s) => f.write_str(s),
+            Kind::Catalog(s) => f.write_str(s),
             Kind::Setlist(s) => f.write_str(s),
             Kind::Unknown(s) => f.write_str(s),
             Kind::InvalidUsername(_name, message) => f.write_str(message),
@@ -168,6 +209,7 @@ impl Display for Kind {
             Kind::Io(i) => write!(f, "{}", i),
             Kind::Notation(i) => write!(f, "{}", i),
             Kind::SemitoneNotation(i) => write!(f, "{}", i),
+            Kind::ChordNotation(i) => write!(f, "{}", i),
             Kind::ParseInt(i) => write!(f, "{}", i),
         }
     }
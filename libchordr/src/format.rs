@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 pub enum Format {
     HTML,
     Chorddown,
+    ChordPro,
     SongBeamer,
     Text,
     #[cfg(feature = "pdf")]
@@ -19,6 +20,7 @@ impl Format {
         &[
             Self::HTML,
             Self::Chorddown,
+            Self::ChordPro,
             Self::SongBeamer,
             Self::Text,
             #[cfg(feature = "pdf")]
@@ -32,6 +34,7 @@ impl Display for Format {
         match self {
             Self::HTML => f.write_str("HTML"),
             Self::Chorddown => f.write_str("Chorddown"),
+            Self::ChordPro => f.write_str("ChordPro"),
             Self::SongBeamer => f.write_str("SongBeamer"),
             Self::Text => f.write_str("Text"),
             #[cfg(feature = "pdf")]
@@ -55,6 +58,7 @@ impl FromStr for Format {
         match value.to_lowercase().as_str() {
             "html" => Ok(Self::HTML),
             "chorddown" => Ok(Self::Chorddown),
+            "chordpro" => Ok(Self::ChordPro),
             "songbeamer" => Ok(Self::SongBeamer),
             "text" => Ok(Self::Text),
             #[cfg(feature = "pdf")]
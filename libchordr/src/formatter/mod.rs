@@ -0,0 +1,343 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::catalog_builder::CatalogBuilder;
+use crate::converter::{ChorddownConverter, ConverterTrait};
+use crate::error::{Error, Result};
+use crate::format::Format;
+use crate::models::chord::fmt::Formatting;
+use crate::models::file_type::FileType;
+use crate::models::meta::{BNotation, MetaTrait, SemitoneNotation};
+use crate::parser::{MetaInformation, Node, Parser, ParserResult, ParserTrait};
+use crate::tokenizer::{build_tokenizer, Meta, Token, Tokenizer};
+
+/// Rewrite Chorddown sources in a canonical style
+///
+/// The meta information is written in a fixed order, the chords are spelled according to the
+/// `SemitoneNotation` and sections are separated by a single blank line. Blank lines inside a
+/// section are collapsed to a single one. Lines that are not understood by the parser (e.g. notes
+/// and comments) are kept as text.
+///
+/// The formatted source is parsed again and compared with the original song. If any content
+/// would get lost, an error is returned instead of the formatted source.
+#[derive(Default)]
+pub struct ChorddownFormatter {
+    semitone_notation: Option<SemitoneNotation>,
+}
+
+/// Define if the formatted source is written back to the file
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FormatMode {
+    /// Overwrite the file with the formatted source
+    Write,
+    /// Only check if the file is formatted
+    Check,
+}
+
+impl ChorddownFormatter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Spell the chords with the given notation
+    ///
+    /// If no notation is set, the notation matching the song's key is used
+    pub fn with_semitone_notation(self, semitone_notation: Option<SemitoneNotation>) -> Self {
+        Self { semitone_notation }
+    }
+
+    /// Return the formatted Chorddown source
+    pub fn format_source(&self, source: &str) -> Result<String> {
+        let (tokens, _warnings) = build_tokenizer().tokenize_with_spans(source.as_bytes())?;
+        let declares_b_notation = tokens
+            .iter()
+            .any(|t| matches!(t.token, Token::Meta(Meta::BNotation(_))));
+        let parser_result = Parser::new().keep_blank_lines().parse_with_spans(tokens)?;
+        let meta = &parser_result.meta;
+        if meta.key_raw.is_some() && meta.key.is_none() {
+            return Err(Error::parser_error(
+                "The song's key is not a valid chord and would be removed",
+            ));
+        }
+        if meta.original_key_raw.is_some() && meta.original_key.is_none() {
+            return Err(Error::parser_error(
+                "The song's original key is not a valid chord and would be removed",
+            ));
+        }
+
+        let semitone_notation = self.semitone_notation.unwrap_or_else(|| {
+            meta.key()
+                .map(|key| SemitoneNotation::for_key(&key))
+                .unwrap_or_default()
+        });
+        let formatting = Formatting {
+            b_notation: meta.b_notation(),
+            semitone_notation,
+            ..Formatting::with_format(Format::Chorddown)
+        };
+        // A B-Notation detected from the chords must not be written as meta information
+        let meta = if declares_b_notation {
+            meta.clone()
+        } else {
+            MetaInformation {
+                b_notation: BNotation::default(),
+                ..meta.clone()
+            }
+        };
+        let formatted = ChorddownConverter::with_normalized_layout().convert(
+            parser_result.node_as_ref(),
+            &meta,
+            formatting,
+        )?;
+
+        verify_content(source, &formatted)?;
+
+        Ok(formatted)
+    }
+
+    /// Format the Chorddown file
+    ///
+    /// Return if the file was not formatted (in [`FormatMode::Write`] it has been rewritten)
+    pub fn format_file<P: AsRef<Path>>(&self, path: P, mode: FormatMode) -> Result<bool> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)?;
+        let formatted = self.format_source(&source)?;
+        if formatted == source {
+            return Ok(false);
+        }
+        if mode == FormatMode::Write {
+            fs::write(path, formatted)?;
+        }
+
+        Ok(true)
+    }
+
+    /// Format all Chorddown files in the directory
+    ///
+    /// Return the result of [`format_file()`] for each file
+    ///
+    /// [`format_file()`]: Self::format_file
+    pub fn format_directory<P: AsRef<Path>>(
+        &self,
+        path: P,
+        mode: FormatMode,
+        recursive: bool,
+    ) -> Vec<(PathBuf, Result<bool>)> {
+        let mut files = vec![];
+        let mut results = vec![];
+        for result in CatalogBuilder::new().collect_song_files(
+            path.as_ref(),
+            &[FileType::Chorddown],
+            recursive,
+        ) {
+            match result {
+                Ok(file) => files.push(file),
+                Err(error) => {
+                    let path = error.path().to_path_buf();
+                    results.push((path, Err(Error::unknown_error(error.to_string()))))
+                }
+            }
+        }
+        files.sort();
+        for file in files {
+            let result = self.format_file(&file, mode);
+            results.push((file, result));
+        }
+
+        results
+    }
+}
+
+/// Make sure the formatted source describes the same song as the original source
+fn verify_content(source: &str, formatted: &str) -> Result<()> {
+    let original = parse_without_spans(source)?;
+    let result = parse_without_spans(formatted)?;
+
+    if !meta_matches(&original.meta, &result.meta)
+        || collect_meta_keywords(&original.node) != collect_meta_keywords(&result.node)
+    {
+        return Err(Error::parser_error(
+            "Formatting would change the song's meta information",
+        ));
+    }
+    if collect_content(&original.node) != collect_content(&result.node) {
+        return Err(Error::parser_error(
+            "Formatting would change the song's content",
+        ));
+    }
+
+    Ok(())
+}
+
+fn parse_without_spans(source: &str) -> Result<ParserResult> {
+    let (tokens, _warnings) = build_tokenizer().tokenize(source.as_bytes())?;
+    Parser::new().keep_blank_lines().parse(tokens)
+}
+
+fn meta_matches(a: &MetaInformation, b: &MetaInformation) -> bool {
+    a.title() == b.title()
+        && a.subtitle() == b.subtitle()
+        && a.artist() == b.artist()
+        && a.composer() == b.composer()
+        && a.lyricist() == b.lyricist()
+        && a.copyright() == b.copyright()
+        && a.album() == b.album()
+        && a.year() == b.year()
+        && a.key() == b.key()
+        && a.original_key() == b.original_key()
+        && a.time() == b.time()
+        && a.tempo() == b.tempo()
+        && a.duration() == b.duration()
+        && a.capo() == b.capo()
+        && a.original_title() == b.original_title()
+        && a.alternative_title() == b.alternative_title()
+        && a.ccli_song_id() == b.ccli_song_id()
+        && a.b_notation() == b.b_notation()
+        && a.tags() == b.tags()
+}
+
+/// Collect the keywords of the meta information declared in the source
+fn collect_meta_keywords(node: &Node) -> Vec<&'static str> {
+    let mut keywords = match node {
+        Node::Document(children) | Node::Section { children, .. } => {
+            children.iter().flat_map(collect_meta_keywords).collect()
+        }
+        Node::Meta(meta) => vec![meta.keyword()],
+        _ => vec![],
+    };
+    keywords.sort_unstable();
+    keywords.dedup();
+
+    keywords
+}
+
+/// Collect the headlines, lyrics, chords and line breaks in the order of their appearance
+///
+/// The lines containing meta information are ignored, because the formatter moves them to the top.
+/// Blank lines between sections and at the start and end of a section are ignored, because the
+/// formatter normalizes them
+fn collect_content(node: &Node) -> Vec<&Node> {
+    match node {
+        Node::Document(children) => children
+            .iter()
+            .filter(|n| !matches!(n, Node::Meta(_) | Node::Newline))
+            .flat_map(collect_content)
+            .collect(),
+        Node::Section { head, children, .. } => std::iter::once(head.as_ref())
+            .chain(collect_lines(children))
+            .collect(),
+        _ => vec![node],
+    }
+}
+
+fn collect_lines(children: &[Node]) -> Vec<&Node> {
+    let mut lines = vec![];
+    let mut nodes = children.iter().peekable();
+    while let Some(node) = nodes.next() {
+        if let Node::Meta(_) = node {
+            nodes.next_if(|n| matches!(n, Node::Newline));
+        } else {
+            lines.extend(collect_content(node));
+        }
+    }
+    let start = lines
+        .iter()
+        .position(|n| !matches!(n, Node::Newline))
+        .unwrap_or(lines.len());
+    let end = lines
+        .iter()
+        .rposition(|n| !matches!(n, Node::Newline))
+        .map_or(start, |i| i + 1);
+
+    lines[start..end].to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_source() {
+        let source = "# Song\nTags: #oldie\nKey: A#\nArtist: Me\nArrangement: Band\n\n\n\n## Verse\nLa [A#]la [Cm7/D#]la\n// Repeat twice\n##! Chorus\n[Bb]Sing\n> Chorus\n";
+        let formatted = ChorddownFormatter::new().format_source(source).unwrap();
+
+        assert_eq!(
+            formatted,
+            "# Song\nArtist: Me\nKey: Bb\nTags: #oldie\nArrangement: Band\n\n## Verse\nLa [Bb]la [Cm7/Eb]la\n// Repeat twice\n\n##! Chorus\n[Bb]Sing\n\n> Chorus\n"
+        );
+        // Formatting is idempotent
+        assert_eq!(
+            ChorddownFormatter::new().format_source(&formatted).unwrap(),
+            formatted
+        );
+    }
+
+    #[test]
+    fn test_format_source_with_semitone_notation() {
+        let source = "# Song\nB-Notation: H\n\n## Verse\nLa [Bb]la [H]la\n";
+        let formatted = ChorddownFormatter::new()
+            .with_semitone_notation(Some(SemitoneNotation::Sharp))
+            .format_source(source)
+            .unwrap();
+
+        assert_eq!(
+            formatted,
+            "# Song\nB-Notation: H\n\n## Verse\nLa [A#]la [H]la\n"
+        );
+    }
+
+    #[test]
+    fn test_format_source_keeps_blank_lines() {
+        let source = "# Song\nKey: G\n\n## Verse\n[G]Line one\n[C]Line two\n\n\n[G]Stanza two\n";
+        let formatted = ChorddownFormatter::new().format_source(source).unwrap();
+
+        assert_eq!(
+            formatted,
+            "# Song\nKey: G\n\n## Verse\n[G]Line one\n[C]Line two\n\n[G]Stanza two\n"
+        );
+    }
+
+    #[test]
+    fn test_format_source_with_detected_b_notation() {
+        let source = include_str!("../../tests/resources/german-test.chorddown");
+        let formatted = ChorddownFormatter::new().format_source(source).unwrap();
+
+        assert!(!formatted.contains("B-Notation"));
+        assert!(formatted.starts_with(
+            "# Überschrift\n\n[Am]Das ist ein Lied für die Umwandlungs[H]-Funktionen\n"
+        ));
+        assert!(
+            formatted.contains("## Die Braut von Korinth\n\nNach Korinthus von Athen gezogen\n")
+        );
+    }
+
+    #[test]
+    fn test_verify_content() {
+        let source = "# Song\n\n## Verse\n[H]Line one\n\n[G]Stanza two\n";
+
+        assert!(verify_content(source, source).is_ok());
+        assert!(verify_content(
+            source,
+            "# Song\n## Verse\n\n[H]Line one\n\n[G]Stanza two\n\n"
+        )
+        .is_ok());
+        assert!(
+            verify_content(source, "# Song\n\n## Verse\n[H]Line one\n[G]Stanza two\n").is_err()
+        );
+        assert!(verify_content(
+            source,
+            "# Song\nB-Notation: H\n\n## Verse\n[H]Line one\n\n[G]Stanza two\n"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_format_source_with_invalid_content() {
+        assert!(ChorddownFormatter::new()
+            .format_source("# Song\n\n## Verse\nLa [Xyz]la\n")
+            .is_err());
+        assert!(ChorddownFormatter::new()
+            .format_source("# Song\nKey: Xyz\n")
+            .is_err());
+    }
+}
@@ -1,12 +1,13 @@
 use std::io::BufRead;
 
 use crate::converter::{Converter, ConverterTrait};
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::models::chord::fmt::Formatting;
+use crate::models::file_type::FileType;
 use crate::models::song_meta_trait::SongMetaTrait;
 use crate::modification::transposition::TransposableTrait;
-use crate::parser::{Parser, ParserResult, ParserTrait};
-use crate::tokenizer::{build_tokenizer, Token, Tokenizer};
+use crate::parser::{Parser, ParserResult};
+use crate::tokenizer::{build_chord_pro_tokenizer, build_tokenizer, Token, Tokenizer};
 
 #[deprecated(note = "Please use the `Token`s directly")]
 pub fn token_lines_to_tokens(token_lines: Vec<Vec<Token>>) -> Vec<Token> {
@@ -21,12 +22,33 @@ pub fn token_lines_to_tokens(token_lines: Vec<Vec<Token>>) -> Vec<Token> {
 }
 
 pub fn parse_content<R: BufRead>(contents: R) -> Result<ParserResult> {
-    let (tokens, _warnings) = build_tokenizer().tokenize(contents)?;
-    Parser::new().parse(tokens)
+    let (tokens, _warnings) = build_tokenizer().tokenize_with_spans(contents)?;
+    Parser::new().parse_with_spans(tokens)
 }
 
-pub fn transpose_content<R: BufRead>(contents: R, semitones: isize) -> Result<ParserResult> {
-    let ParserResult { node, meta } = parse_content(contents)?;
+pub fn parse_content_for_file_type<R: BufRead>(
+    contents: R,
+    file_type: FileType,
+) -> Result<ParserResult> {
+    let (tokens, _warnings) = match file_type {
+        FileType::Chorddown => build_tokenizer().tokenize_with_spans(contents)?,
+        FileType::ChordPro => build_chord_pro_tokenizer().tokenize_with_spans(contents)?,
+        FileType::Jpeg => {
+            return Err(Error::file_type_error(format!(
+                "Can not parse content of type {}",
+                file_type
+            )))
+        }
+    };
+    Parser::new().parse_with_spans(tokens)
+}
+
+pub fn transpose_content<R: BufRead>(
+    contents: R,
+    file_type: FileType,
+    semitones: isize,
+) -> Result<ParserResult> {
+    let ParserResult { node, meta } = parse_content_for_file_type(contents, file_type)?;
 
     let transposed_node = node.transpose(semitones);
     let transposed_meta = meta.transpose(semitones);
@@ -36,19 +58,25 @@ pub fn transpose_content<R: BufRead>(contents: R, semitones: isize) -> Result<Pa
 
 pub fn convert_to_format<R: BufRead>(
     contents: R,
+    file_type: FileType,
     meta: &dyn SongMetaTrait,
     formatting: Formatting,
 ) -> Result<String> {
-    Converter::new().convert(parse_content(contents)?.node_as_ref(), meta, formatting)
+    Converter::new().convert(
+        parse_content_for_file_type(contents, file_type)?.node_as_ref(),
+        meta,
+        formatting,
+    )
 }
 
 pub fn transpose_and_convert_to_format<R: BufRead>(
     contents: R,
+    file_type: FileType,
     semitones: isize,
     _meta: &dyn SongMetaTrait,
     formatting: Formatting,
 ) -> Result<String> {
-    let result = transpose_content(contents, semitones)?;
+    let result = transpose_content(contents, file_type, semitones)?;
     Converter::new().convert(result.node_as_ref(), result.meta_as_ref(), formatting)
 }
 
@@ -73,3 +101,94 @@ pub(crate) fn validate_model_identifier(id: &str) -> Result<(), &'static str> {
         Err("Identifier can only contain alphanumerics, dash ('-'), underscore ('_'), @ or a dot ('.')")
     }
 }
+
+/// Hasher implementing the 64-bit FNV-1a algorithm
+///
+/// Unlike `std::collections::hash_map::DefaultHasher` the result is stable across Rust versions,
+/// so it can be persisted or sent to clients
+pub(crate) struct ContentHasher(u64);
+
+impl Default for ContentHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl std::hash::Hasher for ContentHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        self.0 = bytes.iter().fold(self.0, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Format;
+
+    #[test]
+    fn test_transpose_and_convert_chord_pro() {
+        let content = "{title: Swing Low}\n{key: C}\n\n{start_of_verse: Verse}\n[C]Swing [G]low\n";
+        let result = transpose_content(content.as_bytes(), FileType::ChordPro, 2).unwrap();
+        let html = transpose_and_convert_to_format(
+            content.as_bytes(),
+            FileType::ChordPro,
+            2,
+            result.meta_as_ref(),
+            Formatting::with_format(Format::HTML),
+        )
+        .unwrap();
+
+        assert!(html.contains("<h1>Swing Low</h1>"));
+        assert!(html.contains("<h2>Verse</h2>"));
+        assert!(html.contains("data-chord='D'"));
+        assert!(html.contains("data-chord='A'"));
+        assert!(!html.contains("{title"));
+
+        let html = convert_to_format(
+            content.as_bytes(),
+            FileType::ChordPro,
+            result.meta_as_ref(),
+            Formatting::with_format(Format::HTML),
+        )
+        .unwrap();
+        assert!(html.contains("<h1>Swing Low</h1>"));
+        assert!(html.contains("data-chord='C'"));
+    }
+
+    #[test]
+    fn test_transpose_and_convert_uses_key_signature() {
+        let content = "# Swing Low\nKey: C\n\n## Verse\n[C]Swing [F]low [G]sweet\n";
+        let result = transpose_content(content.as_bytes(), FileType::Chorddown, 5).unwrap();
+        let convert = |formatting: Formatting| {
+            transpose_and_convert_to_format(
+                content.as_bytes(),
+                FileType::Chorddown,
+                5,
+                result.meta_as_ref(),
+                formatting,
+            )
+            .unwrap()
+        };
+
+        // F major is written with flats
+        let html = convert(Formatting::with_format(Format::HTML));
+        assert!(html.contains("data-chord='F'"));
+        assert!(html.contains("data-chord='Bb'"));
+        assert!(!html.contains("data-chord='A#'"));
+
+        // An explicit notation is kept
+        let html = convert(Formatting {
+            semitone_notation_for_key: false,
+            ..Formatting::with_format(Format::HTML)
+        });
+        assert!(html.contains("data-chord='A#'"));
+    }
+}
@@ -0,0 +1,258 @@
+//! Key Detector estimates the key of a song from its chords
+//!
+//! Each of the 24 major and minor keys is scored by
+//!  - how often the song's chords belong to the key (weighted by their harmonic function)
+//!  - whether the first and last chord are the key's tonic
+//!  - the number of authentic (`V -> I`) and plagal (`IV -> I`) cadences
+use serde::{Deserialize, Serialize};
+
+use crate::models::chord::{Chord, Chords, Note, Quality};
+use crate::parser::Node;
+
+/// Scale degrees (in semitones above the tonic) and chord qualities of the diatonic chords of
+/// a major key together with their weight
+const MAJOR_DIATONIC_CHORDS: [(i32, Quality, f32); 7] = [
+    (0, Quality::Major, 1.5),
+    (2, Quality::Minor, 1.0),
+    (4, Quality::Minor, 0.8),
+    (5, Quality::Major, 1.2),
+    (7, Quality::Major, 1.2),
+    (9, Quality::Minor, 1.0),
+    (11, Quality::Diminished, 0.8),
+];
+
+/// Diatonic chords of a (natural and harmonic) minor key
+const MINOR_DIATONIC_CHORDS: [(i32, Quality, f32); 8] = [
+    (0, Quality::Minor, 1.5),
+    (2, Quality::Diminished, 0.8),
+    (3, Quality::Major, 1.0),
+    (5, Quality::Minor, 1.2),
+    (7, Quality::Minor, 1.0),
+    (7, Quality::Major, 1.2),
+    (8, Quality::Major, 1.0),
+    (10, Quality::Major, 1.0),
+];
+
+/// Score for chords whose root belongs to the key but whose quality does not match
+const ROOT_ONLY_SCORE: f32 = 0.3;
+/// Score for chords that do not belong to the key
+const NON_DIATONIC_SCORE: f32 = -0.5;
+const FIRST_CHORD_TONIC_SCORE: f32 = 2.0;
+const LAST_CHORD_TONIC_SCORE: f32 = 3.0;
+const AUTHENTIC_CADENCE_SCORE: f32 = 1.0;
+const PLAGAL_CADENCE_SCORE: f32 = 0.5;
+
+/// Estimated key of a song
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub struct KeyGuess {
+    /// Estimated key (minor keys are represented as minor chords, e.g. `Am`)
+    pub key: Chord,
+    /// Confidence of the guess between `0.0` and `1.0`
+    pub confidence: f32,
+}
+
+impl KeyGuess {
+    pub fn new(key: Chord, confidence: f32) -> Self {
+        Self { key, confidence }
+    }
+}
+
+/// Chord reduced to the information relevant for the key detection
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct HarmonicChord {
+    root: Note,
+    quality: Quality,
+}
+
+impl From<&Chords> for HarmonicChord {
+    fn from(chords: &Chords) -> Self {
+        let chord = chords.chord();
+        let quality = match chord.structure() {
+            Ok(structure) => structure.quality(),
+            Err(_) => match chord.variant() {
+                Some(v) if v.starts_with('m') && !v.starts_with("maj") => Quality::Minor,
+                _ => Quality::Major,
+            },
+        };
+
+        Self {
+            root: chord.root(),
+            quality,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct KeyDetector {}
+
+impl KeyDetector {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Return the most likely key of the song
+    pub fn detect_key(&self, node: &Node) -> Option<KeyGuess> {
+        self.detect(node).into_iter().next()
+    }
+
+    /// Return the possible keys of the song ranked by their confidence
+    ///
+    /// Keys that do not fit the song's chords at all are not included. The result is empty if
+    /// the song does not contain any chords
+    pub fn detect(&self, node: &Node) -> Vec<KeyGuess> {
+        let chords: Vec<HarmonicChord> = node.chords().into_iter().map(Into::into).collect();
+        if chords.is_empty() {
+            return vec![];
+        }
+
+        let mut scores: Vec<(Chord, f32)> = (1..=12)
+            .flat_map(|i| {
+                let tonic = Note::from(i as isize);
+                [
+                    (
+                        Chord::new_without_variant(tonic),
+                        score_key(&chords, tonic, &MAJOR_DIATONIC_CHORDS, Quality::Major),
+                    ),
+                    (
+                        Chord::new_with_variant(tonic, "m"),
+                        score_key(&chords, tonic, &MINOR_DIATONIC_CHORDS, Quality::Minor),
+                    ),
+                ]
+            })
+            .filter(|(_, score)| *score > 0.0)
+            .collect();
+
+        // The sort is stable: On equal scores major keys and lower roots are ranked first
+        scores.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+        // Squaring the scores emphasizes the differences between the best matching keys
+        let total: f32 = scores.iter().map(|(_, score)| score * score).sum();
+        scores
+            .into_iter()
+            .map(|(key, score)| KeyGuess::new(key, score * score / total))
+            .collect()
+    }
+}
+
+fn score_key(
+    chords: &[HarmonicChord],
+    tonic: Note,
+    diatonic_chords: &[(i32, Quality, f32)],
+    tonic_quality: Quality,
+) -> f32 {
+    let degree = |chord: &HarmonicChord| (i32::from(chord.root) - i32::from(tonic)).rem_euclid(12);
+    let is_tonic =
+        |chord: &HarmonicChord| degree(chord) == 0 && quality_matches(chord.quality, tonic_quality);
+
+    let mut score: f32 = chords
+        .iter()
+        .map(|chord| {
+            let degree = degree(chord);
+            let matching_root = diatonic_chords.iter().filter(|(d, _, _)| *d == degree);
+            match matching_root
+                .clone()
+                .find(|(_, quality, _)| quality_matches(chord.quality, *quality))
+            {
+                Some((_, _, weight)) => *weight,
+                None if matching_root.count() > 0 => ROOT_ONLY_SCORE,
+                None => NON_DIATONIC_SCORE,
+            }
+        })
+        .sum();
+
+    if chords.first().is_some_and(is_tonic) {
+        score += FIRST_CHORD_TONIC_SCORE;
+    }
+    if chords.last().is_some_and(is_tonic) {
+        score += LAST_CHORD_TONIC_SCORE;
+    }
+    for pair in chords.windows(2) {
+        if !is_tonic(&pair[1]) {
+            continue;
+        }
+        match degree(&pair[0]) {
+            7 if pair[0].quality != Quality::Minor => score += AUTHENTIC_CADENCE_SCORE,
+            5 => score += PLAGAL_CADENCE_SCORE,
+            _ => {}
+        }
+    }
+
+    score
+}
+
+/// Power chords (e.g. `A5`) fit both major and minor keys
+fn quality_matches(chord_quality: Quality, expected: Quality) -> bool {
+    chord_quality == expected || chord_quality == Quality::Power
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::helper::parse_content;
+    use crate::models::chord::fmt::Formatting;
+    use crate::models::chord::NoteDisplay;
+    use crate::test_helpers::get_test_ast;
+
+    use super::*;
+
+    fn detect(content: &str) -> Vec<KeyGuess> {
+        KeyDetector::new().detect(parse_content(content.as_bytes()).unwrap().node_as_ref())
+    }
+
+    fn key_name(guess: &KeyGuess) -> String {
+        guess.key.note_format(Formatting::default())
+    }
+
+    #[test]
+    fn test_detect_major_key() {
+        let guesses = KeyDetector::new().detect(&get_test_ast());
+
+        assert_eq!(key_name(&guesses[0]), "D");
+        assert!(guesses[0].confidence > guesses[1].confidence);
+    }
+
+    #[test]
+    fn test_detect_minor_key() {
+        let guesses = detect("# Song\n\n## Verse\n[Am]One [Dm]two [G]three [C]four\n[F]five [Dm]six [E7]seven [Am]eight\n");
+
+        assert_eq!(key_name(&guesses[0]), "Am");
+        // The relative major key is a reasonable second guess
+        assert_eq!(key_name(&guesses[1]), "C");
+    }
+
+    #[test]
+    fn test_detect_relative_major_key() {
+        let guesses = detect(
+            "# Song\n\n## Verse\n[C]One [Am]two [F]three [G]four\n[C]five [Am]six [G]seven [C]eight\n",
+        );
+
+        assert_eq!(key_name(&guesses[0]), "C");
+    }
+
+    #[test]
+    fn test_detect_key() {
+        let guess = KeyDetector::new()
+            .detect_key(
+                parse_content("# Song\n\n## Verse\n[G]One [C]two [D7]three [G]four\n".as_bytes())
+                    .unwrap()
+                    .node_as_ref(),
+            )
+            .unwrap();
+
+        assert_eq!(key_name(&guess), "G");
+        assert!(guess.confidence > 0.0 && guess.confidence <= 1.0);
+    }
+
+    #[test]
+    fn test_detect_confidence_sum() {
+        let guesses = KeyDetector::new().detect(&get_test_ast());
+        let sum: f32 = guesses.iter().map(|g| g.confidence).sum();
+
+        assert!((sum - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_detect_without_chords() {
+        assert!(detect("# Song\n\n## Verse\nJust some text\n").is_empty());
+        assert_eq!(KeyDetector::new().detect_key(&Node::Document(vec![])), None);
+    }
+}
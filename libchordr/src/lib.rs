@@ -1,16 +1,23 @@
 mod catalog_builder;
+mod chord_diagram;
 mod converter;
 pub mod data_exchange;
 mod error;
 mod format;
+mod formatter;
 mod helper;
 mod html;
+mod key_detector;
 pub mod models;
 pub mod modification;
 mod parser;
+#[cfg(feature = "pdf")]
+mod pdf;
 pub mod prelude;
 mod repeat_detector;
 mod search;
+mod songbook;
 #[doc(hidden)]
 pub mod test_helpers;
 mod tokenizer;
+mod validation;
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::models::catalog::{Catalog, CatalogTrait};
+use crate::models::list::ListEntryTrait;
+use crate::models::song::Song;
+use crate::models::song_id::SongId;
+
+/// Changes between two revisions of a [`Catalog`]
+///
+/// Transferring the diff instead of the whole Catalog allows clients that already have the base
+/// revision to update with only the changed Songs
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub struct CatalogDiff {
+    /// Revision the diff has to be applied to
+    base_revision: String,
+    /// Revision of the Catalog after the diff has been applied
+    revision: String,
+    #[serde(default)]
+    built_at: Option<String>,
+    added: Vec<Song>,
+    modified: Vec<Song>,
+    removed: Vec<SongId>,
+}
+
+impl CatalogDiff {
+    /// Compute the changes from the `base` Catalog to the `target` Catalog
+    pub fn compute(base: &Catalog, target: &Catalog) -> Self {
+        let base_songs: HashMap<SongId, &Song> = base.iter().map(|s| (s.id(), s)).collect();
+
+        let mut added = vec![];
+        let mut modified = vec![];
+        for song in target.iter() {
+            match base_songs.get(&song.id()) {
+                None => added.push(song.clone()),
+                Some(base_song) if *base_song != song => modified.push(song.clone()),
+                Some(_) => {}
+            }
+        }
+
+        let mut removed: Vec<SongId> = base
+            .iter()
+            .map(|s| s.id())
+            .filter(|id| target.get(id).is_none())
+            .collect();
+        removed.sort();
+
+        Self {
+            base_revision: base.revision(),
+            revision: target.revision(),
+            built_at: target.built_at().map(str::to_owned),
+            added,
+            modified,
+            removed,
+        }
+    }
+
+    /// Apply the changes to the `base` Catalog and return the Catalog of the new revision
+    ///
+    /// Returns an error if `base` is not the revision the diff was computed from
+    pub fn apply(&self, base: &Catalog) -> Result<Catalog> {
+        if base.revision() != self.base_revision {
+            return Err(Error::catalog_error(format!(
+                "Catalog diff for revision '{}' can not be applied to revision '{}'",
+                self.base_revision,
+                base.revision()
+            )));
+        }
+
+        let mut songs: HashMap<SongId, Song> = base.iter().map(|s| (s.id(), s.clone())).collect();
+        for id in &self.removed {
+            songs.remove(id);
+        }
+        for song in self.added.iter().chain(self.modified.iter()) {
+            songs.insert(song.id(), song.clone());
+        }
+
+        let mut songs: Vec<Song> = songs.into_values().collect();
+        songs.sort_by_key(|s| s.id());
+
+        let catalog = Catalog::new(self.revision.clone(), songs);
+        Ok(match &self.built_at {
+            Some(built_at) => catalog.with_built_at(built_at.clone()),
+            None => catalog,
+        })
+    }
+
+    /// Revision the diff has to be applied to
+    pub fn base_revision(&self) -> &str {
+        &self.base_revision
+    }
+
+    /// Revision of the Catalog after the diff has been applied
+    pub fn revision(&self) -> &str {
+        &self.revision
+    }
+
+    pub fn added(&self) -> &[Song] {
+        &self.added
+    }
+
+    pub fn modified(&self) -> &[Song] {
+        &self.modified
+    }
+
+    pub fn removed(&self) -> &[SongId] {
+        &self.removed
+    }
+
+    /// Return if the two revisions contain the same Songs
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.removed.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::get_test_catalog;
+
+    #[test]
+    fn test_compute_and_apply() {
+        let songs: Vec<Song> = get_test_catalog().into_iter().collect();
+        let base = Catalog::from_songs(songs[0..3].to_vec());
+
+        let changed_song = Song::new(
+            songs[1].meta().clone(),
+            format!("{}\nMore lyrics", songs[1].src()),
+        );
+        let target = Catalog::from_songs(vec![
+            songs[0].clone(),
+            changed_song.clone(),
+            songs[3].clone(),
+        ])
+        .with_built_at("Sat, 17 Oct 2026 10:00:00 +0200");
+
+        let diff = CatalogDiff::compute(&base, &target);
+        assert_eq!(diff.base_revision(), base.revision());
+        assert_eq!(diff.revision(), target.revision());
+        assert_eq!(diff.added(), &[songs[3].clone()]);
+        assert_eq!(diff.modified(), &[changed_song]);
+        assert_eq!(diff.removed(), &[songs[2].id()]);
+        assert!(!diff.is_empty());
+
+        assert_eq!(diff.apply(&base).unwrap(), target);
+        assert!(CatalogDiff::compute(&target, &target).is_empty());
+    }
+
+    #[test]
+    fn test_apply_to_wrong_revision() {
+        let songs: Vec<Song> = get_test_catalog().into_iter().collect();
+        let base = Catalog::from_songs(songs[0..2].to_vec());
+        let target = Catalog::from_songs(songs[0..3].to_vec());
+
+        let diff = CatalogDiff::compute(&base, &target);
+        assert!(diff.apply(&target).is_err());
+    }
+}
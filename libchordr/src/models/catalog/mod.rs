@@ -1,20 +1,27 @@
+use std::hash::Hasher;
 use std::slice::Iter;
 use std::vec::IntoIter;
 
 use serde::{Deserialize, Serialize};
 
+use crate::helper::ContentHasher;
 use crate::models::list::ListEntryTrait;
 use crate::models::song::Song;
 use crate::models::song_id::SongId;
 use crate::prelude::RecordTrait;
 
+pub use self::catalog_diff::CatalogDiff;
 pub use self::catalog_trait::CatalogTrait;
 
+mod catalog_diff;
 pub mod catalog_trait;
 
 #[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
 pub struct Catalog {
     revision: String,
+    /// Date and time the Catalog was built (RFC 2822)
+    #[serde(default)]
+    built_at: Option<String>,
     songs: Vec<Song>,
 }
 
@@ -22,9 +29,45 @@ impl Catalog {
     pub fn new<S: Into<String>>(revision: S, songs: Vec<Song>) -> Self {
         Self {
             revision: revision.into(),
+            built_at: None,
             songs,
         }
     }
+
+    /// Build a new Catalog with a revision derived from the content of the Songs
+    ///
+    /// Catalogs with the same Songs have the same revision, independent of the order of the Songs
+    /// and the time they were built
+    pub fn from_songs(songs: Vec<Song>) -> Self {
+        Self::new(content_revision(&songs), songs)
+    }
+
+    pub fn with_built_at<S: Into<String>>(self, built_at: S) -> Self {
+        Self {
+            built_at: Some(built_at.into()),
+            ..self
+        }
+    }
+
+    /// Return the date and time the Catalog was built (RFC 2822)
+    pub fn built_at(&self) -> Option<&str> {
+        self.built_at.as_deref()
+    }
+}
+
+fn content_revision(songs: &[Song]) -> String {
+    let mut sorted_songs: Vec<&Song> = songs.iter().collect();
+    sorted_songs.sort_by_key(|song| song.id());
+
+    let mut hasher = ContentHasher::default();
+    for song in sorted_songs {
+        hasher.write(song.id().as_str().as_bytes());
+        hasher.write_u8(0);
+        hasher.write(song.src().as_bytes());
+        hasher.write_u8(0);
+    }
+
+    format!("{:016x}", hasher.finish())
 }
 
 impl CatalogTrait<Song> for Catalog {
@@ -67,3 +110,35 @@ impl RecordTrait for Catalog {
         self.revision.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::get_test_catalog;
+
+    #[test]
+    fn test_from_songs() {
+        let songs: Vec<Song> = get_test_catalog().into_iter().collect();
+        let a = songs[0].clone();
+        let b = songs[1].clone();
+        let revision = Catalog::from_songs(vec![a.clone(), b.clone()]).revision();
+
+        assert_eq!(revision.len(), 16);
+        assert_eq!(
+            Catalog::from_songs(vec![b.clone(), a.clone()]).revision(),
+            revision
+        );
+        assert_ne!(Catalog::from_songs(vec![a.clone()]).revision(), revision);
+
+        let changed_b = Song::new(b.meta().clone(), format!("{}\nMore lyrics", b.src()));
+        assert_ne!(Catalog::from_songs(vec![a, changed_b]).revision(), revision);
+    }
+
+    #[test]
+    fn test_deserialize_without_built_at() {
+        let catalog: Catalog = serde_json::from_str(r#"{"revision":"1","songs":[]}"#).unwrap();
+
+        assert_eq!(catalog.revision(), "1");
+        assert_eq!(catalog.built_at(), None);
+    }
+}
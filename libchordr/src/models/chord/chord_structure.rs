@@ -0,0 +1,644 @@
+use std::fmt::{Display, Formatter};
+
+use crate::error::Error;
+use crate::models::chord::fmt::Formatting;
+use crate::models::chord::{Chord, Note, NoteDisplay};
+use crate::modification::transposition::TransposableTrait;
+
+/// Quality of the underlying triad
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Quality {
+    Major,
+    Minor,
+    Diminished,
+    Augmented,
+    /// Power chord consisting only of root and fifth (e.g. `E5`)
+    Power,
+}
+
+impl Quality {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Quality::Major => "",
+            Quality::Minor => "m",
+            Quality::Diminished => "dim",
+            Quality::Augmented => "aug",
+            Quality::Power => "5",
+        }
+    }
+
+    fn intervals(&self) -> &'static [u8] {
+        match self {
+            Quality::Major => &[0, 4, 7],
+            Quality::Minor => &[0, 3, 7],
+            Quality::Diminished => &[0, 3, 6],
+            Quality::Augmented => &[0, 4, 8],
+            Quality::Power => &[0, 7],
+        }
+    }
+}
+
+/// Sixth, seventh and the stacked extensions built upon the seventh
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Extension {
+    Sixth,
+    Seventh,
+    MajorSeventh,
+    Ninth,
+    MajorNinth,
+    Eleventh,
+    MajorEleventh,
+    Thirteenth,
+    MajorThirteenth,
+}
+
+impl Extension {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Extension::Sixth => "6",
+            Extension::Seventh => "7",
+            Extension::MajorSeventh => "maj7",
+            Extension::Ninth => "9",
+            Extension::MajorNinth => "maj9",
+            Extension::Eleventh => "11",
+            Extension::MajorEleventh => "maj11",
+            Extension::Thirteenth => "13",
+            Extension::MajorThirteenth => "maj13",
+        }
+    }
+
+    fn from_degree(degree: u8, major: bool) -> Option<Self> {
+        match (degree, major) {
+            (6, false) => Some(Extension::Sixth),
+            (7, false) => Some(Extension::Seventh),
+            (7, true) => Some(Extension::MajorSeventh),
+            (9, false) => Some(Extension::Ninth),
+            (9, true) => Some(Extension::MajorNinth),
+            (11, false) => Some(Extension::Eleventh),
+            (11, true) => Some(Extension::MajorEleventh),
+            (13, false) => Some(Extension::Thirteenth),
+            (13, true) => Some(Extension::MajorThirteenth),
+            _ => None,
+        }
+    }
+
+    fn is_major(&self) -> bool {
+        matches!(
+            self,
+            Extension::MajorSeventh
+                | Extension::MajorNinth
+                | Extension::MajorEleventh
+                | Extension::MajorThirteenth
+        )
+    }
+
+    /// Intervals added on top of the triad (without the seventh)
+    fn upper_intervals(&self) -> &'static [u8] {
+        match self {
+            Extension::Sixth => &[9],
+            Extension::Seventh | Extension::MajorSeventh => &[],
+            Extension::Ninth | Extension::MajorNinth => &[14],
+            Extension::Eleventh | Extension::MajorEleventh => &[14, 17],
+            Extension::Thirteenth | Extension::MajorThirteenth => &[14, 17, 21],
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Suspension {
+    Sus2,
+    Sus4,
+}
+
+impl Suspension {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Suspension::Sus2 => "sus2",
+            Suspension::Sus4 => "sus4",
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Accidental {
+    Flat,
+    Sharp,
+}
+
+/// Chromatically altered chord tone (e.g. `b5` or `#9`)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Alteration {
+    pub accidental: Accidental,
+    pub degree: u8,
+}
+
+impl Display for Alteration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.accidental {
+            Accidental::Flat => write!(f, "b{}", self.degree),
+            Accidental::Sharp => write!(f, "#{}", self.degree),
+        }
+    }
+}
+
+/// Parsed representation of a chord
+///
+/// `Chord` only stores the root and the raw variant (e.g. `m7b5`). `ChordStructure` splits the
+/// variant into its components and validates it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChordStructure {
+    root: Note,
+    quality: Quality,
+    extension: Option<Extension>,
+    suspension: Option<Suspension>,
+    additions: Vec<u8>,
+    alterations: Vec<Alteration>,
+    bass: Option<Note>,
+}
+
+impl ChordStructure {
+    /// Parse the structure of the `Chord`'s variant
+    pub fn try_from_chord(chord: &Chord) -> Result<Self, Error> {
+        let mut structure = Self {
+            root: chord.root(),
+            quality: Quality::Major,
+            extension: None,
+            suspension: None,
+            additions: vec![],
+            alterations: vec![],
+            bass: None,
+        };
+        if let Some(variant) = chord.variant() {
+            VariantParser::new(variant).parse_into(&mut structure)?;
+        }
+
+        Ok(structure)
+    }
+
+    pub fn root(&self) -> Note {
+        self.root
+    }
+
+    pub fn quality(&self) -> Quality {
+        self.quality
+    }
+
+    pub fn extension(&self) -> Option<Extension> {
+        self.extension
+    }
+
+    pub fn suspension(&self) -> Option<Suspension> {
+        self.suspension
+    }
+
+    /// Added degrees (e.g. `9` for `add9`)
+    pub fn additions(&self) -> &[u8] {
+        &self.additions
+    }
+
+    pub fn alterations(&self) -> &[Alteration] {
+        &self.alterations
+    }
+
+    /// Bass note of an inversion or slash chord
+    pub fn bass(&self) -> Option<Note> {
+        self.bass
+    }
+
+    pub fn with_bass(self, bass: Option<Note>) -> Self {
+        Self { bass, ..self }
+    }
+
+    /// Return the chord reduced to its triad (keeping suspensions and the bass note)
+    pub fn simplify(&self) -> Self {
+        Self {
+            extension: None,
+            additions: vec![],
+            alterations: vec![],
+            ..self.clone()
+        }
+    }
+
+    /// Semitone offsets of the chord tones relative to the root
+    ///
+    /// Extensions above the octave are reported as compound intervals (e.g. `14` for the ninth)
+    pub fn intervals(&self) -> Vec<u8> {
+        let mut intervals = self.quality.intervals().to_vec();
+        match self.suspension {
+            Some(Suspension::Sus2) => replace_third(&mut intervals, 2),
+            Some(Suspension::Sus4) => replace_third(&mut intervals, 5),
+            None => {}
+        }
+        if let Some(extension) = self.extension {
+            match (extension, self.quality) {
+                (Extension::Sixth, _) => {}
+                (Extension::Seventh, Quality::Diminished) => intervals.push(9),
+                (e, _) if e.is_major() => intervals.push(11),
+                _ => intervals.push(10),
+            }
+            intervals.extend_from_slice(extension.upper_intervals());
+        }
+        for degree in &self.additions {
+            intervals.push(degree_to_interval(*degree));
+        }
+        for alteration in &self.alterations {
+            let natural = degree_to_interval(alteration.degree);
+            let altered = match alteration.accidental {
+                Accidental::Flat => natural - 1,
+                Accidental::Sharp => natural + 1,
+            };
+            intervals.retain(|i| *i != natural);
+            intervals.push(altered);
+        }
+
+        intervals.sort_unstable();
+        intervals.dedup();
+        intervals
+    }
+}
+
+impl NoteDisplay for ChordStructure {
+    fn note_format(&self, formatting: Formatting) -> String {
+        let mut output = self.root.note_format(formatting);
+        // Power chords can not have any further components
+        if self.quality == Quality::Power {
+            output.push_str(Quality::Power.as_str());
+        } else {
+            output.push_str(self.quality.as_str());
+            if let Some(extension) = self.extension {
+                let six_nine = extension == Extension::Sixth && self.additions.first() == Some(&9);
+                output.push_str(if six_nine { "69" } else { extension.as_str() });
+            }
+            if let Some(suspension) = self.suspension {
+                output.push_str(suspension.as_str());
+            }
+            for (i, degree) in self.additions.iter().enumerate() {
+                if i == 0 && self.extension == Some(Extension::Sixth) && *degree == 9 {
+                    continue;
+                }
+                output.push_str(&format!("add{}", degree));
+            }
+            for alteration in &self.alterations {
+                output.push_str(&alteration.to_string());
+            }
+        }
+        if let Some(bass) = self.bass {
+            output.push('/');
+            output.push_str(&bass.note_format(formatting));
+        }
+
+        output
+    }
+}
+
+impl TransposableTrait for ChordStructure {
+    fn transpose(self, semitones: isize) -> Self {
+        Self {
+            root: self.root.transpose(semitones),
+            bass: self.bass.map(|b| b.transpose(semitones)),
+            ..self
+        }
+    }
+}
+
+fn replace_third(intervals: &mut Vec<u8>, replacement: u8) {
+    intervals.retain(|i| *i != 3 && *i != 4);
+    intervals.push(replacement);
+}
+
+fn degree_to_interval(degree: u8) -> u8 {
+    match degree {
+        2 => 2,
+        4 => 5,
+        5 => 7,
+        6 => 9,
+        9 => 14,
+        11 => 17,
+        13 => 21,
+        _ => unreachable!("Degree {} is not supported", degree),
+    }
+}
+
+/// Parser for the variant part of a chord (everything after the root)
+struct VariantParser<'a> {
+    variant: &'a str,
+    rest: &'a str,
+}
+
+impl<'a> VariantParser<'a> {
+    fn new(variant: &'a str) -> Self {
+        Self {
+            variant,
+            rest: variant.trim(),
+        }
+    }
+
+    fn parse_into(mut self, structure: &mut ChordStructure) -> Result<(), Error> {
+        if self.rest == "5" {
+            structure.quality = Quality::Power;
+            return Ok(());
+        }
+        if self.consume_any(&["ø7", "ø"]) {
+            structure.quality = Quality::Minor;
+            structure.extension = Some(Extension::Seventh);
+            structure.alterations.push(Alteration {
+                accidental: Accidental::Flat,
+                degree: 5,
+            });
+        } else {
+            structure.quality = self.parse_quality();
+            structure.extension = self.parse_extension(structure)?;
+        }
+
+        while !self.rest.is_empty() {
+            // Parentheses are only used for grouping (e.g. `C7(b9)`)
+            if self.consume_any(&["(", ")", ","]) {
+                continue;
+            }
+            if self.consume_any(&["sus2"]) {
+                self.set_suspension(structure, Suspension::Sus2)?;
+            } else if self.consume_any(&["sus4", "sus"]) {
+                self.set_suspension(structure, Suspension::Sus4)?;
+            } else if self.consume_any(&["add"]) {
+                let degree = self.parse_degree(&[2, 4, 9, 11, 13])?;
+                structure.additions.push(degree);
+            } else if let Some(accidental) = self.parse_accidental() {
+                let degree = self.parse_degree(&[5, 9, 11, 13])?;
+                structure
+                    .alterations
+                    .push(Alteration { accidental, degree });
+            } else {
+                return Err(self.error(format!("unexpected '{}'", self.rest)));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn parse_quality(&mut self) -> Quality {
+        // `maj7`, `M7` and `Δ` describe the seventh and are handled in `parse_extension()`
+        if self.rest.starts_with("maj") || self.rest.starts_with('M') || self.rest.starts_with('Δ')
+        {
+            Quality::Major
+        } else if self.consume_any(&["min", "mi", "m", "-"]) {
+            Quality::Minor
+        } else if self.consume_any(&["dim", "°", "o"]) {
+            Quality::Diminished
+        } else if self.consume_any(&["aug", "+"]) {
+            Quality::Augmented
+        } else {
+            Quality::Major
+        }
+    }
+
+    fn parse_extension(
+        &mut self,
+        structure: &mut ChordStructure,
+    ) -> Result<Option<Extension>, Error> {
+        if self.consume_any(&["Δ"]) {
+            // `Δ` alone stands for the major seventh
+            let degree = self.parse_number()?.unwrap_or(7);
+            return Extension::from_degree(degree, true)
+                .map(Some)
+                .ok_or_else(|| self.error(format!("invalid major extension {}", degree)));
+        }
+        // The major seventh of minor chords is often parenthesized (e.g. `Cm(maj7)`), the
+        // closing parenthesis is skipped in `parse_into()`
+        let major = self.consume_any(&["maj", "Maj", "M", "(maj", "(Maj", "(M"]);
+        match self.parse_number()? {
+            Some(69) if !major => {
+                structure.additions.push(9);
+                Ok(Some(Extension::Sixth))
+            }
+            // `D2` and `A4` are short for the suspended chords, or additions if there is a third
+            Some(degree @ (2 | 4)) if !major => {
+                if structure.quality == Quality::Major {
+                    let suspension = if degree == 2 {
+                        Suspension::Sus2
+                    } else {
+                        Suspension::Sus4
+                    };
+                    self.set_suspension(structure, suspension)?;
+                } else {
+                    structure.additions.push(degree);
+                }
+                Ok(None)
+            }
+            Some(degree) => match Extension::from_degree(degree, major) {
+                Some(extension) => Ok(Some(extension)),
+                None => Err(self.error(format!("invalid extension {}", degree))),
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn set_suspension(
+        &self,
+        structure: &mut ChordStructure,
+        suspension: Suspension,
+    ) -> Result<(), Error> {
+        if structure.suspension.is_some() {
+            return Err(self.error("multiple suspensions"));
+        }
+        if structure.quality != Quality::Major {
+            return Err(self.error("suspended chords must not have a third"));
+        }
+        structure.suspension = Some(suspension);
+        Ok(())
+    }
+
+    fn parse_accidental(&mut self) -> Option<Accidental> {
+        if self.consume_any(&["b", "♭"]) {
+            Some(Accidental::Flat)
+        } else if self.consume_any(&["#", "♯"]) {
+            Some(Accidental::Sharp)
+        } else {
+            None
+        }
+    }
+
+    fn parse_degree(&mut self, allowed: &[u8]) -> Result<u8, Error> {
+        match self.parse_number()? {
+            Some(degree) if allowed.contains(&degree) => Ok(degree),
+            Some(degree) => Err(self.error(format!("invalid degree {}", degree))),
+            None => Err(self.error("missing degree")),
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<Option<u8>, Error> {
+        let length = self
+            .rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(self.rest.len());
+        if length == 0 {
+            return Ok(None);
+        }
+        let (number, rest) = self.rest.split_at(length);
+        self.rest = rest;
+        match number.parse() {
+            Ok(number) => Ok(Some(number)),
+            Err(_) => Err(self.error(format!("invalid number {}", number))),
+        }
+    }
+
+    fn consume_any(&mut self, prefixes: &[&str]) -> bool {
+        for prefix in prefixes {
+            if let Some(rest) = self.rest.strip_prefix(prefix) {
+                self.rest = rest;
+                return true;
+            }
+        }
+        false
+    }
+
+    fn error<S: AsRef<str>>(&self, message: S) -> Error {
+        Error::chord_error(format!(
+            "Invalid chord variant '{}': {}",
+            self.variant,
+            message.as_ref()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::format::Format;
+    use crate::models::meta::BNotation;
+
+    use super::*;
+
+    fn parse(input: &str) -> Result<ChordStructure, Error> {
+        ChordStructure::try_from_chord(&Chord::try_from(input, BNotation::B).unwrap())
+    }
+
+    #[test]
+    fn test_try_from_chord() {
+        let structure = parse("C").unwrap();
+        assert_eq!(structure.root(), Note::C);
+        assert_eq!(structure.quality(), Quality::Major);
+        assert_eq!(structure.extension(), None);
+
+        let structure = parse("Am7").unwrap();
+        assert_eq!(structure.root(), Note::A);
+        assert_eq!(structure.quality(), Quality::Minor);
+        assert_eq!(structure.extension(), Some(Extension::Seventh));
+
+        let structure = parse("F#7sus4").unwrap();
+        assert_eq!(structure.root(), Note::Fis);
+        assert_eq!(structure.extension(), Some(Extension::Seventh));
+        assert_eq!(structure.suspension(), Some(Suspension::Sus4));
+
+        let structure = parse("Fmaj13#11").unwrap();
+        assert_eq!(structure.extension(), Some(Extension::MajorThirteenth));
+        assert_eq!(
+            structure.alterations(),
+            &[Alteration {
+                accidental: Accidental::Sharp,
+                degree: 11
+            }]
+        );
+
+        let structure = parse("C#madd2add4").unwrap();
+        assert_eq!(structure.quality(), Quality::Minor);
+        assert_eq!(structure.additions(), &[2, 4]);
+
+        assert_eq!(parse("E5").unwrap().quality(), Quality::Power);
+        assert_eq!(parse("Bdim7").unwrap().quality(), Quality::Diminished);
+        assert_eq!(parse("G+").unwrap().quality(), Quality::Augmented);
+    }
+
+    #[test]
+    fn test_try_from_chord_shorthand_suspension() {
+        let structure = parse("D2").unwrap();
+        assert_eq!(structure.quality(), Quality::Major);
+        assert_eq!(structure.suspension(), Some(Suspension::Sus2));
+        assert_eq!(parse("A4").unwrap().suspension(), Some(Suspension::Sus4));
+        assert_eq!(parse("Am2").unwrap().additions(), &[2]);
+    }
+
+    #[test]
+    fn test_try_from_chord_minor_major_seventh() {
+        let structure = parse("Cm(maj7)").unwrap();
+        assert_eq!(structure.quality(), Quality::Minor);
+        assert_eq!(structure.extension(), Some(Extension::MajorSeventh));
+    }
+
+    #[test]
+    fn test_try_from_chord_capitalized_major() {
+        let structure = parse("CMaj7").unwrap();
+        assert_eq!(structure.quality(), Quality::Major);
+        assert_eq!(structure.extension(), Some(Extension::MajorSeventh));
+    }
+
+    #[test]
+    fn test_try_from_chord_invalid() {
+        assert!(parse("D#9Daniel").is_err());
+        assert!(parse("C8").is_err());
+        assert_eq!(
+            parse("C999").unwrap_err().to_string(),
+            "Invalid chord variant '999': invalid number 999"
+        );
+        assert!(parse("Cadd3").is_err());
+        assert!(parse("Cmsus4").is_err());
+        assert!(parse("Csus2sus4").is_err());
+        assert_eq!(
+            parse("C7x").unwrap_err().to_string(),
+            "Invalid chord variant '7x': unexpected 'x'"
+        );
+    }
+
+    #[test]
+    fn test_note_format_normalized() {
+        let formatting = Formatting::with_format(Format::Text);
+        let cases = [
+            ("C", "C"),
+            ("Cmin", "Cm"),
+            ("C-7", "Cm7"),
+            ("CM7", "Cmaj7"),
+            ("CΔ", "Cmaj7"),
+            ("Cmaj", "C"),
+            ("C°", "Cdim"),
+            ("C+", "Caug"),
+            ("Csus", "Csus4"),
+            ("C7(b9)", "C7b9"),
+            ("Cø", "Cm7b5"),
+            ("C69", "C69"),
+            ("Cmmaj7", "Cmmaj7"),
+            ("A♯dim", "A#dim"),
+            ("E5", "E5"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                parse(input).unwrap().note_format(formatting),
+                expected,
+                "Input {}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_intervals() {
+        assert_eq!(parse("C").unwrap().intervals(), vec![0, 4, 7]);
+        assert_eq!(parse("Cm7").unwrap().intervals(), vec![0, 3, 7, 10]);
+        assert_eq!(parse("Cmaj7").unwrap().intervals(), vec![0, 4, 7, 11]);
+        assert_eq!(parse("Cdim7").unwrap().intervals(), vec![0, 3, 6, 9]);
+        assert_eq!(parse("Csus2").unwrap().intervals(), vec![0, 2, 7]);
+        assert_eq!(parse("C7b9").unwrap().intervals(), vec![0, 4, 7, 10, 13]);
+        assert_eq!(parse("Cm7b5").unwrap().intervals(), vec![0, 3, 6, 10]);
+        assert_eq!(parse("C5").unwrap().intervals(), vec![0, 7]);
+    }
+
+    #[test]
+    fn test_simplify() {
+        let formatting = Formatting::with_format(Format::Text);
+        assert_eq!(
+            parse("Am7b5").unwrap().simplify().note_format(formatting),
+            "Am"
+        );
+        assert_eq!(
+            parse("D7sus4").unwrap().simplify().note_format(formatting),
+            "Dsus4"
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use crate::error::Error;
 use crate::models::chord::fmt::Formatting;
-use crate::models::chord::{Chord, NoteDisplay, TransposableTrait};
+use crate::models::chord::{Chord, ChordStructure, NoteDisplay, TransposableTrait};
 use crate::models::meta::BNotation;
 
 #[derive(Debug, PartialEq, PartialOrd, Clone)]
@@ -29,6 +29,26 @@ impl Chords {
 
         Ok(Chords(chord1, chord2))
     }
+
+    /// Return the (first) chord
+    pub fn chord(&self) -> &Chord {
+        &self.0
+    }
+
+    /// Parse the chords into a `ChordStructure`
+    ///
+    /// The second chord of a slash chord (e.g. `D/F#`) will be used as the bass note
+    pub fn structure(&self) -> Result<ChordStructure, Error> {
+        let structure = self.0.structure()?;
+        match &self.1 {
+            None => Ok(structure),
+            Some(bass) if bass.variant().is_none() => Ok(structure.with_bass(Some(bass.root()))),
+            Some(bass) => Err(Error::chord_error(format!(
+                "Bass note '{}' must not have a variant",
+                bass.note_format(Formatting::with_format(crate::format::Format::Text))
+            ))),
+        }
+    }
 }
 
 impl TransposableTrait for Chords {
@@ -181,4 +201,30 @@ mod tests {
         assert_eq!(chord1.root, Note::B);
         assert_eq!(chord1.variant, Some("m".to_owned()));
     }
+
+    #[test]
+    fn test_structure() {
+        let formatting = Formatting::with_format(crate::format::Format::Text);
+        let structure = Chords::try_from("Dmaj7/F#", BNotation::B)
+            .unwrap()
+            .structure()
+            .unwrap();
+        assert_eq!(structure.bass(), Some(Note::Fis));
+        assert_eq!(structure.note_format(formatting), "Dmaj7/F#");
+
+        let structure = Chords::try_from("CM7/E", BNotation::B)
+            .unwrap()
+            .structure()
+            .unwrap();
+        assert_eq!(structure.note_format(formatting), "Cmaj7/E");
+
+        assert!(Chords::try_from("Bbmaj7/C#m", BNotation::B)
+            .unwrap()
+            .structure()
+            .is_err());
+        assert!(Chords::try_from("C9Daniel", BNotation::B)
+            .unwrap()
+            .structure()
+            .is_err());
+    }
 }
@@ -1,12 +1,34 @@
+use crate::chord_diagram::{Instrument, Tuning};
 use crate::format::Format;
-use crate::models::meta::{BNotation, SemitoneNotation};
+use crate::models::chord::Note;
+use crate::models::meta::{BNotation, ChordNotation, SemitoneNotation};
 use serde::{Deserialize, Serialize};
 
 #[derive(Copy, Clone, PartialEq, Debug, Deserialize, Serialize)]
 pub struct Formatting {
     pub b_notation: BNotation,
     pub semitone_notation: SemitoneNotation,
+    /// Spell the chords according to the key signature of the song's key instead of using
+    /// `semitone_notation` (see `SemitoneNotation::for_key()`)
+    #[serde(default = "default_semitone_notation_for_key")]
+    pub semitone_notation_for_key: bool,
     pub format: Format,
+    /// Render the chord shapes for a guitar with a capo instead of the concert-pitch chords
+    #[serde(default)]
+    pub capo: Option<CapoFormatting>,
+    /// Display the chords by name or relative to the song's key
+    #[serde(default)]
+    pub chord_notation: ChordNotation,
+    /// Root of the song's key used for the relative chord notations (set by the converters)
+    #[serde(skip)]
+    pub key: Option<Note>,
+    /// Append diagrams of the chords used in the song (HTML only)
+    #[serde(default)]
+    pub chord_diagrams: Option<ChordDiagramFormatting>,
+}
+
+fn default_semitone_notation_for_key() -> bool {
+    true
 }
 
 impl Formatting {
@@ -14,9 +36,25 @@ impl Formatting {
         Self {
             b_notation: BNotation::default(),
             semitone_notation: Default::default(),
+            semitone_notation_for_key: default_semitone_notation_for_key(),
             format,
+            capo: None,
+            chord_notation: ChordNotation::default(),
+            key: None,
+            chord_diagrams: None,
         }
     }
+
+    /// Return a copy with the root of the song's `key` used for the relative chord notations
+    pub fn with_key(self, key: Option<Note>) -> Self {
+        Self { key, ..self }
+    }
+
+    /// Return the name of the scale degree of `note` if a relative chord notation is used
+    pub(crate) fn degree(&self, note: Note) -> Option<&'static str> {
+        self.key
+            .and_then(|key| self.chord_notation.degree(note, key))
+    }
 }
 
 impl Default for Formatting {
@@ -24,11 +62,110 @@ impl Default for Formatting {
         Self {
             b_notation: BNotation::default(),
             semitone_notation: SemitoneNotation::default(),
+            semitone_notation_for_key: default_semitone_notation_for_key(),
             format: Format::HTML,
+            capo: None,
+            chord_notation: ChordNotation::default(),
+            key: None,
+            chord_diagrams: None,
+        }
+    }
+}
+
+/// Settings for the capo-aware rendering of chords
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Deserialize, Serialize)]
+pub struct CapoFormatting {
+    /// Fret of the capo (if `None` the capo from the song's meta information is used)
+    pub fret: Option<u8>,
+    /// Display the concert-pitch chords alongside the chord shapes
+    ///
+    /// Ignored by the Chorddown converter, because a source file can only hold one chord per
+    /// position
+    pub show_concert_pitch: bool,
+}
+
+impl CapoFormatting {
+    pub fn new(fret: Option<u8>, show_concert_pitch: bool) -> Self {
+        Self {
+            fret,
+            show_concert_pitch,
         }
     }
+
+    /// Return the capo fret to use for the song with the given meta `capo` value
+    ///
+    /// The meta value may contain additional text (e.g. `3rd fret`), only the leading number is
+    /// used. `None` is returned if no capo is set
+    pub fn effective_fret(&self, meta_capo: Option<&str>) -> Option<u8> {
+        let fret = match self.fret {
+            Some(fret) => Some(fret),
+            None => meta_capo.and_then(|capo| {
+                let digits: String = capo
+                    .trim()
+                    .chars()
+                    .take_while(|c| c.is_ascii_digit())
+                    .collect();
+                digits.parse().ok()
+            }),
+        };
+
+        fret.filter(|f| *f > 0)
+    }
+}
+
+/// Instrument and tuning to draw the chord diagrams for
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Deserialize, Serialize)]
+pub struct ChordDiagramFormatting {
+    pub instrument: Instrument,
+    pub tuning: Tuning,
+}
+
+impl ChordDiagramFormatting {
+    pub fn new(instrument: Instrument, tuning: Tuning) -> Self {
+        Self { instrument, tuning }
+    }
 }
 
 pub trait NoteDisplay {
     fn note_format(&self, format: Formatting) -> String;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capo_effective_fret() {
+        assert_eq!(CapoFormatting::new(None, false).effective_fret(None), None);
+        assert_eq!(
+            CapoFormatting::new(None, false).effective_fret(Some("2")),
+            Some(2)
+        );
+        assert_eq!(
+            CapoFormatting::new(None, false).effective_fret(Some(" 3rd fret")),
+            Some(3)
+        );
+        assert_eq!(
+            CapoFormatting::new(None, false).effective_fret(Some("none")),
+            None
+        );
+        assert_eq!(
+            CapoFormatting::new(Some(4), false).effective_fret(Some("2")),
+            Some(4)
+        );
+        assert_eq!(
+            CapoFormatting::new(Some(0), false).effective_fret(Some("2")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_deserialize_without_capo() {
+        let formatting: Formatting = serde_json::from_str(
+            r#"{"b_notation":"B","semitone_notation":"Sharp","format":"HTML"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(formatting, Formatting::default());
+    }
+}
@@ -5,15 +5,19 @@ use serde::{Deserialize, Deserializer, Serialize};
 use crate::error::Error;
 use crate::format::Format;
 use crate::models::chord::fmt::Formatting;
-use crate::models::meta::BNotation;
+use crate::models::meta::{BNotation, ChordNotation};
 #[deprecated(note = "Use ::modification::transposition::TransposableTrait")]
 pub use crate::modification::transposition::TransposableTrait;
 use crate::prelude::SemitoneNotation;
 
+pub use self::chord_structure::{
+    Accidental, Alteration, ChordStructure, Extension, Quality, Suspension,
+};
 pub use self::chords::Chords;
 pub use self::fmt::NoteDisplay;
 pub use self::note::Note;
 
+mod chord_structure;
 mod chords;
 pub mod fmt;
 mod note;
@@ -64,6 +68,13 @@ impl Chord {
         self.variant.as_deref()
     }
 
+    /// Parse the variant into a `ChordStructure`
+    ///
+    /// Returns an error if the variant can not be interpreted (e.g. `C9Daniel`)
+    pub fn structure(&self) -> Result<ChordStructure, Error> {
+        ChordStructure::try_from_chord(self)
+    }
+
     pub fn try_from(value: &str, b_notation: BNotation) -> Result<Self, Error> {
         if value.is_empty() {
             return Err(Error::chord_error("Given chord brackets are empty"));
@@ -147,6 +158,11 @@ impl TransposableTrait for Chord {
 
 impl NoteDisplay for Chord {
     fn note_format(&self, formatting: Formatting) -> String {
+        if formatting.chord_notation == ChordNotation::Roman {
+            if let Some(numeral) = formatting.degree(self.root) {
+                return roman_numeral_with_variant(numeral, self.variant.as_deref());
+            }
+        }
         match self.variant {
             Some(ref v) => format!("{}{}", NoteDisplay::note_format(&self.root, formatting), v),
             None => NoteDisplay::note_format(&self.root, formatting),
@@ -154,6 +170,32 @@ impl NoteDisplay for Chord {
     }
 }
 
+/// Build the roman numeral chord name (lowercase numerals for minor and diminished chords)
+fn roman_numeral_with_variant(numeral: &str, variant: Option<&str>) -> String {
+    let variant = match variant {
+        None => return numeral.to_owned(),
+        Some(v) => v,
+    };
+    let minor_suffix = if variant.starts_with("maj") {
+        None
+    } else {
+        variant
+            .strip_prefix("min")
+            .or_else(|| variant.strip_prefix('m'))
+            .or_else(|| variant.strip_prefix('-'))
+    };
+
+    if let Some(rest) = variant.strip_prefix("dim") {
+        format!("{}°{}", numeral.to_lowercase(), rest)
+    } else if variant.starts_with('°') || variant.starts_with('ø') {
+        format!("{}{}", numeral.to_lowercase(), variant)
+    } else if let Some(rest) = minor_suffix {
+        format!("{}{}", numeral.to_lowercase(), rest)
+    } else {
+        format!("{}{}", numeral, variant)
+    }
+}
+
 fn get_serialization_b_notation() -> BNotation {
     BNotation::B
 }
@@ -162,7 +204,12 @@ fn get_serialization_formatting() -> Formatting {
     Formatting {
         b_notation: get_serialization_b_notation(),
         semitone_notation: SemitoneNotation::Sharp,
+        semitone_notation_for_key: false,
         format: Format::HTML,
+        capo: None,
+        chord_notation: ChordNotation::Letter,
+        key: None,
+        chord_diagrams: None,
     }
 }
 
@@ -429,4 +476,51 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn note_format_relative_notation() {
+        let format = |chord: &str, chord_notation: ChordNotation| {
+            let formatting = Formatting {
+                chord_notation,
+                ..Formatting::default()
+            }
+            .with_key(Some(Note::G));
+
+            Chords::try_from(chord, BNotation::B)
+                .unwrap()
+                .note_format(formatting)
+        };
+
+        assert_eq!(format("G", ChordNotation::Nashville), "1");
+        assert_eq!(format("C", ChordNotation::Nashville), "4");
+        assert_eq!(format("D/F#", ChordNotation::Nashville), "5/7");
+        assert_eq!(format("Em", ChordNotation::Nashville), "6m");
+        assert_eq!(format("F", ChordNotation::Nashville), "b7");
+        assert_eq!(format("Am7", ChordNotation::Nashville), "2m7");
+
+        assert_eq!(format("G", ChordNotation::Roman), "I");
+        assert_eq!(format("Cmaj7", ChordNotation::Roman), "IVmaj7");
+        assert_eq!(format("D7", ChordNotation::Roman), "V7");
+        assert_eq!(format("Em", ChordNotation::Roman), "vi");
+        assert_eq!(format("Am7", ChordNotation::Roman), "ii7");
+        assert_eq!(format("F#dim", ChordNotation::Roman), "vii°");
+        assert_eq!(format("D/F#", ChordNotation::Roman), "V/VII");
+
+        assert_eq!(format("D/F#", ChordNotation::Letter), "D/F#");
+    }
+
+    #[test]
+    fn note_format_relative_notation_without_key() {
+        let formatting = Formatting {
+            chord_notation: ChordNotation::Nashville,
+            ..Formatting::default()
+        };
+
+        assert_eq!(
+            Chord::try_from("Em", BNotation::B)
+                .unwrap()
+                .note_format(formatting),
+            "Em"
+        );
+    }
 }
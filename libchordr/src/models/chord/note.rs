@@ -137,6 +137,9 @@ impl Debug for Note {
 
 impl NoteDisplay for Note {
     fn note_format(&self, formatting: Formatting) -> String {
+        if let Some(degree) = formatting.degree(*self) {
+            return degree.to_owned();
+        }
         let sharp = formatting.semitone_notation == SemitoneNotation::Sharp;
         match self {
             Self::C => "C",
@@ -218,6 +221,7 @@ impl TransposableTrait for Note {
 #[cfg(test)]
 mod tests {
     use crate::format::Format;
+    use crate::models::meta::ChordNotation;
 
     use super::*;
 
@@ -379,22 +383,42 @@ mod tests {
         let sharp_b = Formatting {
             b_notation: BNotation::B,
             semitone_notation: SemitoneNotation::Sharp,
+            semitone_notation_for_key: false,
             format: Format::HTML,
+            capo: None,
+            chord_notation: ChordNotation::Letter,
+            key: None,
+            chord_diagrams: None,
         };
         let flat_b = Formatting {
             b_notation: BNotation::B,
             semitone_notation: SemitoneNotation::Flat,
+            semitone_notation_for_key: false,
             format: Format::HTML,
+            capo: None,
+            chord_notation: ChordNotation::Letter,
+            key: None,
+            chord_diagrams: None,
         };
         let sharp_europe = Formatting {
             b_notation: BNotation::H,
             semitone_notation: SemitoneNotation::Sharp,
+            semitone_notation_for_key: false,
             format: Format::HTML,
+            capo: None,
+            chord_notation: ChordNotation::Letter,
+            key: None,
+            chord_diagrams: None,
         };
         let flat_europe = Formatting {
             b_notation: BNotation::H,
             semitone_notation: SemitoneNotation::Flat,
+            semitone_notation_for_key: false,
             format: Format::HTML,
+            capo: None,
+            chord_notation: ChordNotation::Letter,
+            key: None,
+            chord_diagrams: None,
         };
         assert_eq!(Note::C.note_format(sharp_b), "C".to_owned());
         assert_eq!(Note::C.note_format(flat_b), "C".to_owned());
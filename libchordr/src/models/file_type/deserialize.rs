@@ -12,7 +12,7 @@ impl<'de> Visitor<'de> for FileTypeVisitor {
     type Value = FileType;
 
     fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
-        formatter.write_str("one of the strings \"chorddown\" or \"jpeg\"")
+        formatter.write_str("one of the strings \"chorddown\", \"chordpro\" or \"jpeg\"")
     }
 
     fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt::{Display, Error, Formatter};
+use std::str::FromStr;
+
+use crate::models::chord::Note;
+
+const NASHVILLE_DEGREES: [&str; 12] = [
+    "1", "b2", "2", "b3", "3", "4", "#4", "5", "b6", "6", "b7", "7",
+];
+const ROMAN_DEGREES: [&str; 12] = [
+    "I", "bII", "II", "bIII", "III", "IV", "#IV", "V", "bVI", "VI", "bVII", "VII",
+];
+
+/// Enum defining if chords are displayed by their name or relative to the song's key
+///
+/// `Nashville` renders the scale degrees as numbers (`1`, `4`, `5/7`, `6m`), `Roman` uses roman
+/// numerals with lowercase numerals for minor chords (`I`, `IV`, `V`, `vi`)
+#[derive(Deserialize, Serialize, PartialEq, PartialOrd, Clone, Copy, Debug, Default)]
+pub enum ChordNotation {
+    #[default]
+    Letter,
+    Nashville,
+    Roman,
+}
+
+impl ChordNotation {
+    /// Return the name of the scale degree of `note` in the given `key`
+    ///
+    /// Returns `None` for the `Letter` notation
+    pub fn degree(&self, note: Note, key: Note) -> Option<&'static str> {
+        let index = (i32::from(note) - i32::from(key)).rem_euclid(12) as usize;
+        match self {
+            Self::Letter => None,
+            Self::Nashville => Some(NASHVILLE_DEGREES[index]),
+            Self::Roman => Some(ROMAN_DEGREES[index]),
+        }
+    }
+}
+
+impl TryFrom<&str> for ChordNotation {
+    type Error = ChordNotationError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::from_str(value)
+    }
+}
+
+impl FromStr for ChordNotation {
+    type Err = ChordNotationError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "letter" => Ok(Self::Letter),
+            "nashville" | "number" | "1" => Ok(Self::Nashville),
+            "roman" | "i" => Ok(Self::Roman),
+            _ => Err(ChordNotationError(value.to_string())),
+        }
+    }
+}
+
+impl Display for ChordNotation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            Self::Letter => f.write_str("C"),
+            Self::Nashville => f.write_str("1"),
+            Self::Roman => f.write_str("I"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ChordNotationError(String);
+
+impl std::error::Error for ChordNotationError {}
+
+impl Display for ChordNotationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "Invalid chord-notation '{}'", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_degree() {
+        assert_eq!(ChordNotation::Letter.degree(Note::D, Note::G), None);
+        assert_eq!(ChordNotation::Nashville.degree(Note::G, Note::G), Some("1"));
+        assert_eq!(ChordNotation::Nashville.degree(Note::D, Note::G), Some("5"));
+        assert_eq!(
+            ChordNotation::Nashville.degree(Note::Fis, Note::G),
+            Some("7")
+        );
+        assert_eq!(
+            ChordNotation::Nashville.degree(Note::F, Note::G),
+            Some("b7")
+        );
+        assert_eq!(ChordNotation::Roman.degree(Note::E, Note::G), Some("VI"));
+        assert_eq!(ChordNotation::Roman.degree(Note::C, Note::G), Some("IV"));
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(
+            ChordNotation::from_str("nashville").unwrap(),
+            ChordNotation::Nashville
+        );
+        assert_eq!(
+            ChordNotation::from_str("Roman").unwrap(),
+            ChordNotation::Roman
+        );
+        assert_eq!(
+            ChordNotation::from_str("letter").unwrap(),
+            ChordNotation::Letter
+        );
+        assert!(ChordNotation::from_str("solfege").is_err());
+    }
+}
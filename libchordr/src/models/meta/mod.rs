@@ -1,9 +1,11 @@
 pub mod b_notation;
+pub mod chord_notation;
 pub mod meta_trait;
 pub mod semitone_notation;
 pub mod tags;
 
 pub use self::b_notation::BNotation;
+pub use self::chord_notation::ChordNotation;
 pub use self::meta_trait::MetaTrait;
 pub use self::semitone_notation::SemitoneNotation;
 pub use self::tags::Tags;
@@ -3,6 +3,9 @@ use std::convert::TryFrom;
 use std::fmt::{Display, Error, Formatter};
 use std::str::FromStr;
 
+use crate::models::chord::{Chord, Note, Quality};
+use crate::modification::transposition::TransposableTrait;
+
 /// Enum defining if `C#` or `Db` should be used
 #[derive(Deserialize, Serialize, PartialEq, PartialOrd, Clone, Copy, Debug)]
 pub enum SemitoneNotation {
@@ -10,7 +13,26 @@ pub enum SemitoneNotation {
     Flat,
 }
 
-impl SemitoneNotation {}
+impl SemitoneNotation {
+    /// Return the notation matching the key signature of `key`
+    ///
+    /// Keys with flats in their signature (e.g. `F`, `Bb` or `Dm`) use the flat notation, all
+    /// other keys use sharps. Minor keys are detected through the chord's structure
+    pub fn for_key(key: &Chord) -> Self {
+        let is_minor = matches!(key.structure(), Ok(s) if s.quality() == Quality::Minor);
+        // Use the relative major key of minor keys
+        let major_root = if is_minor {
+            key.root().transpose(3)
+        } else {
+            key.root()
+        };
+
+        match major_root {
+            Note::F | Note::Ais | Note::Dis | Note::Gis | Note::Cis => Self::Flat,
+            _ => Self::Sharp,
+        }
+    }
+}
 
 impl Default for SemitoneNotation {
     fn default() -> Self {
@@ -69,3 +91,26 @@ impl Display for SemitoneNotationError {
         write!(f, "Invalid semitone-notation '{}'", self.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::models::meta::BNotation;
+
+    use super::*;
+
+    #[test]
+    fn test_for_key() {
+        let for_key =
+            |key: &str| SemitoneNotation::for_key(&Chord::try_from(key, BNotation::B).unwrap());
+        assert_eq!(for_key("C"), SemitoneNotation::Sharp);
+        assert_eq!(for_key("E"), SemitoneNotation::Sharp);
+        assert_eq!(for_key("F#"), SemitoneNotation::Sharp);
+        assert_eq!(for_key("F"), SemitoneNotation::Flat);
+        assert_eq!(for_key("Bb"), SemitoneNotation::Flat);
+        assert_eq!(for_key("Db"), SemitoneNotation::Flat);
+        assert_eq!(for_key("Em"), SemitoneNotation::Sharp);
+        assert_eq!(for_key("Dm"), SemitoneNotation::Flat);
+        assert_eq!(for_key("Cm"), SemitoneNotation::Flat);
+        assert_eq!(for_key("C#m"), SemitoneNotation::Sharp);
+    }
+}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 
+use crate::key_detector::KeyGuess;
 use crate::models::chord::Chord;
 use crate::models::list::ListEntryTrait;
 use crate::models::meta::*;
@@ -36,6 +37,9 @@ pub struct SongMeta {
     ccli_song_id: Option<String>,
     b_notation: BNotation,
     tags: Option<Tags>,
+    /// Key estimated from the song's chords if no `key` is defined
+    #[serde(default)]
+    detected_key: Option<KeyGuess>,
 }
 
 impl SongMeta {
@@ -62,6 +66,7 @@ impl SongMeta {
             ccli_song_id: None,
             b_notation: Default::default(),
             tags: Default::default(),
+            detected_key: None,
         }
     }
 
@@ -93,8 +98,20 @@ impl SongMeta {
             ccli_song_id: meta.ccli_song_id(),
             b_notation: meta.b_notation(),
             tags: Some(meta.tags()),
+            detected_key: None,
         }
     }
+
+    /// Return the key estimated by the `KeyDetector`
+    pub fn detected_key(&self) -> Option<&KeyGuess> {
+        self.detected_key.as_ref()
+    }
+
+    pub fn with_detected_key(mut self, detected_key: Option<KeyGuess>) -> Self {
+        self.detected_key = detected_key;
+
+        self
+    }
 }
 
 impl MetaTrait for SongMeta {
@@ -1,4 +1,4 @@
-pub(crate) use reference_resolver::ReferenceResolver;
+pub use reference_resolver::ReferenceResolver;
 pub use section::Section;
 pub use section_identifier::SectionIdentifier;
 pub use section_identifier_error::SectionIdentifierError;
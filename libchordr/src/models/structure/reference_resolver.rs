@@ -1,16 +1,17 @@
 use crate::models::structure::Section;
 use crate::repeat_detector::RepeatDetector;
 
+/// Find the Section a reference (`> Chorus`) points to
 #[derive(Default)]
-pub(crate) struct ReferenceResolver {}
+pub struct ReferenceResolver {}
 
 impl ReferenceResolver {
-    pub(crate) fn new() -> Self {
+    pub fn new() -> Self {
         Self {}
     }
 
     /// Try to find the target Section pointed to by [reference]
-    pub(crate) fn resolve_reference<'a, 'b, 'c>(
+    pub fn resolve_reference<'a, 'b, 'c>(
         &'a self,
         reference: &'b Section,
         sections: &'c [Section],
@@ -52,7 +53,7 @@ mod tests {
     use crate::models::structure::reference_resolver::ReferenceResolver;
     use crate::parser::{Node, SectionType};
     use crate::test_helpers::get_test_ast;
-    use crate::tokenizer::Modifier;
+    use crate::tokenizer::{Modifier, Span};
 
     #[test]
     fn resolve_reference_test() {
@@ -72,6 +73,7 @@ mod tests {
                 head: Box::new(Node::quote("2x Chorus")),
                 section_type: SectionType::Reference,
                 children: vec![],
+                span: Span::default(),
             },
             Node::newline(),
         ])
@@ -92,6 +94,7 @@ mod tests {
                 head: Box::new(Node::quote("2x My Chorus")),
                 section_type: SectionType::Reference,
                 children: vec![],
+                span: Span::default(),
             },
             Node::newline(),
         ])
@@ -112,6 +115,7 @@ mod tests {
                 head: Box::new(Node::quote("Chorus 2x")),
                 section_type: SectionType::Reference,
                 children: vec![],
+                span: Span::default(),
             },
             Node::newline(),
         ])
@@ -132,6 +136,7 @@ mod tests {
                 head: Box::new(Node::quote("My Chorus 2x")),
                 section_type: SectionType::Reference,
                 children: vec![],
+                span: Span::default(),
             },
             Node::newline(),
         ])
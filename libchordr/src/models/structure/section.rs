@@ -1,5 +1,6 @@
 use super::section_identifier::SectionIdentifier;
 use super::section_type::SectionType;
+use crate::tokenizer::Span;
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Section {
@@ -8,4 +9,6 @@ pub struct Section {
     pub identifier: SectionIdentifier,
     pub has_content: bool,
     pub is_reference: bool,
+    /// Location of the Section's head in the source
+    pub span: Span,
 }
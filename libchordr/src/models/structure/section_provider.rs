@@ -35,11 +35,13 @@ impl SectionProvider {
             head,
             section_type,
             children,
+            span,
         } = node
         {
             if let Some(info) = Self::extract_headline(head) {
                 if info.level.unwrap_or(10) > 1 {
-                    let identifier = SectionIdentifier::try_from(head.as_ref()).unwrap();
+                    // Sections without a name (e.g. an empty `>` while typing) can not be identified
+                    let identifier = SectionIdentifier::try_from(head.as_ref()).ok()?;
 
                     return Some(Section {
                         section_type: *section_type,
@@ -47,6 +49,7 @@ impl SectionProvider {
                         has_content: !children.is_empty(),
                         is_reference: matches!(**head, Node::Quote(_)),
                         identifier,
+                        span: *span,
                     });
                 }
             }
@@ -1,3 +1,98 @@
+use crate::error::Error;
+use crate::models::chord::{Chord, Note};
+use crate::parser::ParserResult;
+
 pub trait TransposableTrait {
     fn transpose(self, semitones: isize) -> Self;
 }
+
+/// Trait for types that know their key and can be transposed into another key
+pub trait KeyTransposableTrait: Sized {
+    /// Transpose into the key of `target`
+    ///
+    /// Returns an error if the current key is unknown
+    fn transpose_to_key(self, target: Chord) -> Result<Self, Error>;
+}
+
+impl KeyTransposableTrait for ParserResult {
+    fn transpose_to_key(self, target: Chord) -> Result<Self, Error> {
+        let key = match self.meta.key {
+            Some(ref key) => key.clone(),
+            None => {
+                return Err(Error::chord_error(
+                    "The song does not define a key to transpose from",
+                ))
+            }
+        };
+        let semitones = semitones_between(key.root(), target.root());
+        let ParserResult { node, meta } = self;
+
+        Ok(ParserResult::new(
+            node.transpose(semitones),
+            meta.transpose(semitones),
+        ))
+    }
+}
+
+/// Return the number of semitones to transpose `from` into `to`
+///
+/// The shorter direction is chosen, so the result is in the range `-5..=6`
+pub fn semitones_between(from: Note, to: Note) -> isize {
+    let distance = (to as isize - from as isize).rem_euclid(12);
+    if distance > 6 {
+        distance - 12
+    } else {
+        distance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::helper::parse_content;
+    use crate::models::meta::{BNotation, MetaTrait};
+
+    use super::*;
+
+    #[test]
+    fn test_semitones_between() {
+        assert_eq!(semitones_between(Note::C, Note::C), 0);
+        assert_eq!(semitones_between(Note::C, Note::D), 2);
+        assert_eq!(semitones_between(Note::F, Note::E), -1);
+        assert_eq!(semitones_between(Note::C, Note::Fis), 6);
+        assert_eq!(semitones_between(Note::C, Note::G), -5);
+        assert_eq!(semitones_between(Note::B, Note::C), 1);
+        assert_eq!(semitones_between(Note::C, Note::B), -1);
+    }
+
+    #[test]
+    fn test_transpose_to_key() {
+        let content = "# Song\nKey: F\n\n## Verse\n[F]A [Bb]B [C7]C\n";
+        let result = parse_content(content.as_bytes())
+            .unwrap()
+            .transpose_to_key(Chord::try_from("E", BNotation::B).unwrap())
+            .unwrap();
+
+        assert_eq!(
+            result.meta_as_ref().key(),
+            Some(Chord::new_without_variant(Note::E))
+        );
+        assert_eq!(
+            result.meta_as_ref().original_key(),
+            Some(Chord::new_without_variant(Note::F))
+        );
+        assert_eq!(
+            result.node().transpose(1),
+            parse_content(content.as_bytes()).unwrap().node
+        );
+    }
+
+    #[test]
+    fn test_transpose_to_key_without_key() {
+        let content = "# Song\n\n## Verse\n[F]A [Bb]B [C7]C\n";
+        let result = parse_content(content.as_bytes())
+            .unwrap()
+            .transpose_to_key(Chord::try_from("E", BNotation::B).unwrap());
+
+        assert!(result.is_err());
+    }
+}
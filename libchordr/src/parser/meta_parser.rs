@@ -21,7 +21,10 @@ impl MetaParser {
         Self {}
     }
 
-    pub fn parse_borrowed(&mut self, tokens: &[Token]) -> Result<MetaInformation, Error> {
+    pub fn parse_borrowed<'a, I: IntoIterator<Item = &'a Token>>(
+        &mut self,
+        tokens: I,
+    ) -> Result<MetaInformation, Error> {
         let mut meta = MetaInformation::default();
         for token in tokens {
             meta = self.visit(token, meta);
@@ -2,7 +2,7 @@ use crate::error::Error;
 use crate::models::meta::*;
 use crate::parser::meta_parser::MetaParser;
 use crate::parser::node_parser::NodeParser;
-use crate::tokenizer::Token;
+use crate::tokenizer::{SpannedToken, Token};
 
 pub use self::meta_information::MetaInformation;
 pub use self::node::Node;
@@ -24,11 +24,22 @@ pub trait ParserTrait {
 }
 
 #[derive(Default)]
-pub struct Parser {}
+pub struct Parser {
+    keep_blank_lines: bool,
+}
 
 impl Parser {
     pub fn new() -> Self {
-        Self {}
+        Self::default()
+    }
+
+    /// Keep single blank lines (e.g. between two stanzas) as two consecutive `Node::Newline`s
+    ///
+    /// By default all blank lines are removed
+    pub fn keep_blank_lines(self) -> Self {
+        Self {
+            keep_blank_lines: true,
+        }
     }
 }
 
@@ -36,28 +47,43 @@ impl ParserTrait for Parser {
     type OkType = ParserResult;
 
     fn parse(&mut self, tokens: Vec<Token>) -> Result<ParserResult, Error> {
-        let meta = MetaParser::new().parse_borrowed(&tokens)?;
-        let node = NodeParser::with_b_notation(meta.b_notation).parse(tokens)?;
-
-        Ok(ParserResult::new(node, meta))
+        self.parse_with_spans(tokens.into_iter().map(SpannedToken::from).collect())
     }
 }
 
 impl Parser {
+    /// Parse the given tokens and keep their location in the source in the Nodes
+    pub fn parse_with_spans(&mut self, tokens: Vec<SpannedToken>) -> Result<ParserResult, Error> {
+        let meta = MetaParser::new().parse_borrowed(tokens.iter().map(|t| &t.token))?;
+        let node = NodeParser::with_b_notation(meta.b_notation)
+            .keep_blank_lines(self.keep_blank_lines)
+            .parse_with_spans(tokens)?;
+
+        Ok(ParserResult::new(node, meta))
+    }
+
     /// Remove leading and duplicate Newline tokens from the stream
-    pub fn cleanup_tokens(tokens: Vec<Token>) -> Vec<Token> {
-        // Initialize to `true` so that leading Newline tokens will be skipped
-        let mut previous_token_was_newline: bool = true;
+    pub fn cleanup_tokens<T: AsRef<Token>>(tokens: Vec<T>) -> Vec<T> {
+        Self::limit_newlines(tokens, 1)
+    }
+
+    /// Remove leading Newline tokens and allow at most `max_newlines` consecutive Newline tokens
+    pub(crate) fn limit_newlines<T: AsRef<Token>>(tokens: Vec<T>, max_newlines: usize) -> Vec<T> {
+        // Initialize to `max_newlines` so that leading Newline tokens will be skipped
+        let mut newlines = max_newlines;
 
         tokens
             .into_iter()
             .filter(|token| {
-                // Skip multiple Newline tokens
-                if *token == Token::Newline && previous_token_was_newline {
-                    false
-                } else {
-                    previous_token_was_newline = *token == Token::Newline;
+                if *token.as_ref() != Token::Newline {
+                    newlines = 0;
+                    true
+                } else if newlines < max_newlines {
+                    newlines += 1;
                     true
+                } else {
+                    // Skip additional Newline tokens
+                    false
                 }
             })
             .collect()
@@ -69,7 +95,7 @@ mod tests {
     use crate::test_helpers::{
         get_test_ast, get_test_ast_small, get_test_tokens, get_test_tokens_small,
     };
-    use crate::tokenizer::Modifier;
+    use crate::tokenizer::{build_tokenizer, Modifier, Position, Span, Tokenizer};
 
     use super::*;
 
@@ -107,6 +133,42 @@ mod tests {
         assert_eq!(parser_result.node(), get_test_ast());
     }
 
+    #[test]
+    fn test_parse_with_spans() {
+        let (tokens, _warnings) = build_tokenizer()
+            .tokenize_with_spans("# Song\n\n## Verse\nLa [G]la\n".as_bytes())
+            .unwrap();
+        let node = Parser::new().parse_with_spans(tokens).unwrap().node();
+
+        let sections = match &node {
+            Node::Document(sections) => sections,
+            _ => panic!("Expected a Document"),
+        };
+        assert_eq!(sections[1].span().start, Position::new(3, 1));
+        let pair = match &sections[1] {
+            Node::Section { children, .. } => &children[2],
+            _ => panic!("Expected a Section"),
+        };
+        assert_eq!(
+            pair.span(),
+            Span::new(Position::new(4, 4), Position::new(4, 9))
+        );
+    }
+
+    #[test]
+    fn test_parse_with_spans_error() {
+        let (tokens, _warnings) = build_tokenizer()
+            .tokenize_with_spans("# Song\n\n## Verse\nLa [Xyz]la\n".as_bytes())
+            .unwrap();
+        let error = match Parser::new().parse_with_spans(tokens) {
+            Ok(_) => panic!("Expected an error for the invalid chord"),
+            Err(error) => error,
+        };
+
+        assert_eq!(error.span().unwrap().start, Position::new(4, 4));
+        assert!(error.to_string().ends_with("(line 4, column 4)"));
+    }
+
     #[test]
     fn test_detect_b_notation() {
         let mut parser = Parser::new();
@@ -220,4 +282,63 @@ mod tests {
             vec![Token::chord("H"), Token::newline(),]
         );
     }
+
+    #[test]
+    fn test_limit_newlines() {
+        assert_eq!(
+            Parser::limit_newlines(
+                vec![
+                    Token::newline(),
+                    Token::headline(1, "Test", Modifier::None),
+                    Token::newline(),
+                    Token::newline(),
+                    Token::newline(),
+                    Token::chord("H"),
+                    Token::newline(),
+                    Token::newline(),
+                ],
+                2
+            ),
+            vec![
+                Token::headline(1, "Test", Modifier::None),
+                Token::newline(),
+                Token::newline(),
+                Token::chord("H"),
+                Token::newline(),
+                Token::newline(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_keep_blank_lines() {
+        let content = "# Song\n\n## Verse\nLine one\n\n\nStanza two\n";
+        let (tokens, _) = build_tokenizer().tokenize(content.as_bytes()).unwrap();
+        let result = Parser::new().keep_blank_lines().parse(tokens).unwrap();
+
+        assert_eq!(
+            result.node(),
+            Node::Document(vec![
+                Node::Section {
+                    head: Box::new(Node::Headline(Token::headline(1, "Song", Modifier::None))),
+                    children: vec![Node::Newline, Node::Newline],
+                    section_type: SectionType::Verse,
+                    span: Span::default(),
+                },
+                Node::Section {
+                    head: Box::new(Node::Headline(Token::headline(2, "Verse", Modifier::None))),
+                    children: vec![
+                        Node::Newline,
+                        Node::Text(Token::literal("Line one")),
+                        Node::Newline,
+                        Node::Newline,
+                        Node::Text(Token::literal("Stanza two")),
+                        Node::Newline,
+                    ],
+                    section_type: SectionType::Verse,
+                    span: Span::default(),
+                },
+            ])
+        );
+    }
 }
@@ -6,7 +6,7 @@ use crate::models::meta::BNotation;
 use crate::models::structure::{Section, SectionProvider};
 use crate::modification::transposition::TransposableTrait;
 use crate::parser::section_type::SectionType;
-use crate::tokenizer::{Meta, Modifier, Token};
+use crate::tokenizer::{Meta, Modifier, Span, Token};
 
 #[derive(PartialOrd, PartialEq, Debug, Clone)]
 pub enum Node {
@@ -14,8 +14,9 @@ pub enum Node {
         chords: Chords,
         text: Token,
         last_in_line: bool,
+        span: Span,
     },
-    ChordStandalone(Chords),
+    ChordStandalone(Chords, Span),
     Text(Token),
     Meta(Meta),
 
@@ -26,6 +27,7 @@ pub enum Node {
         head: Box<Node>,
         section_type: SectionType,
         children: Vec<Node>,
+        span: Span,
     },
     Newline,
 }
@@ -43,6 +45,7 @@ impl Node {
             head: Box::new(Node::headline(level, value, section_type.into())),
             section_type,
             children,
+            span: Span::default(),
         }
     }
 
@@ -52,10 +55,10 @@ impl Node {
 
     #[allow(unused)]
     pub(crate) fn chord_standalone<S: AsRef<str>>(value: S) -> Result<Self, Error> {
-        Ok(Node::ChordStandalone(Chords::try_from(
-            value.as_ref(),
-            BNotation::B,
-        )?))
+        Ok(Node::ChordStandalone(
+            Chords::try_from(value.as_ref(), BNotation::B)?,
+            Span::default(),
+        ))
     }
 
     pub(crate) fn chord_text_pair<S1: AsRef<str>, S2: Into<String>>(
@@ -69,6 +72,7 @@ impl Node {
             chords,
             text,
             last_in_line: false,
+            span: Span::default(),
         })
     }
 
@@ -83,6 +87,7 @@ impl Node {
             chords,
             text,
             last_in_line: true,
+            span: Span::default(),
         })
     }
 
@@ -111,6 +116,32 @@ impl Node {
     pub fn get_sections(&self) -> Vec<Section> {
         SectionProvider::get_sections(self)
     }
+
+    /// Return the location of the Node in the source
+    ///
+    /// Only Sections (located at their head) and chords keep their location. For other Nodes (and
+    /// Nodes that were not parsed from a source) the unknown `Span` is returned
+    pub fn span(&self) -> Span {
+        match self {
+            Node::ChordTextPair { span, .. }
+            | Node::ChordStandalone(_, span)
+            | Node::Section { span, .. } => *span,
+            _ => Span::default(),
+        }
+    }
+
+    /// Return the chords of the Node and its descendants in the order of their appearance
+    pub fn chords(&self) -> Vec<&Chords> {
+        match self {
+            Node::ChordTextPair { chords, .. } | Node::ChordStandalone(chords, _) => vec![chords],
+            Node::Document(children) | Node::Section { children, .. } => {
+                children.iter().flat_map(Node::chords).collect()
+            }
+            Node::Text(_) | Node::Headline(_) | Node::Quote(_) | Node::Meta(_) | Node::Newline => {
+                vec![]
+            }
+        }
+    }
 }
 
 impl TransposableTrait for Node {
@@ -120,12 +151,16 @@ impl TransposableTrait for Node {
                 chords,
                 text,
                 last_in_line,
+                span,
             } => Node::ChordTextPair {
                 chords: chords.transpose(semitones),
                 text,
                 last_in_line,
+                span,
             },
-            Node::ChordStandalone(chords) => Node::ChordStandalone(chords.transpose(semitones)),
+            Node::ChordStandalone(chords, span) => {
+                Node::ChordStandalone(chords.transpose(semitones), span)
+            }
 
             Node::Document(nodes) => {
                 Node::Document(nodes.into_iter().map(|n| n.transpose(semitones)).collect())
@@ -134,6 +169,7 @@ impl TransposableTrait for Node {
                 head,
                 section_type,
                 children,
+                span,
             } => Node::Section {
                 head,
                 section_type,
@@ -141,6 +177,7 @@ impl TransposableTrait for Node {
                     .into_iter()
                     .map(|n| n.transpose(semitones))
                     .collect()),
+                span,
             },
             _ => self,
         }
@@ -197,4 +234,20 @@ mod tests {
 
         assert_eq!(input.transpose(2), expected);
     }
+
+    #[test]
+    fn test_chords() {
+        let expected: Vec<Chords> = [
+            "D", "G", "D", "A7", "D7", "E", "G", "D", "E", "A", "A#", "H",
+        ]
+        .iter()
+        .map(|c| Chords::try_from(c, BNotation::B).unwrap())
+        .collect();
+
+        assert_eq!(
+            get_test_ast_small().chords(),
+            expected.iter().collect::<Vec<_>>()
+        );
+        assert!(Node::text("Swing").chords().is_empty());
+    }
 }
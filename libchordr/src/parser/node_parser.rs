@@ -2,7 +2,7 @@ use std::iter::Peekable;
 use std::vec::IntoIter;
 
 use crate::models::chord::Chords;
-use crate::tokenizer::Token;
+use crate::tokenizer::{Span, SpannedToken, Token};
 
 pub use super::meta_information::MetaInformation;
 pub use super::node::Node;
@@ -12,13 +12,41 @@ pub use super::*;
 
 pub struct NodeParser {
     b_notation: BNotation,
+    keep_blank_lines: bool,
 }
 
+type Tokens = Peekable<IntoIter<SpannedToken>>;
+
 impl ParserTrait for NodeParser {
     type OkType = Node;
 
     fn parse(&mut self, tokens: Vec<Token>) -> Result<Self::OkType, Error> {
-        let mut tokens_iterator = Parser::cleanup_tokens(tokens).into_iter().peekable();
+        self.parse_with_spans(tokens.into_iter().map(SpannedToken::from).collect())
+    }
+}
+
+impl NodeParser {
+    pub fn with_b_notation(b_notation: BNotation) -> Self {
+        Self {
+            b_notation,
+            keep_blank_lines: false,
+        }
+    }
+
+    /// Define if single blank lines should be kept as two consecutive `Node::Newline`s
+    pub fn keep_blank_lines(self, keep_blank_lines: bool) -> Self {
+        Self {
+            keep_blank_lines,
+            ..self
+        }
+    }
+
+    /// Parse the given tokens and keep their location in the source in the Nodes
+    pub fn parse_with_spans(&mut self, tokens: Vec<SpannedToken>) -> Result<Node, Error> {
+        let max_newlines = if self.keep_blank_lines { 2 } else { 1 };
+        let mut tokens_iterator = Parser::limit_newlines(tokens, max_newlines)
+            .into_iter()
+            .peekable();
 
         let mut elements = vec![];
 
@@ -28,44 +56,31 @@ impl ParserTrait for NodeParser {
 
         Ok(Node::Document(elements))
     }
-}
-
-impl NodeParser {
-    pub fn with_b_notation(b_notation: BNotation) -> Self {
-        Self { b_notation }
-    }
 
-    fn visit(
-        &mut self,
-        token: Token,
-        tokens: &mut Peekable<IntoIter<Token>>,
-    ) -> Result<Node, Error> {
+    fn visit(&mut self, spanned_token: SpannedToken, tokens: &mut Tokens) -> Result<Node, Error> {
+        let SpannedToken { token, span } = spanned_token;
         log::trace!("Visit token: {:?}", token);
         match token {
-            Token::Chord(_) => self.visit_chord(token, tokens),
+            Token::Chord(_) => self.visit_chord(token, span, tokens),
             Token::Headline {
-                level,
+                level: _,
                 text: _,
                 modifier,
             } => {
-                if level == 1 {}
                 let head = Box::new(Node::Headline(token));
 
-                if tokens.peek().is_some() {
-                    let children = self.consume_children(tokens)?;
-
-                    Ok(Node::Section {
-                        head,
-                        children,
-                        section_type: modifier.into(),
-                    })
+                let children = if tokens.peek().is_some() {
+                    self.consume_children(tokens)?
                 } else {
-                    Ok(Node::Section {
-                        head,
-                        children: vec![],
-                        section_type: modifier.into(),
-                    })
-                }
+                    vec![]
+                };
+
+                Ok(Node::Section {
+                    head,
+                    children,
+                    section_type: modifier.into(),
+                    span,
+                })
             }
             Token::Meta(meta) => Ok(Node::Meta(meta)),
             Token::Literal(_) => Ok(Node::Text(token)),
@@ -73,20 +88,18 @@ impl NodeParser {
                 head: Box::new(Node::Quote(token)),
                 children: vec![],
                 section_type: SectionType::Reference,
+                span,
             }),
             Token::Newline => Ok(Node::Newline),
         }
     }
 
     /// Collect children while there is an upcoming token and it isn't the start of a new section
-    fn consume_children(
-        &mut self,
-        tokens: &mut Peekable<IntoIter<Token>>,
-    ) -> Result<Vec<Node>, Error> {
+    fn consume_children(&mut self, tokens: &mut Tokens) -> Result<Vec<Node>, Error> {
         let mut children = vec![];
 
         while let Some(token) = tokens.peek() {
-            if token_is_start_of_section(token) {
+            if token_is_start_of_section(&token.token) {
                 break;
             }
             let result = self.visit(tokens.next().unwrap(), tokens)?;
@@ -98,7 +111,8 @@ impl NodeParser {
     fn visit_chord(
         &mut self,
         token: Token,
-        tokens: &mut Peekable<IntoIter<Token>>,
+        span: Span,
+        tokens: &mut Tokens,
     ) -> Result<Node, Error> {
         let chords_raw = if let Token::Chord(c) = token {
             c
@@ -110,11 +124,12 @@ impl NodeParser {
         let chord_result = Chords::try_from(&chords_raw, self.b_notation);
 
         let chords = match chord_result {
+            Err(error) if !span.is_unknown() => return Err(error.with_span(span)),
             Err(error) => {
                 return match tokens.peek() {
                     Some(next) => Err(Error::chord_error(format!(
                         "{} (before token {:?})",
-                        error, next
+                        error, next.token
                     ))),
                     None => Err(error),
                 };
@@ -123,20 +138,21 @@ impl NodeParser {
             Ok(chords) => chords,
         };
 
-        if let Some(Token::Literal(_)) = tokens.peek() {
+        if let Some(Token::Literal(_)) = tokens.peek().map(|t| &t.token) {
             // Consume the next token
             let text = tokens.next().unwrap();
 
-            let last_in_line = matches!(tokens.peek(), Some(Token::Newline));
+            let last_in_line = matches!(tokens.peek().map(|t| &t.token), Some(Token::Newline));
 
             return Ok(Node::ChordTextPair {
                 chords,
-                text,
+                text: text.token,
                 last_in_line,
+                span: Span::new(span.start, text.span.end),
             });
         }
 
-        Ok(Node::ChordStandalone(chords))
+        Ok(Node::ChordStandalone(chords, span))
     }
 }
 
@@ -0,0 +1,12 @@
+/// Position on a page in points, measured from the top left corner
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct Coordinates {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Coordinates {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+}
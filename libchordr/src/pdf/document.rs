@@ -0,0 +1,246 @@
+use std::fmt::Write;
+
+use super::coordinates::Coordinates;
+use super::font::Font;
+
+/// Minimal PDF document
+///
+/// The document only uses the standard Type 1 fonts and uncompressed content streams. All
+/// non-ASCII characters are escaped, which allows the rendered document to be returned as `String`
+pub(crate) struct PdfDocument {
+    title: String,
+    width: f32,
+    height: f32,
+    pages: Vec<String>,
+}
+
+impl PdfDocument {
+    pub fn new<S: Into<String>>(title: S, width: f32, height: f32) -> Self {
+        Self {
+            title: title.into(),
+            width,
+            height,
+            pages: vec![],
+        }
+    }
+
+    /// Add a new page and return its index
+    pub fn add_page(&mut self) -> usize {
+        self.pages.push(String::new());
+        self.pages.len() - 1
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Write `text` to the page with the given index
+    ///
+    /// The position is measured from the top left corner of the page
+    pub fn write_text(
+        &mut self,
+        page: usize,
+        position: Coordinates,
+        font: Font,
+        size: f32,
+        text: &str,
+    ) {
+        if text.trim().is_empty() {
+            return;
+        }
+        let y = self.height - position.y;
+        if let Some(content) = self.pages.get_mut(page) {
+            let _ = writeln!(
+                content,
+                "BT /{} {:.2} Tf {:.2} {:.2} Td ({}) Tj ET",
+                font.resource_name(),
+                size,
+                position.x,
+                y,
+                escape_text(text)
+            );
+        }
+    }
+
+    /// Render the document
+    pub fn render(&self) -> String {
+        let mut output = String::from("%PDF-1.4\n");
+        let mut offsets: Vec<usize> = vec![];
+        let fonts = Font::all();
+        let first_font_id = 4;
+        let first_page_id = first_font_id + fonts.len();
+
+        let mut push_object = |output: &mut String, id: usize, body: &str| {
+            offsets.push(output.len());
+            let _ = write!(output, "{} 0 obj\n{}\nendobj\n", id, body);
+        };
+
+        push_object(&mut output, 1, "<< /Type /Catalog /Pages 2 0 R >>");
+        let kids = (0..self.pages.len())
+            .map(|i| format!("{} 0 R", first_page_id + 2 * i))
+            .collect::<Vec<String>>()
+            .join(" ");
+        push_object(
+            &mut output,
+            2,
+            &format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids,
+                self.pages.len()
+            ),
+        );
+        push_object(
+            &mut output,
+            3,
+            &format!(
+                "<< /Title ({}) /Producer (chordr) >>",
+                escape_text(&self.title)
+            ),
+        );
+
+        let mut font_resources = String::new();
+        for (i, font) in fonts.iter().enumerate() {
+            let _ = write!(
+                font_resources,
+                "/{} {} 0 R ",
+                font.resource_name(),
+                first_font_id + i
+            );
+            push_object(
+                &mut output,
+                first_font_id + i,
+                &format!(
+                    "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+                    font.base_font()
+                ),
+            );
+        }
+
+        for (i, content) in self.pages.iter().enumerate() {
+            let page_id = first_page_id + 2 * i;
+            push_object(
+                &mut output,
+                page_id,
+                &format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] /Resources << /Font << {}>> >> /Contents {} 0 R >>",
+                    self.width,
+                    self.height,
+                    font_resources,
+                    page_id + 1
+                ),
+            );
+            push_object(
+                &mut output,
+                page_id + 1,
+                &format!(
+                    "<< /Length {} >>\nstream\n{}endstream",
+                    content.len(),
+                    content
+                ),
+            );
+        }
+
+        let xref_offset = output.len();
+        let _ = write!(
+            output,
+            "xref\n0 {}\n0000000000 65535 f \n",
+            offsets.len() + 1
+        );
+        for offset in offsets.iter() {
+            let _ = writeln!(output, "{:010} 00000 n ", offset);
+        }
+        let _ = write!(
+            output,
+            "trailer\n<< /Size {} /Root 1 0 R /Info 3 0 R >>\nstartxref\n{}\n%%EOF\n",
+            offsets.len() + 1,
+            xref_offset
+        );
+
+        output
+    }
+}
+
+/// Escape the text for a PDF string literal using the `WinAnsiEncoding`
+///
+/// Characters outside of the ASCII range are written as octal escape sequences, characters that
+/// can not be represented in the encoding are replaced with a question mark
+fn escape_text(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                output.push('\\');
+                output.push(c)
+            }
+            ' '..='~' => output.push(c),
+            _ => {
+                let _ = write!(output, "\\{:03o}", win_ansi_byte(c));
+            }
+        }
+    }
+    output
+}
+
+fn win_ansi_byte(c: char) -> u8 {
+    match c {
+        '€' => 0x80,
+        '…' => 0x85,
+        '‘' => 0x91,
+        '’' => 0x92,
+        '“' => 0x93,
+        '”' => 0x94,
+        '•' => 0x95,
+        '–' => 0x96,
+        '—' => 0x97,
+        '♯' => b'#',
+        '♭' => b'b',
+        '\u{a0}'..='\u{ff}' => c as u32 as u8,
+        _ => b'?',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_text() {
+        assert_eq!(escape_text("Swing low"), "Swing low");
+        assert_eq!(escape_text("(Chorus)"), "\\(Chorus\\)");
+        assert_eq!(escape_text("a\\b"), "a\\\\b");
+        assert_eq!(escape_text("Überschrift"), "\\334berschrift");
+        assert_eq!(escape_text("Comin’"), "Comin\\222");
+        assert_eq!(escape_text("☃"), "\\077");
+    }
+
+    #[test]
+    fn test_render() {
+        let mut document = PdfDocument::new("Test", 595.28, 841.89);
+        let page = document.add_page();
+        document.write_text(
+            page,
+            Coordinates::new(10.0, 20.0),
+            Font::Regular,
+            12.0,
+            "Hello",
+        );
+        let output = document.render();
+
+        assert!(output.starts_with("%PDF-1.4\n"));
+        assert!(output.ends_with("%%EOF\n"));
+        assert!(output.is_ascii());
+        assert!(output.contains("BT /F1 12.00 Tf 10.00 821.89 Td (Hello) Tj ET"));
+        assert!(output.contains("/Count 1"));
+
+        // The xref table must point to the objects
+        let xref_offset: usize = output
+            .lines()
+            .rev()
+            .nth(1)
+            .and_then(|l| l.parse().ok())
+            .unwrap();
+        assert!(output[xref_offset..].starts_with("xref"));
+        assert!(output[output.find("0000000009").unwrap()..].contains(" 00000 n "));
+        assert!(output[9..].starts_with("1 0 obj"));
+    }
+}
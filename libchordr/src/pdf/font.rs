@@ -0,0 +1,53 @@
+/// Standard Type 1 fonts which every PDF reader must provide
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Font {
+    Regular,
+    Bold,
+    Italic,
+    Mono,
+    MonoBold,
+}
+
+impl Font {
+    pub fn all() -> [Font; 5] {
+        [
+            Font::Regular,
+            Font::Bold,
+            Font::Italic,
+            Font::Mono,
+            Font::MonoBold,
+        ]
+    }
+
+    /// Name of the font in the page's resource dictionary
+    pub fn resource_name(&self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+            Font::Italic => "F3",
+            Font::Mono => "F4",
+            Font::MonoBold => "F5",
+        }
+    }
+
+    pub fn base_font(&self) -> &'static str {
+        match self {
+            Font::Regular => "Helvetica",
+            Font::Bold => "Helvetica-Bold",
+            Font::Italic => "Helvetica-Oblique",
+            Font::Mono => "Courier",
+            Font::MonoBold => "Courier-Bold",
+        }
+    }
+
+    /// Width of a single character relative to the font size
+    ///
+    /// For the proportional fonts this is only an approximation
+    pub fn character_width(&self) -> f32 {
+        match self {
+            Font::Mono | Font::MonoBold => 0.6,
+            Font::Regular | Font::Italic => 0.5,
+            Font::Bold => 0.55,
+        }
+    }
+}
@@ -0,0 +1,6 @@
+//! Minimal PDF writer used by the `PdfConverter`
+pub(crate) mod coordinates;
+pub(crate) mod document;
+pub(crate) mod font;
+pub(crate) mod pdf_builder;
+pub(crate) mod styles;
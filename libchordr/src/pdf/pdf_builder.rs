@@ -0,0 +1,470 @@
+use crate::models::chord::fmt::*;
+use crate::models::meta::MetaTrait;
+use crate::parser::Node;
+use crate::tokenizer::Token;
+
+use super::coordinates::Coordinates;
+use super::document::PdfDocument;
+use super::styles::{Style, Styles};
+
+/// A4 in points
+const PAGE_WIDTH: f32 = 595.28;
+const PAGE_HEIGHT: f32 = 841.89;
+const MARGIN: f32 = 50.0;
+
+/// Single line of the layout
+#[derive(Debug, Clone, PartialEq)]
+enum Line {
+    Text(Style, String),
+    Spacer(f32),
+}
+
+impl Line {
+    fn height(&self) -> f32 {
+        match self {
+            Line::Text(style, _) => style.line_height(),
+            Line::Spacer(height) => *height,
+        }
+    }
+}
+
+/// Lines which should be kept on the same page if possible (e.g. a section)
+type Block = Vec<Line>;
+
+/// Part of a lyrics line with the chord that is played at its start
+struct Segment {
+    chord: Option<String>,
+    text: String,
+}
+
+/// Builder to lay out songs as pages of a `PdfDocument`
+///
+/// Chords are printed above the lyrics using a monospaced font, so that each chord is aligned with
+/// the text of its `ChordTextPair`. A section is moved to the next page if it doesn't fit onto the
+/// current one
+pub(crate) struct PdfBuilder {
+    styles: Styles,
+    formatting: Formatting,
+    document: PdfDocument,
+    page: usize,
+    y: f32,
+}
+
+impl PdfBuilder {
+    pub fn new<S: Into<String>>(title: S, styles: Styles) -> Self {
+        Self {
+            styles,
+            formatting: Formatting::default(),
+            document: PdfDocument::new(title, PAGE_WIDTH, PAGE_HEIGHT),
+            page: 0,
+            y: MARGIN,
+        }
+    }
+
+    /// Add the song to the document, starting on a new page
+    ///
+    /// Returns the (1-based) number of the page the song starts on
+    pub fn add_song(&mut self, node: &Node, meta: &dyn MetaTrait, formatting: Formatting) -> usize {
+        self.formatting = formatting.with_key(meta.key().map(|key| key.root()));
+        self.new_page();
+        let first_page = self.page + 1;
+        let header = self.build_meta(meta);
+        self.add_block(header);
+        for block in self.build_blocks(node) {
+            self.add_block(block);
+        }
+
+        first_page
+    }
+
+    /// Add a table of contents with the given titles and page numbers, starting on a new page
+    pub fn add_table_of_contents(&mut self, title: &str, entries: &[(String, usize)]) {
+        self.new_page();
+        self.add_block(vec![Line::Text(self.styles.title, title.to_owned())]);
+
+        let max_columns = self.max_columns();
+        let lines = entries
+            .iter()
+            .enumerate()
+            .map(|(i, (title, page))| {
+                let prefix = format!("{:>3}. ", i + 1);
+                let page = page.to_string();
+                let available = max_columns - prefix.len() - page.len() - 2;
+                let title = title.chars().take(available).collect::<String>();
+                let dots = ".".repeat(available - title.chars().count());
+
+                Line::Text(
+                    self.styles.lyrics,
+                    format!("{}{} {} {}", prefix, title, dots, page),
+                )
+            })
+            .collect();
+        self.add_block(lines);
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.document.page_count()
+    }
+
+    pub fn build(self) -> PdfDocument {
+        self.document
+    }
+
+    fn new_page(&mut self) {
+        self.page = self.document.add_page();
+        self.y = MARGIN;
+    }
+
+    /// Number of monospaced characters that fit into one line
+    fn max_columns(&self) -> usize {
+        ((PAGE_WIDTH - 2.0 * MARGIN) / self.styles.lyrics.character_width()).floor() as usize
+    }
+
+    fn content_height() -> f32 {
+        PAGE_HEIGHT - 2.0 * MARGIN
+    }
+
+    fn remaining_height(&self) -> f32 {
+        PAGE_HEIGHT - MARGIN - self.y
+    }
+
+    fn add_block(&mut self, block: Block) {
+        let block = trim_block(block);
+        if block.is_empty() {
+            return;
+        }
+        let gap = self.styles.lyrics.line_height() * 0.75;
+        let at_top_of_page = self.y <= MARGIN;
+        if !at_top_of_page {
+            self.y += gap;
+        }
+
+        let block_height: f32 = block.iter().map(Line::height).sum();
+        if !at_top_of_page
+            && block_height > self.remaining_height()
+            && block_height <= Self::content_height()
+        {
+            self.new_page();
+        }
+
+        for line in block {
+            let height = line.height();
+            if height > self.remaining_height() {
+                self.new_page();
+            }
+            if let Line::Text(style, text) = line {
+                // `y` points to the top of the line, but the text is placed on its baseline
+                let position = Coordinates::new(MARGIN, self.y + style.size);
+                self.document
+                    .write_text(self.page, position, style.font, style.size, &text);
+            }
+            self.y += height;
+        }
+    }
+
+    fn build_meta(&self, meta: &dyn MetaTrait) -> Block {
+        let mut block = vec![];
+        if let Some(v) = meta.title() {
+            block.push(Line::Text(self.styles.title, v));
+        }
+        if let Some(v) = meta.subtitle() {
+            block.push(Line::Text(self.styles.subtitle, v));
+        }
+
+        let mut credits = vec![];
+        if let Some(v) = meta.artist() {
+            credits.push(format!("Artist: {}", v));
+        }
+        if let Some(v) = meta.composer() {
+            credits.push(format!("Composer: {}", v));
+        }
+        if let Some(v) = meta.lyricist() {
+            credits.push(format!("Lyricist: {}", v));
+        }
+        if !credits.is_empty() {
+            block.push(Line::Text(self.styles.meta, credits.join("   ")));
+        }
+
+        let mut details = vec![];
+        if let Some(v) = meta.key() {
+            details.push(format!(
+                "Key: {}",
+                v.note_format(self.formatting.with_key(None))
+            ));
+        }
+        if let Some(v) = meta.capo() {
+            details.push(format!("Capo: {}", v));
+        }
+        if let Some(v) = meta.tempo() {
+            details.push(format!("Tempo: {}", v));
+        }
+        if let Some(v) = meta.time() {
+            details.push(format!("Time: {}", v));
+        }
+        if !details.is_empty() {
+            block.push(Line::Text(self.styles.meta, details.join("   ")));
+        }
+
+        block
+    }
+
+    fn build_blocks(&self, node: &Node) -> Vec<Block> {
+        match node {
+            Node::Document(children) => children
+                .iter()
+                .flat_map(|child| self.build_blocks(child))
+                .collect(),
+            Node::Section { head, children, .. } => {
+                let mut block = match head.as_ref() {
+                    // The title has already been rendered as part of the metadata
+                    Node::Headline(Token::Headline { level: 1, .. }) => vec![],
+                    Node::Headline(Token::Headline { text, .. }) => {
+                        vec![Line::Text(self.styles.headline, text.to_owned())]
+                    }
+                    Node::Quote(Token::Quote(text)) => {
+                        vec![Line::Text(self.styles.reference, text.to_owned())]
+                    }
+                    _ => vec![],
+                };
+                block.append(&mut self.build_lines(children));
+
+                vec![block]
+            }
+            _ => vec![self.build_lines(std::slice::from_ref(node))],
+        }
+    }
+
+    fn build_lines(&self, nodes: &[Node]) -> Block {
+        let mut block = vec![];
+        let mut segments = vec![];
+        let mut previous: Option<&Node> = None;
+        for node in nodes {
+            match node {
+                Node::ChordTextPair { chords, text, .. } => segments.push(Segment {
+                    chord: Some(chords.note_format(self.formatting)),
+                    text: literal(text),
+                }),
+                Node::ChordStandalone(chords, _) => segments.push(Segment {
+                    chord: Some(chords.note_format(self.formatting)),
+                    text: String::new(),
+                }),
+                Node::Text(text) => segments.push(Segment {
+                    chord: None,
+                    text: literal(text),
+                }),
+                Node::Newline => {
+                    if segments.is_empty() {
+                        // Only blank lines add space, not the end of a headline
+                        if previous == Some(&Node::Newline) {
+                            block.push(Line::Spacer(self.styles.lyrics.line_height() * 0.5));
+                        }
+                    } else {
+                        block.append(&mut self.build_rows(&segments));
+                        segments.clear();
+                    }
+                }
+                Node::Section { .. } | Node::Document(_) => {
+                    for mut nested in self.build_blocks(node) {
+                        block.append(&mut nested)
+                    }
+                }
+                Node::Meta(_) | Node::Headline(_) | Node::Quote(_) => {}
+            }
+            previous = Some(node);
+        }
+        block.append(&mut self.build_rows(&segments));
+
+        block
+    }
+
+    /// Build the chord and lyrics rows for the segments of one line
+    ///
+    /// If the line is too long it will be wrapped at segment boundaries
+    fn build_rows(&self, segments: &[Segment]) -> Block {
+        let max_columns = self.max_columns();
+
+        let mut lines = vec![];
+        let mut chord_row = String::new();
+        let mut lyrics_row = String::new();
+        for segment in segments {
+            let segment_width = segment_width(&chord_row, &lyrics_row, segment);
+            if segment_width > max_columns && !lyrics_row.trim().is_empty() {
+                self.push_rows(&mut lines, &chord_row, &lyrics_row);
+                chord_row.clear();
+                lyrics_row.clear();
+            }
+
+            if let Some(chord) = &segment.chord {
+                let start = chord_start(&chord_row, &lyrics_row);
+                pad(&mut chord_row, start);
+                pad(&mut lyrics_row, start);
+                chord_row.push_str(chord);
+            }
+            if lyrics_row.is_empty() {
+                lyrics_row.push_str(segment.text.trim_start());
+            } else {
+                lyrics_row.push_str(&segment.text);
+            }
+        }
+        self.push_rows(&mut lines, &chord_row, &lyrics_row);
+
+        lines
+    }
+
+    fn push_rows(&self, lines: &mut Block, chord_row: &str, lyrics_row: &str) {
+        if !chord_row.trim().is_empty() {
+            lines.push(Line::Text(
+                self.styles.chord,
+                chord_row.trim_end().to_owned(),
+            ));
+        }
+        if !lyrics_row.trim().is_empty() {
+            lines.push(Line::Text(
+                self.styles.lyrics,
+                lyrics_row.trim_end().to_owned(),
+            ));
+        }
+    }
+}
+
+fn literal(token: &Token) -> String {
+    match token {
+        Token::Literal(text) => text.to_owned(),
+        _ => String::new(),
+    }
+}
+
+/// Column at which the next chord should start
+///
+/// The chord is placed above the current end of the lyrics, but must not overlap the previous chord
+fn chord_start(chord_row: &str, lyrics_row: &str) -> usize {
+    let chord_length = chord_row.chars().count();
+    let lyrics_length = lyrics_row.chars().count();
+    if chord_length > 0 {
+        lyrics_length.max(chord_length + 1)
+    } else {
+        lyrics_length
+    }
+}
+
+/// Number of columns the rows would occupy after adding the segment
+fn segment_width(chord_row: &str, lyrics_row: &str, segment: &Segment) -> usize {
+    let text_length = segment.text.trim_end().chars().count();
+    match &segment.chord {
+        Some(chord) => {
+            let start = chord_start(chord_row, lyrics_row);
+            (start + chord.chars().count()).max(start + text_length)
+        }
+        None => lyrics_row.chars().count() + text_length,
+    }
+}
+
+fn pad(row: &mut String, columns: usize) {
+    let length = row.chars().count();
+    if length < columns {
+        row.push_str(&" ".repeat(columns - length));
+    }
+}
+
+/// Remove spacers from the start and end of the block
+fn trim_block(mut block: Block) -> Block {
+    while let Some(Line::Spacer(_)) = block.last() {
+        block.pop();
+    }
+    let first_text = block
+        .iter()
+        .position(|line| matches!(line, Line::Text(..)))
+        .unwrap_or(block.len());
+    block.split_off(first_text)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::format::Format;
+    use crate::test_helpers::get_test_ast;
+
+    use super::*;
+
+    fn get_text_lines(block: &Block) -> Vec<&str> {
+        block
+            .iter()
+            .filter_map(|line| match line {
+                Line::Text(_, text) => Some(text.as_str()),
+                Line::Spacer(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_build_blocks() {
+        let builder = PdfBuilder {
+            formatting: Formatting::with_format(Format::PDF),
+            ..PdfBuilder::new("Test", Styles::default())
+        };
+        let blocks = builder.build_blocks(&get_test_ast());
+        let blocks = blocks
+            .into_iter()
+            .map(trim_block)
+            .filter(|b| !b.is_empty())
+            .collect::<Vec<Block>>();
+
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(
+            get_text_lines(&blocks[0]),
+            vec![
+                "Chorus",
+                "      D          G    D",
+                "Swing low, sweet chariot,",
+                "                       A7",
+                "Comin’ for to carry me home.",
+                "      D7         G    D",
+                "Swing low, sweet chariot,",
+                "              A7       D",
+                "Comin’ for to carry me home.",
+            ]
+        );
+        assert_eq!(get_text_lines(&blocks[2]), vec!["Chorus"]);
+    }
+
+    #[test]
+    fn test_build_rows_do_not_overlap() {
+        let builder = PdfBuilder {
+            formatting: Formatting::with_format(Format::PDF),
+            ..PdfBuilder::new("Test", Styles::default())
+        };
+        let segments = vec![
+            Segment {
+                chord: Some("Cmaj7".to_owned()),
+                text: "a".to_owned(),
+            },
+            Segment {
+                chord: Some("G".to_owned()),
+                text: "b".to_owned(),
+            },
+        ];
+        assert_eq!(
+            get_text_lines(&builder.build_rows(&segments)),
+            vec!["Cmaj7 G", "a     b"]
+        );
+    }
+
+    #[test]
+    fn test_build_rows_wrap() {
+        let builder = PdfBuilder {
+            formatting: Formatting::with_format(Format::PDF),
+            ..PdfBuilder::new("Test", Styles::default())
+        };
+        let segments = (0..20)
+            .map(|_| Segment {
+                chord: Some("Am".to_owned()),
+                text: "la la la ".to_owned(),
+            })
+            .collect::<Vec<Segment>>();
+        let lines = builder.build_rows(&segments);
+        let lines = get_text_lines(&lines);
+
+        assert_eq!(lines.len(), 6);
+        assert!(lines.iter().all(|l| l.chars().count() <= 82));
+        assert!(lines[2].starts_with("Am"));
+    }
+}
@@ -0,0 +1,46 @@
+use super::font::Font;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct Style {
+    pub font: Font,
+    pub size: f32,
+}
+
+impl Style {
+    pub fn new(font: Font, size: f32) -> Self {
+        Self { font, size }
+    }
+
+    pub fn line_height(&self) -> f32 {
+        self.size * 1.25
+    }
+
+    pub fn character_width(&self) -> f32 {
+        self.font.character_width() * self.size
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct Styles {
+    pub title: Style,
+    pub subtitle: Style,
+    pub meta: Style,
+    pub headline: Style,
+    pub chord: Style,
+    pub lyrics: Style,
+    pub reference: Style,
+}
+
+impl Default for Styles {
+    fn default() -> Self {
+        Self {
+            title: Style::new(Font::Bold, 20.0),
+            subtitle: Style::new(Font::Italic, 12.0),
+            meta: Style::new(Font::Regular, 10.0),
+            headline: Style::new(Font::Bold, 12.0),
+            chord: Style::new(Font::MonoBold, 10.0),
+            lyrics: Style::new(Font::Mono, 10.0),
+            reference: Style::new(Font::Italic, 11.0),
+        }
+    }
+}
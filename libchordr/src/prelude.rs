@@ -3,9 +3,12 @@ pub use crate::error::Error;
 pub use crate::error::Result;
 
 /// Tokenization
+pub use crate::tokenizer::build_chord_pro_tokenizer;
 pub use crate::tokenizer::build_tokenizer;
+pub use crate::tokenizer::Meta;
 pub use crate::tokenizer::Token;
 pub use crate::tokenizer::Tokenizer;
+pub use crate::tokenizer::{Position, Span, SpannedToken, TokenizerError, TokenizerErrorKind};
 
 /// Format conversion
 pub use crate::converter::Converter;
@@ -18,15 +21,21 @@ pub use crate::parser::Parser;
 pub use crate::parser::ParserResult;
 pub use crate::parser::ParserTrait;
 
+/// Chord diagrams
+pub use crate::chord_diagram::{ChordDiagram, Instrument, Tuning};
+
+/// Key detection
+pub use crate::key_detector::{KeyDetector, KeyGuess};
+
 /// Search
 pub use crate::search::SearchIndex;
 
 /// Data structures
-pub use crate::models::catalog::{Catalog, CatalogTrait};
+pub use crate::models::catalog::{Catalog, CatalogDiff, CatalogTrait};
 pub use crate::models::chord::fmt::Formatting;
 pub use crate::models::file_type::FileType;
 pub use crate::models::list::*;
-pub use crate::models::meta::{BNotation, MetaTrait, SemitoneNotation};
+pub use crate::models::meta::{BNotation, ChordNotation, MetaTrait, SemitoneNotation};
 #[allow(deprecated)]
 pub use crate::models::record_id_trait::RecordIdTrait;
 pub use crate::models::record_trait::RecordTrait;
@@ -41,8 +50,19 @@ pub use crate::models::song_sorting::SongSorting;
 pub use crate::models::team::{Team, TeamId};
 pub use crate::models::user::{Credentials, MainData, Password, User, Username};
 
+/// Validation
+pub use crate::validation::{Diagnostic, DiagnosticKind, Severity, Validator};
+
+/// Source formatting
+pub use crate::formatter::{ChorddownFormatter, FormatMode};
+
+/// Songbook export
+pub use crate::songbook::{Songbook, SongbookEntry};
+
 /// Catalog management
-pub use crate::catalog_builder::{CatalogBuildError, CatalogBuildResult, CatalogBuilder};
+pub use crate::catalog_builder::{
+    CatalogBuildError, CatalogBuildResult, CatalogBuilder, CatalogCache, CatalogChanges,
+};
 
 /// Helper methods
 pub use crate::helper::*;
@@ -0,0 +1,60 @@
+/// Return the edit distance between `a` and `b` if it is not larger than `max`
+///
+/// Insertions, deletions, substitutions and transpositions of two adjacent characters count as
+/// one edit (optimal string alignment distance)
+pub(crate) fn bounded_distance(a: &str, b: &str, max: usize) -> Option<usize> {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+
+    // Rows of the distance matrix for the previous two and the current character of `a`
+    let mut before_previous: Vec<usize> = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current: Vec<usize> = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        current[0] = i;
+        let mut row_minimum = current[0];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (previous[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(before_previous[j - 2] + 1);
+            }
+            current[j] = distance;
+            row_minimum = row_minimum.min(distance);
+        }
+        if row_minimum > max {
+            return None;
+        }
+        std::mem::swap(&mut before_previous, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    let distance = previous[b.len()];
+    if distance <= max {
+        Some(distance)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounded_distance() {
+        assert_eq!(bounded_distance("chariot", "chariot", 2), Some(0));
+        assert_eq!(bounded_distance("chariot", "chariott", 2), Some(1));
+        assert_eq!(bounded_distance("chariot", "cahriot", 2), Some(1));
+        assert_eq!(bounded_distance("chariot", "charot", 2), Some(1));
+        assert_eq!(bounded_distance("chariot", "charity", 2), Some(2));
+        assert_eq!(bounded_distance("chariot", "carrot", 1), None);
+        assert_eq!(bounded_distance("chariot", "car", 2), None);
+        assert_eq!(bounded_distance("", "ab", 2), Some(2));
+    }
+}
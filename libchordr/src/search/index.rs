@@ -1,105 +1,323 @@
-use crate::format::Format;
-use crate::models::meta::Tags;
-use crate::prelude::{
-    convert_to_format, Catalog, CatalogTrait, Formatting, ListEntryTrait, MetaTrait, Result, Song,
-    SongId,
-};
-use std::collections::HashMap;
-use std::str::FromStr;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Bound;
 
-#[derive(Debug)]
-struct IndexEntry {
-    tags: Tags,
-    text: String,
-}
+use crate::helper::parse_content_for_file_type;
+use crate::parser::Node;
+use crate::prelude::{Catalog, CatalogTrait, ListEntryTrait, MetaTrait, Song, SongData, SongId};
+use crate::tokenizer::Token;
 
-impl IndexEntry {
-    fn search_in_tags(&self, search: &str) -> bool {
-        if self.tags.is_empty() {
-            return false;
-        }
+use super::distance::bounded_distance;
+use super::progression::{Progression, ProgressionChord};
+use super::query::Query;
+use super::tokenizer::tokenize;
 
-        // Try to parse the whole search string into a list of Tags
-        let search_term_as_tags = match Tags::from_str(search) {
-            Ok(t) => t,
-            Err(_) => return false,
-        };
+/// Weight of a match in the respective field of a song
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Field {
+    Title,
+    Artist,
+    Album,
+    Lyrics,
+}
 
-        for search_tag in search_term_as_tags.iter() {
-            if self
-                .tags
-                .iter()
-                .find(|t| t.as_str().to_lowercase() == search_tag.as_str())
-                .is_none()
-            {
-                // Return false if one of the searched tags was NOT found
-                return false;
-            }
+impl Field {
+    fn weight(self) -> f32 {
+        match self {
+            Field::Title => 10.0,
+            Field::Artist => 5.0,
+            Field::Album => 3.0,
+            Field::Lyrics => 1.0,
         }
-
-        true
     }
+}
 
-    fn contains(&self, search: &str) -> bool {
-        if search.starts_with('#') {
-            if self.search_in_tags(search) {
-                return true;
-            }
-        }
+/// Factor for terms that start with the searched term (e.g. while the user is still typing)
+const PREFIX_MATCH: f32 = 0.7;
+/// Factor for terms that differ from the searched term by one edit
+const ONE_TYPO_MATCH: f32 = 0.5;
+/// Factor for terms that differ from the searched term by two edits
+const TWO_TYPOS_MATCH: f32 = 0.25;
+/// Minimum length of a search term to be matched as prefix
+const MIN_PREFIX_LENGTH: usize = 2;
 
-        self.text.contains(search)
-    }
+#[derive(Debug)]
+struct Posting {
+    document: usize,
+    field: Field,
+    /// Positions of the term in the field
+    positions: Vec<u32>,
 }
 
-type Storage = HashMap<SongId, IndexEntry>;
+#[derive(Debug)]
+struct Document {
+    song_id: SongId,
+    title: String,
+    /// Sequence of the song's chords
+    chords: Vec<ProgressionChord>,
+}
 
+/// Inverted index mapping each term to the songs (and fields) it appears in
 #[derive(Debug)]
 pub struct Index {
-    map: Storage,
+    documents: Vec<Document>,
+    terms: BTreeMap<String, Vec<Posting>>,
 }
 
 impl Index {
     pub(super) fn build_index(catalog: &Catalog) -> Self {
-        let mut index = Self::with_capacity(catalog.len());
+        let mut index = Self {
+            documents: Vec::with_capacity(catalog.len()),
+            terms: BTreeMap::new(),
+        };
         for song in catalog.iter() {
-            if let Ok(text) = extract_text(song) {
-                index.map.insert(
-                    song.id(),
-                    IndexEntry {
-                        text: text.to_lowercase(),
-                        tags: song.meta().tags(),
-                    },
-                );
-            }
+            index.add_song(song);
         }
         index
     }
 
-    pub(super) fn search(&self, search: &str) -> Vec<&SongId> {
-        self.map
+    fn add_song(&mut self, song: &Song) {
+        let document = self.documents.len();
+        let meta = song.meta();
+        let mut lyrics = String::new();
+        let mut chords = vec![];
+        if let Ok(result) = parse_content_for_file_type(song.src().as_bytes(), song.file_type()) {
+            let node = result.node_as_ref();
+            collect_text(node, &mut lyrics);
+            chords = node.chords().into_iter().map(Into::into).collect();
+        }
+        self.documents.push(Document {
+            song_id: song.id(),
+            title: song.title().to_lowercase(),
+            chords,
+        });
+
+        let fields = [
+            (Field::Title, Some(song.title())),
+            (Field::Artist, meta.artist()),
+            (Field::Album, meta.album()),
+            (Field::Lyrics, Some(lyrics)),
+        ];
+        for (field, text) in fields {
+            let text = match text {
+                Some(t) => t,
+                None => continue,
+            };
+            let mut positions: HashMap<String, Vec<u32>> = HashMap::new();
+            for (position, term) in tokenize(&text).into_iter().enumerate() {
+                positions.entry(term).or_default().push(position as u32);
+            }
+            for (term, positions) in positions {
+                self.terms.entry(term).or_default().push(Posting {
+                    document,
+                    field,
+                    positions,
+                });
+            }
+        }
+    }
+
+    /// Return the IDs of the songs matching the text of the query ordered by their relevance
+    ///
+    /// A song matches if it contains all terms (or similar terms) and phrases of the query and
+    /// none of the excluded terms and phrases. If the query contains no text all songs match
+    pub(super) fn search(&self, query: &Query) -> Vec<(&SongId, f32)> {
+        let mut scores: Vec<Option<f32>> = vec![Some(0.0); self.documents.len()];
+        let mut add_scores = |term_scores: HashMap<usize, f32>| {
+            for (document, score) in scores.iter_mut().enumerate() {
+                *score = match (*score, term_scores.get(&document)) {
+                    (Some(total), Some(term_score)) => Some(total + term_score),
+                    _ => None,
+                };
+            }
+        };
+        for term in &query.terms {
+            add_scores(self.score_term(term));
+        }
+        for phrase in &query.phrases {
+            add_scores(self.score_phrase(phrase));
+        }
+        for progression in &query.progressions {
+            add_scores(self.score_progression(progression));
+        }
+
+        let mut excluded: Vec<bool> = vec![false; self.documents.len()];
+        for term in &query.excluded_terms {
+            for posting in self.terms.get(term).into_iter().flatten() {
+                excluded[posting.document] = true;
+            }
+        }
+        for phrase in &query.excluded_phrases {
+            for document in self.score_phrase(phrase).into_keys() {
+                excluded[document] = true;
+            }
+        }
+        for progression in &query.excluded_progressions {
+            for document in self.score_progression(progression).into_keys() {
+                excluded[document] = true;
+            }
+        }
+
+        let mut result: Vec<(&Document, f32)> = scores
+            .into_iter()
+            .enumerate()
+            .filter(|(document, _)| !excluded[*document])
+            .filter_map(|(document, score)| Some((&self.documents[document], score?)))
+            .collect();
+        result.sort_by(|(a_document, a_score), (b_document, b_score)| {
+            b_score
+                .partial_cmp(a_score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a_document.title.cmp(&b_document.title))
+        });
+
+        result
+            .into_iter()
+            .map(|(document, score)| (&document.song_id, score))
+            .collect()
+    }
+
+    /// Return the score of each document containing the chord progression
+    ///
+    /// Songs using the progression more often rank higher
+    fn score_progression(&self, progression: &Progression) -> HashMap<usize, f32> {
+        self.documents
+            .iter()
+            .enumerate()
+            .filter_map(
+                |(index, document)| match progression.count_in(&document.chords) {
+                    0 => None,
+                    count => Some((index, 1.0 + (count as f32).ln())),
+                },
+            )
+            .collect()
+    }
+
+    /// Return the score of each document containing the exact `phrase`
+    fn score_phrase(&self, phrase: &[String]) -> HashMap<usize, f32> {
+        let mut scores: HashMap<usize, f32> = HashMap::new();
+        let postings: Vec<&Vec<Posting>> = match phrase
             .iter()
-            .filter_map(|(song_id, entry)| {
-                if entry.contains(search) {
-                    Some(song_id)
-                } else {
-                    None
+            .map(|term| self.terms.get(term))
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(p) => p,
+            None => return scores,
+        };
+
+        for first in postings[0] {
+            let occurrences = first
+                .positions
+                .iter()
+                .filter(|start| {
+                    postings[1..]
+                        .iter()
+                        .enumerate()
+                        .all(|(offset, term_postings)| {
+                            term_postings.iter().any(|p| {
+                                p.document == first.document
+                                    && p.field == first.field
+                                    && p.positions.contains(&(**start + offset as u32 + 1))
+                            })
+                        })
+                })
+                .count();
+            if occurrences > 0 {
+                let idf = (1.0 + self.documents.len() as f32 / postings[0].len() as f32).ln();
+                let score = first.field.weight()
+                    * (1.0 + (occurrences as f32).ln())
+                    * idf
+                    * phrase.len() as f32;
+                let entry = scores.entry(first.document).or_default();
+                if score > *entry {
+                    *entry = score;
                 }
+            }
+        }
+        scores
+    }
+
+    /// Return the score of each document containing `term` (or a similar term)
+    fn score_term(&self, term: &str) -> HashMap<usize, f32> {
+        let mut scores: HashMap<usize, f32> = HashMap::new();
+        for (indexed_term, postings) in self.matching_terms(term) {
+            let factor = match_factor(term, indexed_term);
+            let idf = (1.0 + self.documents.len() as f32 / postings.len() as f32).ln();
+            for posting in postings {
+                let score = posting.field.weight()
+                    * (1.0 + (posting.positions.len() as f32).ln())
+                    * idf
+                    * factor;
+                let entry = scores.entry(posting.document).or_default();
+                // Only count the best match of the term in each document
+                if score > *entry {
+                    *entry = score;
+                }
+            }
+        }
+        scores
+    }
+
+    /// Return the indexed terms starting with `term`, or the terms with typos if there are none
+    fn matching_terms<'a>(&'a self, term: &str) -> Vec<(&'a String, &'a Vec<Posting>)> {
+        // The vocabulary is sorted, so all terms starting with `term` directly follow it
+        let prefix_matches: Vec<_> = self
+            .terms
+            .range::<str, _>((Bound::Included(term), Bound::Unbounded))
+            .take_while(|(indexed_term, _)| indexed_term.starts_with(term))
+            .filter(|(indexed_term, _)| {
+                term.len() >= MIN_PREFIX_LENGTH || indexed_term.as_str() == term
             })
+            .collect();
+        let max_typos = max_typos(term);
+        if !prefix_matches.is_empty() || max_typos == 0 {
+            return prefix_matches;
+        }
+
+        self.terms
+            .iter()
+            .filter(|(indexed_term, _)| bounded_distance(term, indexed_term, max_typos).is_some())
             .collect()
     }
+}
 
-    fn with_capacity(capacity: usize) -> Self {
-        Self {
-            map: HashMap::with_capacity(capacity),
-        }
+/// Longer terms may contain more typos
+fn max_typos(term: &str) -> usize {
+    match term.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
     }
 }
 
-fn extract_text(s: &Song) -> Result<String> {
-    let formatting = Formatting {
-        format: Format::Text,
-        ..Formatting::default()
-    };
+fn match_factor(term: &str, indexed_term: &str) -> f32 {
+    if term == indexed_term {
+        return 1.0;
+    }
+    if indexed_term.starts_with(term) {
+        return PREFIX_MATCH;
+    }
+    match bounded_distance(term, indexed_term, max_typos(term)) {
+        Some(1) => ONE_TYPO_MATCH,
+        _ => TWO_TYPOS_MATCH,
+    }
+}
 
-    convert_to_format(s.src().as_bytes(), s.meta(), formatting)
+fn collect_text(node: &Node, lyrics: &mut String) {
+    match node {
+        Node::ChordTextPair {
+            text: Token::Literal(text),
+            ..
+        }
+        | Node::Text(Token::Literal(text))
+        | Node::Quote(Token::Quote(text)) => {
+            lyrics.push_str(text);
+        }
+        Node::Newline => lyrics.push('\n'),
+        Node::Document(children) | Node::Section { children, .. } => {
+            for child in children {
+                collect_text(child, lyrics)
+            }
+        }
+        _ => {}
+    }
 }
@@ -2,8 +2,14 @@ use std::rc::Rc;
 
 use crate::prelude::{Catalog, CatalogTrait, Song};
 use crate::search::index::Index;
+use crate::search::progression::Progression;
+use crate::search::query::Query;
 
+mod distance;
 mod index;
+mod progression;
+mod query;
+mod tokenizer;
 
 #[derive(Debug)]
 pub struct SearchIndex {
@@ -19,19 +25,47 @@ impl SearchIndex {
         }
     }
 
-    /// Return the [Song]s from the [Catalog] filtered by [search]
+    /// Return the [Song]s from the [Catalog] matching [search] ordered by their relevance
+    ///
+    /// Matches in the title rank higher than matches in the artist, album or lyrics. Search terms
+    /// also match words they are a prefix of and words with small typos.
+    ///
+    /// The search may contain filters for the song's metadata, tags and exact phrases, e.g.
+    /// `artist:hillsong key:G tempo:<90 #christmas -#archived "exact phrase"` or chord progressions
+    /// like `progression:1-5-6-4`
     pub fn search_by_term(&self, search: &str) -> Vec<&Song> {
+        self.search_with_score(search)
+            .into_iter()
+            .map(|(song, _)| song)
+            .collect()
+    }
+
+    /// Return the [Song]s containing the chord progression (in any key) ordered by relevance
+    ///
+    /// The progression can be given as Nashville numbers (`1-5-6-4`), Roman numerals
+    /// (`I V vi IV`) or chord names (`G D Em C`). An invalid progression matches no songs
+    pub fn search_by_progression(&self, progression: &str) -> Vec<&Song> {
+        if Progression::parse(progression).is_none() {
+            return vec![];
+        }
+
+        self.search_by_term(&format!("progression:\"{}\"", progression))
+    }
+
+    /// Return the [Song]s from the [Catalog] matching [search] together with their relevance score
+    pub fn search_with_score(&self, search: &str) -> Vec<(&Song, f32)> {
         if search.is_empty() || search.trim().is_empty() {
             // If the search is empty return all songs
-            return self.catalog.iter().collect();
+            return self.catalog.iter().map(|song| (song, 0.0)).collect();
         }
 
-        let search_normalized = search.to_lowercase();
+        let query = Query::parse(search);
 
         self.index
-            .search(&search_normalized)
+            .search(&query)
             .into_iter()
-            .filter_map(|song_id| self.catalog.get(song_id))
+            .filter_map(|(song_id, score)| Some((self.catalog.get(song_id)?, score)))
+            .filter(|(song, _)| query.matches_meta(song.meta()))
             .collect()
     }
 }
@@ -40,9 +74,55 @@ impl SearchIndex {
 mod test {
     use std::rc::Rc;
 
+    use crate::helper::parse_content;
+    use crate::models::file_type::FileType;
+    use crate::prelude::{Catalog, ListEntryTrait, MetaTrait, Song, SongMeta};
     use crate::search::SearchIndex;
     use crate::test_helpers::get_test_catalog;
 
+    fn song(id: &str, src: &str) -> Song {
+        let result = parse_content(src.as_bytes()).unwrap();
+        let meta = SongMeta::new_with_meta_information(
+            id.into(),
+            result.meta_as_ref().title().unwrap(),
+            FileType::Chorddown,
+            result.meta_as_ref(),
+        );
+        Song::new(meta, src)
+    }
+
+    fn ranking_catalog() -> Catalog {
+        Catalog::new(
+            "ranking-catalog",
+            vec![
+                song(
+                    "lyrics",
+                    "# Nobody Knows\n\n## Verse\n[D]Amazing grace is what they [G]sing\n",
+                ),
+                song(
+                    "artist",
+                    "# Another Song\nArtist: Grace Kelly\n\n## Verse\n[C]La la la\n",
+                ),
+                song(
+                    "title",
+                    "# Amazing Grace\n\n## Verse\n[G]How sweet the [C]sound\n",
+                ),
+                song(
+                    "umlaut",
+                    "# Über den Wolken\nArtist: Reinhard Mey\n\n## Verse\n[C]Wind Nord-Ost, Startbahn null-drei\n",
+                ),
+            ],
+        )
+    }
+
+    fn search_ids(search_index: &SearchIndex, search: &str) -> Vec<String> {
+        search_index
+            .search_by_term(search)
+            .into_iter()
+            .map(|song| song.id().to_string())
+            .collect()
+    }
+
     #[test]
     fn test_search_by_term() {
         let search_index = SearchIndex::build_for_catalog(Rc::new(get_test_catalog()));
@@ -58,4 +138,112 @@ mod test {
         assert_eq!(search_index.search_by_term("    ").len(), 5);
         assert_eq!(search_index.search_by_term(" \t \n    ").len(), 5);
     }
+
+    #[test]
+    fn test_search_by_term_field_weighting() {
+        let search_index = SearchIndex::build_for_catalog(Rc::new(ranking_catalog()));
+
+        assert_eq!(
+            search_ids(&search_index, "grace"),
+            vec!["title", "artist", "lyrics"]
+        );
+        assert_eq!(
+            search_ids(&search_index, "amazing grace"),
+            vec!["title", "lyrics"]
+        );
+    }
+
+    #[test]
+    fn test_search_by_term_prefix_and_typos() {
+        let search_index = SearchIndex::build_for_catalog(Rc::new(ranking_catalog()));
+
+        assert_eq!(search_ids(&search_index, "amaz"), vec!["title", "lyrics"]);
+        assert_eq!(
+            search_ids(&search_index, "amzaing"),
+            vec!["title", "lyrics"]
+        );
+        assert_eq!(search_ids(&search_index, "startban"), vec!["umlaut"]);
+        // Terms with typos (e.g. "song") are only searched if no term starts with the search term
+        assert_eq!(search_ids(&search_index, "sing"), vec!["lyrics"]);
+        assert!(search_ids(&search_index, "xylophone").is_empty());
+    }
+
+    #[test]
+    fn test_search_by_term_diacritics() {
+        let search_index = SearchIndex::build_for_catalog(Rc::new(ranking_catalog()));
+
+        assert_eq!(search_ids(&search_index, "über"), vec!["umlaut"]);
+        assert_eq!(search_ids(&search_index, "ueber wolken"), vec!["umlaut"]);
+        assert_eq!(search_ids(&search_index, "uber"), vec!["umlaut"]);
+    }
+
+    #[test]
+    fn test_search_by_tag() {
+        let search_index = SearchIndex::build_for_catalog(Rc::new(get_test_catalog()));
+
+        assert!(search_index.search_by_term("#not-a-tag").is_empty());
+    }
+
+    #[test]
+    fn test_search_with_score() {
+        let search_index = SearchIndex::build_for_catalog(Rc::new(ranking_catalog()));
+        let result = search_index.search_with_score("grace");
+
+        assert_eq!(result.len(), 3);
+        assert!(result[0].1 > result[1].1);
+        assert!(result[1].1 > result[2].1);
+    }
+
+    #[test]
+    fn test_search_by_query() {
+        let search_index = SearchIndex::build_for_catalog(Rc::new(ranking_catalog()));
+
+        assert_eq!(
+            search_ids(&search_index, "\"grace is what\""),
+            vec!["lyrics"]
+        );
+        assert!(search_ids(&search_index, "\"grace amazing\"").is_empty());
+        assert_eq!(
+            search_ids(&search_index, "grace -artist:kelly"),
+            vec!["title", "lyrics"]
+        );
+        assert_eq!(
+            search_ids(&search_index, "grace -sound"),
+            vec!["artist", "lyrics"]
+        );
+        assert_eq!(search_ids(&search_index, "artist:mey"), vec!["umlaut"]);
+        assert_eq!(
+            search_ids(&search_index, "wolken artist:mey"),
+            vec!["umlaut"]
+        );
+        assert!(search_ids(&search_index, "grace artist:mey").is_empty());
+    }
+
+    #[test]
+    fn test_search_by_progression() {
+        let search_index = SearchIndex::build_for_catalog(Rc::new(ranking_catalog()));
+
+        // "Amazing Grace" uses `G C`, "Nobody Knows" uses `D G`
+        assert_eq!(
+            search_ids(&search_index, "progression:1-4"),
+            vec!["title", "lyrics"]
+        );
+        assert_eq!(
+            search_ids(&search_index, "progression:\"I IV\" -grace"),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            search_ids(&search_index, "progression:\"A D\" amazing"),
+            vec!["title", "lyrics"]
+        );
+        assert_eq!(
+            search_index
+                .search_by_progression("1 4")
+                .into_iter()
+                .map(|song| song.id().to_string())
+                .collect::<Vec<_>>(),
+            vec!["title", "lyrics"]
+        );
+        assert!(search_index.search_by_progression("1").is_empty());
+    }
 }
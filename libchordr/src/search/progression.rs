@@ -0,0 +1,232 @@
+use crate::models::chord::{Chord, Chords};
+use crate::models::meta::BNotation;
+
+use super::query::is_minor;
+
+/// Chord of a song reduced to the information relevant to compare progressions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ProgressionChord {
+    /// Pitch class of the chord's root (`0` to `11`)
+    root: u8,
+    minor: bool,
+}
+
+impl From<&Chords> for ProgressionChord {
+    fn from(chords: &Chords) -> Self {
+        let chord = chords.chord();
+
+        Self {
+            root: i32::from(chord.root()).rem_euclid(12) as u8,
+            minor: is_minor(chord),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Step {
+    /// Semitones above the root of the progression's first chord
+    interval: u8,
+    /// Expected quality of the chord (`None` if any quality matches)
+    minor: Option<bool>,
+}
+
+/// Sequence of chords independent of the key
+///
+/// Progressions can be written as Nashville numbers (`1-5-6m-4`), Roman numerals (`I V vi IV`)
+/// or chord names (`G D Em C`). Numbers without quality match both major and minor chords
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Progression(Vec<Step>);
+
+impl Progression {
+    /// Parse the progression or return `None` if it contains less than two chords or an invalid
+    /// chord
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        let degrees = value
+            .split(|c: char| c.is_whitespace() || c == '-' || c == ',' || c == '|')
+            .filter(|part| !part.is_empty())
+            .map(parse_degree)
+            .collect::<Option<Vec<(i32, Option<bool>)>>>()?;
+        if degrees.len() < 2 {
+            return None;
+        }
+
+        let first = degrees[0].0;
+        Some(Self(
+            degrees
+                .into_iter()
+                .map(|(degree, minor)| Step {
+                    interval: (degree - first).rem_euclid(12) as u8,
+                    minor,
+                })
+                .collect(),
+        ))
+    }
+
+    /// Return how often the progression occurs in the chords (in any key)
+    ///
+    /// Repeated chords (e.g. `G G D`) are treated as a single chord
+    pub(crate) fn count_in(&self, chords: &[ProgressionChord]) -> usize {
+        let mut chords = chords.to_vec();
+        chords.dedup();
+
+        chords
+            .windows(self.0.len())
+            .filter(|window| self.matches(window))
+            .count()
+    }
+
+    fn matches(&self, window: &[ProgressionChord]) -> bool {
+        let first = window[0].root;
+        self.0.iter().zip(window).all(|(step, chord)| {
+            (chord.root + 12 - first) % 12 == step.interval
+                && step.minor.is_none_or(|minor| minor == chord.minor)
+        })
+    }
+}
+
+/// Return the semitones of the chord above an arbitrary reference and its quality
+fn parse_degree(value: &str) -> Option<(i32, Option<bool>)> {
+    let (accidental, rest) = match value.chars().next()? {
+        'b' if value.len() > 1 && !value[1..].starts_with(char::is_alphabetic) => (-1, &value[1..]),
+        'b' if is_roman(&value[1..]) => (-1, &value[1..]),
+        '#' => (1, &value[1..]),
+        _ => (0, value),
+    };
+
+    if rest.starts_with(|c: char| c.is_ascii_digit()) {
+        let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+        let suffix = &rest[digits.len()..];
+        let semitones = scale_degree_semitones(digits.parse().ok()?)?;
+        let minor = match suffix {
+            "" => None,
+            "m" | "-" | "min" => Some(true),
+            "M" | "maj" => Some(false),
+            _ => return None,
+        };
+
+        return Some((semitones + accidental, minor));
+    }
+
+    if is_roman(rest) {
+        let numeral: String = rest
+            .chars()
+            .take_while(|c| matches!(c, 'I' | 'V' | 'i' | 'v'))
+            .collect();
+        let semitones = scale_degree_semitones(roman_to_degree(&numeral.to_uppercase())?)?;
+        let minor = numeral.chars().all(|c| c.is_lowercase());
+
+        return Some((semitones + accidental, Some(minor)));
+    }
+
+    let chord = Chord::try_from(value, BNotation::B).ok()?;
+
+    Some((i32::from(chord.root()), Some(is_minor(&chord))))
+}
+
+fn is_roman(value: &str) -> bool {
+    let numeral: String = value
+        .chars()
+        .take_while(|c| matches!(c, 'I' | 'V' | 'i' | 'v'))
+        .collect();
+
+    !numeral.is_empty() && roman_to_degree(&numeral.to_uppercase()).is_some()
+}
+
+fn roman_to_degree(numeral: &str) -> Option<u8> {
+    match numeral {
+        "I" => Some(1),
+        "II" => Some(2),
+        "III" => Some(3),
+        "IV" => Some(4),
+        "V" => Some(5),
+        "VI" => Some(6),
+        "VII" => Some(7),
+        _ => None,
+    }
+}
+
+/// Semitones of the scale degree above the tonic of a major scale
+fn scale_degree_semitones(degree: u8) -> Option<i32> {
+    match degree {
+        1 => Some(0),
+        2 => Some(2),
+        3 => Some(4),
+        4 => Some(5),
+        5 => Some(7),
+        6 => Some(9),
+        7 => Some(11),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chords(names: &str) -> Vec<ProgressionChord> {
+        names
+            .split_whitespace()
+            .map(|name| ProgressionChord::from(&Chords::try_from(name, BNotation::B).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let expected = Progression::parse("G D Em C").unwrap();
+        assert_eq!(
+            expected,
+            Progression(vec![
+                Step {
+                    interval: 0,
+                    minor: Some(false)
+                },
+                Step {
+                    interval: 7,
+                    minor: Some(false)
+                },
+                Step {
+                    interval: 9,
+                    minor: Some(true)
+                },
+                Step {
+                    interval: 5,
+                    minor: Some(false)
+                },
+            ])
+        );
+        assert_eq!(Progression::parse("I-V-vi-IV").unwrap(), expected);
+        assert_eq!(
+            Progression::parse("1-5-6m-4").unwrap().0[2].minor,
+            Some(true)
+        );
+        assert_eq!(Progression::parse("1 5 6 4").unwrap().0[2].minor, None);
+        assert_eq!(Progression::parse("1 b7 4").unwrap().0[1].interval, 10);
+        assert_eq!(Progression::parse("I bVII IV").unwrap().0[1].interval, 10);
+        assert_eq!(Progression::parse("Bb F").unwrap().0[1].interval, 7);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(Progression::parse("1"), None);
+        assert_eq!(Progression::parse(""), None);
+        assert_eq!(Progression::parse("1-9-4"), None);
+        assert_eq!(Progression::parse("G X C"), None);
+    }
+
+    #[test]
+    fn test_count_in() {
+        let progression = Progression::parse("1-5-6-4").unwrap();
+
+        assert_eq!(progression.count_in(&chords("G D Em C G D Em C")), 2);
+        // Transposed
+        assert_eq!(progression.count_in(&chords("A E F#m D")), 1);
+        // Repeated chords are ignored
+        assert_eq!(progression.count_in(&chords("A A E E/G# F#m7 D")), 1);
+        assert_eq!(progression.count_in(&chords("A E D F#m")), 0);
+        assert_eq!(progression.count_in(&chords("A E")), 0);
+
+        let progression = Progression::parse("G D Em C").unwrap();
+        assert_eq!(progression.count_in(&chords("C G Am F")), 1);
+        assert_eq!(progression.count_in(&chords("C G A F")), 0);
+    }
+}
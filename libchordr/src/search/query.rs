@@ -0,0 +1,478 @@
+use crate::models::chord::{Chord, Quality};
+use crate::models::meta::{BNotation, MetaTrait};
+use crate::prelude::SongMeta;
+
+use super::progression::Progression;
+use super::tokenizer::tokenize;
+
+/// Parsed search query
+///
+/// The query language supports
+///  - free text: `amazing grace`
+///  - exact phrases: `"how sweet the sound"`
+///  - field filters: `title:grace`, `artist:hillsong`, `album:"live at wembley"`, `composer:`,
+///    `lyricist:`
+///  - the key: `key:G`, `key:F#m` (the detected key is used if the song defines no key). The key
+///    is read with the song's B-Notation, so `key:H` finds songs in B written with `H`
+///  - numeric filters: `tempo:<90`, `tempo:>=120`, `tempo:80-100`, `year:1998`
+///  - tags: `#christmas` or `tag:christmas`
+///  - chord progressions in any key: `progression:1-5-6-4`, `progression:"G D Em C"`
+///
+/// Prefixing a word, phrase, filter or tag with `-` excludes the matching songs (e.g.
+/// `-#archived`). Filters with unknown fields or invalid values are searched as free text
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct Query {
+    pub(crate) terms: Vec<String>,
+    pub(crate) excluded_terms: Vec<String>,
+    pub(crate) phrases: Vec<Vec<String>>,
+    pub(crate) excluded_phrases: Vec<Vec<String>>,
+    pub(crate) progressions: Vec<Progression>,
+    pub(crate) excluded_progressions: Vec<Progression>,
+    filters: Vec<(Filter, bool)>,
+}
+
+impl Query {
+    pub(crate) fn parse(search: &str) -> Self {
+        let mut query = Query::default();
+        for word in split_words(search) {
+            query.add_word(word);
+        }
+        query
+    }
+
+    /// Return if the song's metadata fulfills all filters of the query
+    pub(crate) fn matches_meta(&self, meta: &SongMeta) -> bool {
+        self.filters
+            .iter()
+            .all(|(filter, negated)| filter.matches(meta) != *negated)
+    }
+
+    fn add_word(&mut self, word: Word) {
+        let Word {
+            negated,
+            field,
+            value,
+            quoted,
+        } = word;
+
+        if let Some(tag) = value
+            .strip_prefix('#')
+            .filter(|_| field.is_none() && !quoted)
+        {
+            if !tag.is_empty() {
+                self.filters
+                    .push((Filter::Tag(tag.to_lowercase()), negated));
+            }
+            return;
+        }
+
+        if let Some(field) = &field {
+            if matches!(field.to_lowercase().as_str(), "progression" | "chords") {
+                if let Some(progression) = Progression::parse(&value) {
+                    if negated {
+                        self.excluded_progressions.push(progression);
+                    } else {
+                        self.progressions.push(progression);
+                    }
+                    return;
+                }
+            }
+            if let Some(filter) = Filter::parse(field, &value) {
+                self.filters.push((filter, negated));
+                return;
+            }
+        }
+
+        let text = match field {
+            Some(field) => format!("{} {}", field, value),
+            None => value,
+        };
+        let terms = tokenize(&text);
+        match (quoted, negated) {
+            (true, false) if terms.len() > 1 => self.phrases.push(terms),
+            (true, true) if terms.len() > 1 => self.excluded_phrases.push(terms),
+            (_, false) => self.terms.extend(terms),
+            (_, true) => self.excluded_terms.extend(terms),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Filter {
+    Text(TextField, Vec<String>),
+    /// Key as entered, it is parsed with the B-Notation of each song
+    Key(String),
+    Tempo(Comparison),
+    Year(Comparison),
+    Tag(String),
+}
+
+impl Filter {
+    fn parse(field: &str, value: &str) -> Option<Self> {
+        let text = |text_field| {
+            let terms = tokenize(value);
+            if terms.is_empty() {
+                None
+            } else {
+                Some(Filter::Text(text_field, terms))
+            }
+        };
+
+        match field.to_lowercase().as_str() {
+            "title" => text(TextField::Title),
+            "artist" => text(TextField::Artist),
+            "album" => text(TextField::Album),
+            "composer" => text(TextField::Composer),
+            "lyricist" => text(TextField::Lyricist),
+            "key" => [BNotation::B, BNotation::H]
+                .iter()
+                .any(|b_notation| Chord::try_from(value, *b_notation).is_ok())
+                .then(|| Filter::Key(value.to_owned())),
+            "tempo" | "bpm" => Comparison::parse(value).map(Filter::Tempo),
+            "year" => Comparison::parse(value).map(Filter::Year),
+            "tag" => {
+                let tag = value.trim_matches('#').to_lowercase();
+                if tag.is_empty() {
+                    None
+                } else {
+                    Some(Filter::Tag(tag))
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn matches(&self, meta: &SongMeta) -> bool {
+        match self {
+            Filter::Text(field, terms) => match field.value(meta) {
+                Some(value) => {
+                    let field_terms = tokenize(&value);
+                    terms
+                        .iter()
+                        .all(|term| field_terms.iter().any(|t| t.starts_with(term.as_str())))
+                }
+                None => false,
+            },
+            Filter::Key(key) => {
+                let key = match Chord::try_from(key, meta.b_notation()) {
+                    Ok(key) => key,
+                    Err(_) => return false,
+                };
+                let song_key = meta
+                    .key()
+                    .or_else(|| meta.detected_key().map(|guess| guess.key.clone()));
+                match song_key {
+                    Some(song_key) => {
+                        song_key.root() == key.root() && is_minor(&song_key) == is_minor(&key)
+                    }
+                    None => false,
+                }
+            }
+            Filter::Tempo(comparison) => meta
+                .tempo()
+                .and_then(|tempo| leading_number(&tempo))
+                .is_some_and(|tempo| comparison.matches(tempo)),
+            Filter::Year(comparison) => meta
+                .year()
+                .and_then(|year| leading_number(&year))
+                .is_some_and(|year| comparison.matches(year)),
+            Filter::Tag(tag) => meta
+                .tags()
+                .iter()
+                .any(|t| &t.as_str().to_lowercase() == tag),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TextField {
+    Title,
+    Artist,
+    Album,
+    Composer,
+    Lyricist,
+}
+
+impl TextField {
+    fn value(self, meta: &SongMeta) -> Option<String> {
+        match self {
+            TextField::Title => meta.title(),
+            TextField::Artist => meta.artist(),
+            TextField::Album => meta.album(),
+            TextField::Composer => meta.composer(),
+            TextField::Lyricist => meta.lyricist(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+    Equal(u32),
+    Less(u32),
+    LessOrEqual(u32),
+    Greater(u32),
+    GreaterOrEqual(u32),
+    Range(u32, u32),
+}
+
+impl Comparison {
+    fn parse(value: &str) -> Option<Self> {
+        let number = |v: &str| v.trim().parse::<u32>().ok();
+        if let Some(v) = value.strip_prefix("<=") {
+            return number(v).map(Comparison::LessOrEqual);
+        }
+        if let Some(v) = value.strip_prefix(">=") {
+            return number(v).map(Comparison::GreaterOrEqual);
+        }
+        if let Some(v) = value.strip_prefix('<') {
+            return number(v).map(Comparison::Less);
+        }
+        if let Some(v) = value.strip_prefix('>') {
+            return number(v).map(Comparison::Greater);
+        }
+        if let Some((from, to)) = value.split_once('-') {
+            return Some(Comparison::Range(number(from)?, number(to)?));
+        }
+
+        number(value.strip_prefix('=').unwrap_or(value)).map(Comparison::Equal)
+    }
+
+    fn matches(self, value: u32) -> bool {
+        match self {
+            Comparison::Equal(n) => value == n,
+            Comparison::Less(n) => value < n,
+            Comparison::LessOrEqual(n) => value <= n,
+            Comparison::Greater(n) => value > n,
+            Comparison::GreaterOrEqual(n) => value >= n,
+            Comparison::Range(from, to) => from <= value && value <= to,
+        }
+    }
+}
+
+pub(super) fn is_minor(chord: &Chord) -> bool {
+    match chord.structure() {
+        Ok(structure) => structure.quality() == Quality::Minor,
+        Err(_) => chord
+            .variant()
+            .is_some_and(|v| v.starts_with('m') && !v.starts_with("maj")),
+    }
+}
+
+/// Parse the number at the beginning of `value` (e.g. `92` for "92 bpm")
+fn leading_number(value: &str) -> Option<u32> {
+    let digits: String = value
+        .trim()
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+
+    digits.parse().ok()
+}
+
+#[derive(Debug, PartialEq)]
+struct Word {
+    negated: bool,
+    field: Option<String>,
+    value: String,
+    quoted: bool,
+}
+
+/// Split the search into words, keeping quoted strings together
+fn split_words(search: &str) -> Vec<Word> {
+    let mut words = vec![];
+    let mut chars = search.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let negated = chars.next_if_eq(&'-').is_some();
+        let mut field = None;
+        let mut value = String::new();
+        let mut quoted = false;
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => {
+                    quoted = true;
+                    for c in chars.by_ref() {
+                        if c == '"' {
+                            break;
+                        }
+                        value.push(c);
+                    }
+                }
+                ':' if field.is_none() && !quoted && !value.is_empty() => {
+                    field = Some(std::mem::take(&mut value));
+                }
+                _ if c.is_whitespace() => break,
+                _ => value.push(c),
+            }
+        }
+
+        if field.is_some() || !value.is_empty() {
+            words.push(Word {
+                negated,
+                field,
+                value,
+                quoted,
+            });
+        }
+    }
+
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::file_type::FileType;
+
+    use super::*;
+
+    fn meta(src: &str) -> SongMeta {
+        let result = crate::helper::parse_content(src.as_bytes()).unwrap();
+        SongMeta::new_with_meta_information(
+            "song".into(),
+            result.meta_as_ref().title().unwrap(),
+            FileType::Chorddown,
+            result.meta_as_ref(),
+        )
+    }
+
+    #[test]
+    fn test_parse() {
+        let query = Query::parse(r#"Amazing -grace "how sweet the sound" -"wretch like me""#);
+
+        assert_eq!(query.terms, vec!["amazing"]);
+        assert_eq!(query.excluded_terms, vec!["grace"]);
+        assert_eq!(query.phrases, vec![vec!["how", "sweet", "the", "sound"]]);
+        assert_eq!(query.excluded_phrases, vec![vec!["wretch", "like", "me"]]);
+        assert!(query.filters.is_empty());
+    }
+
+    #[test]
+    fn test_parse_filters() {
+        let query = Query::parse(
+            r#"artist:hillsong album:"live at wembley" key:G tempo:<90 #christmas -#archived"#,
+        );
+
+        assert!(query.terms.is_empty());
+        assert!(query.phrases.is_empty());
+        assert_eq!(
+            query.filters,
+            vec![
+                (
+                    Filter::Text(TextField::Artist, vec!["hillsong".to_string()]),
+                    false
+                ),
+                (
+                    Filter::Text(
+                        TextField::Album,
+                        vec!["live".to_string(), "at".to_string(), "wembley".to_string()]
+                    ),
+                    false
+                ),
+                (Filter::Key("G".to_string()), false),
+                (Filter::Tempo(Comparison::Less(90)), false),
+                (Filter::Tag("christmas".to_string()), false),
+                (Filter::Tag("archived".to_string()), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_filters() {
+        let query = Query::parse("color:blue tempo:fast key:X");
+
+        assert!(query.filters.is_empty());
+        assert_eq!(
+            query.terms,
+            vec!["color", "blue", "tempo", "fast", "key", "x"]
+        );
+    }
+
+    #[test]
+    fn test_parse_progression() {
+        let query = Query::parse(r#"progression:1-5-6-4 -chords:"G C D" progression:G"#);
+
+        assert_eq!(
+            query.progressions,
+            vec![Progression::parse("1-5-6-4").unwrap()]
+        );
+        assert_eq!(
+            query.excluded_progressions,
+            vec![Progression::parse("I IV V").unwrap()]
+        );
+        assert_eq!(query.terms, vec!["progression", "g"]);
+    }
+
+    #[test]
+    fn test_comparison() {
+        assert_eq!(Comparison::parse("90"), Some(Comparison::Equal(90)));
+        assert_eq!(
+            Comparison::parse(">=120"),
+            Some(Comparison::GreaterOrEqual(120))
+        );
+        assert_eq!(
+            Comparison::parse("80-100"),
+            Some(Comparison::Range(80, 100))
+        );
+        assert_eq!(Comparison::parse("<slow"), None);
+        assert!(Comparison::Range(80, 100).matches(100));
+        assert!(!Comparison::Less(90).matches(90));
+    }
+
+    #[test]
+    fn test_matches_meta() {
+        let meta = meta(
+            "# Silent Night\nArtist: Franz Xaver Gruber\nKey: Bb\nTempo: 72 bpm\nYear: 1818\nTags: #christmas #classic\n",
+        );
+        let matches = |search: &str| Query::parse(search).matches_meta(&meta);
+
+        assert!(matches("artist:gruber"));
+        assert!(matches("artist:\"franz xav\""));
+        assert!(!matches("artist:hillsong"));
+        assert!(matches("key:Bb"));
+        assert!(matches("key:A#"));
+        assert!(!matches("key:Bbm"));
+        assert!(!matches("key:G"));
+        assert!(matches("tempo:<90"));
+        assert!(!matches("tempo:>=90"));
+        assert!(matches("year:1800-1900"));
+        assert!(matches("#christmas tag:classic"));
+        assert!(!matches("#christmas -#classic"));
+        assert!(matches("-#archived"));
+        assert!(!matches("-artist:gruber"));
+    }
+
+    #[test]
+    fn test_matches_meta_w_h_notation() {
+        let meta = meta("# Stille Nacht\nKey: H\nB-Notation: H\n");
+        let matches = |search: &str| Query::parse(search).matches_meta(&meta);
+
+        assert!(matches("key:H"));
+        // `B` means B flat in the H notation
+        assert!(!matches("key:B"));
+
+        let meta = self::meta("# Silent Night\nKey: B\n");
+        assert!(Query::parse("key:B").matches_meta(&meta));
+        assert!(!Query::parse("key:Bb").matches_meta(&meta));
+    }
+
+    #[test]
+    fn test_matches_meta_w_detected_key() {
+        let meta = meta("# Song\n\n## Verse\n[Am]One [Dm]two [E7]three [Am]four\n");
+        let meta = meta.with_detected_key(
+            crate::key_detector::KeyDetector::new().detect_key(
+                crate::helper::parse_content(
+                    "# Song\n\n## Verse\n[Am]One [Dm]two [E7]three [Am]four\n".as_bytes(),
+                )
+                .unwrap()
+                .node_as_ref(),
+            ),
+        );
+
+        assert!(Query::parse("key:Am").matches_meta(&meta));
+        assert!(!Query::parse("key:C").matches_meta(&meta));
+    }
+}
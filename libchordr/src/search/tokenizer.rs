@@ -0,0 +1,74 @@
+/// Split the text into lowercase search terms with diacritics removed
+///
+/// German umlauts are transcribed the same way as in
+/// [`SectionIdentifier`](crate::models::structure::SectionIdentifier) (`ä` becomes `ae`), other
+/// diacritics are dropped (`é` becomes `e`). Apostrophes are removed so that `don't` becomes a
+/// single term
+pub(crate) fn tokenize(text: &str) -> Vec<String> {
+    let mut terms = vec![];
+    let mut current = String::new();
+    for c in text.chars() {
+        if c == '\'' || c == '’' {
+            continue;
+        }
+        if c.is_alphanumeric() {
+            fold(c, &mut current);
+        } else if !current.is_empty() {
+            terms.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        terms.push(current);
+    }
+
+    terms
+}
+
+/// Append the lowercase ASCII transcription of `c` to `out`
+fn fold(c: char, out: &mut String) {
+    let folded = match c {
+        'Ä' | 'ä' | 'Æ' | 'æ' => "ae",
+        'Ö' | 'ö' | 'Œ' | 'œ' => "oe",
+        'Ü' | 'ü' => "ue",
+        'ß' => "ss",
+        'À' | 'Á' | 'Â' | 'Ã' | 'Å' | 'à' | 'á' | 'â' | 'ã' | 'å' => "a",
+        'Ç' | 'ç' => "c",
+        'È' | 'É' | 'Ê' | 'Ë' | 'è' | 'é' | 'ê' | 'ë' => "e",
+        'Ì' | 'Í' | 'Î' | 'Ï' | 'ì' | 'í' | 'î' | 'ï' => "i",
+        'Ñ' | 'ñ' => "n",
+        'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ø' | 'ò' | 'ó' | 'ô' | 'õ' | 'ø' => "o",
+        'Ù' | 'Ú' | 'Û' | 'ù' | 'ú' | 'û' => "u",
+        'Ý' | 'ý' | 'ÿ' => "y",
+        _ => {
+            out.extend(c.to_lowercase());
+            return;
+        }
+    };
+    out.push_str(folded);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("Swing low, sweet Chariot!"),
+            vec!["swing", "low", "sweet", "chariot"]
+        );
+        assert_eq!(
+            tokenize("Umwandlungs-Funktionen 42"),
+            vec!["umwandlungs", "funktionen", "42"]
+        );
+        assert_eq!(tokenize("Don't stop"), vec!["dont", "stop"]);
+        assert!(tokenize(" -- ").is_empty());
+    }
+
+    #[test]
+    fn test_tokenize_diacritics() {
+        assert_eq!(tokenize("Überschrift"), vec!["ueberschrift"]);
+        assert_eq!(tokenize("Äpfel süß"), vec!["aepfel", "suess"]);
+        assert_eq!(tokenize("Café Señor"), vec!["cafe", "senor"]);
+    }
+}
@@ -0,0 +1,360 @@
+use crate::converter::{Converter, ConverterTrait};
+use crate::data_exchange::setlist::{DeserializeService, SharingSetlist};
+use crate::data_exchange::DxDeserializer;
+use crate::error::{Error, Result};
+use crate::format::Format;
+use crate::helper::parse_content_for_file_type;
+use crate::html::escape::Escape;
+use crate::models::catalog::{Catalog, CatalogTrait};
+use crate::models::chord::fmt::Formatting;
+use crate::models::list::ListEntryTrait;
+use crate::models::setlist::Setlist;
+use crate::models::song_data::SongData;
+use crate::models::song_id::SongId;
+use crate::models::song_settings::SongSettings;
+use crate::modification::transposition::TransposableTrait;
+use crate::parser::ParserResult;
+
+/// Separator between the songs in the `Text` output (form feed)
+const TEXT_PAGE_BREAK: &str = "\u{c}";
+
+/// Reference to a song of a `Songbook` and the settings to render it with
+#[derive(Debug, Clone, PartialEq)]
+pub struct SongbookEntry {
+    pub song_id: SongId,
+    pub settings: Option<SongSettings>,
+}
+
+impl SongbookEntry {
+    pub fn new<S: Into<SongId>>(song_id: S, settings: Option<SongSettings>) -> Self {
+        Self {
+            song_id: song_id.into(),
+            settings,
+        }
+    }
+}
+
+/// Collection of songs that will be rendered into a single document
+///
+/// The document starts with a table of contents, followed by the songs each starting on a new
+/// page. Each song is transposed and formatted according to its `SongSettings`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Songbook {
+    title: String,
+    entries: Vec<SongbookEntry>,
+}
+
+/// Parsed song ready to be converted
+struct PreparedSong {
+    title: String,
+    parser_result: ParserResult,
+    formatting: Formatting,
+}
+
+impl Songbook {
+    pub fn new<S: Into<String>>(title: S, entries: Vec<SongbookEntry>) -> Self {
+        Self {
+            title: title.into(),
+            entries,
+        }
+    }
+
+    /// Build the `Songbook` from a shared setlist query string (see `data_exchange::setlist`)
+    pub fn from_sharing_setlist_query(query: &str) -> Result<Self> {
+        match DeserializeService::deserialize(query) {
+            Ok(sharing_setlist) => Ok(Self::from(&sharing_setlist)),
+            Err(e) => Err(Error::setlist_error(e.to_string())),
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn entries(&self) -> &[SongbookEntry] {
+        &self.entries
+    }
+
+    /// Render the songs from the `Catalog` into one document of the given `Format`
+    ///
+    /// Supported formats are `HTML`, `Text` and `PDF`
+    pub fn render(&self, catalog: &Catalog, format: Format) -> Result<String> {
+        let songs = self.prepare_songs(catalog, format)?;
+
+        match format {
+            Format::HTML => self.render_html(&songs),
+            Format::Text => self.render_text(&songs),
+            #[cfg(feature = "pdf")]
+            Format::PDF => Ok(self.render_pdf(&songs)),
+            _ => Err(Error::setlist_error(format!(
+                "Songbooks can not be rendered as {}",
+                format
+            ))),
+        }
+    }
+
+    fn prepare_songs(&self, catalog: &Catalog, format: Format) -> Result<Vec<PreparedSong>> {
+        self.entries
+            .iter()
+            .map(|entry| {
+                let song = match catalog.get(entry.song_id.clone()) {
+                    Some(s) => s,
+                    None => {
+                        return Err(Error::setlist_error(format!(
+                            "Song '{}' not found in the catalog",
+                            entry.song_id
+                        )))
+                    }
+                };
+                let settings = entry.settings.clone().unwrap_or_default();
+                let ParserResult { node, meta } =
+                    parse_content_for_file_type(song.src().as_bytes(), song.file_type())?;
+                let semitones = settings.transpose_semitone();
+                let parser_result = if semitones != 0 {
+                    ParserResult::new(node.transpose(semitones), meta.transpose(semitones))
+                } else {
+                    ParserResult::new(node, meta)
+                };
+
+                Ok(PreparedSong {
+                    title: song.title(),
+                    parser_result,
+                    formatting: Formatting {
+                        format,
+                        ..settings.formatting()
+                    },
+                })
+            })
+            .collect()
+    }
+
+    fn convert_song(&self, song: &PreparedSong) -> Result<String> {
+        Converter::new().convert(
+            song.parser_result.node_as_ref(),
+            song.parser_result.meta_as_ref(),
+            song.formatting,
+        )
+    }
+
+    fn render_html(&self, songs: &[PreparedSong]) -> Result<String> {
+        let toc_entries = songs
+            .iter()
+            .enumerate()
+            .map(|(i, song)| {
+                format!(
+                    r##"<li><a href="#songbook-song-{}">{}</a></li>"##,
+                    i + 1,
+                    Escape(&song.title)
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        let mut output = format!(
+            "<nav class=\"songbook-toc\">\n<h1>{}</h1>\n<ol>\n{}\n</ol>\n</nav>\n",
+            Escape(&self.title),
+            toc_entries
+        );
+        for (i, song) in songs.iter().enumerate() {
+            output.push_str(&format!(
+                "<section class=\"songbook-song\" id=\"songbook-song-{}\" style=\"break-before: page\">\n{}\n</section>\n",
+                i + 1,
+                self.convert_song(song)?
+            ));
+        }
+
+        Ok(output)
+    }
+
+    fn render_text(&self, songs: &[PreparedSong]) -> Result<String> {
+        let mut pages = vec![];
+        let toc_entries = songs
+            .iter()
+            .enumerate()
+            .map(|(i, song)| format!("{:>3}. {}", i + 1, song.title))
+            .collect::<Vec<String>>()
+            .join("\n");
+        pages.push(format!("{}\n\n{}\n", self.title, toc_entries));
+
+        for song in songs {
+            pages.push(self.convert_song(song)?);
+        }
+
+        Ok(pages.join(TEXT_PAGE_BREAK))
+    }
+
+    #[cfg(feature = "pdf")]
+    fn render_pdf(&self, songs: &[PreparedSong]) -> String {
+        use crate::pdf::pdf_builder::PdfBuilder;
+        use crate::pdf::styles::Styles;
+
+        let build_songs = |builder: &mut PdfBuilder| -> Vec<(String, usize)> {
+            songs
+                .iter()
+                .map(|song| {
+                    let page = builder.add_song(
+                        song.parser_result.node_as_ref(),
+                        song.parser_result.meta_as_ref(),
+                        song.formatting,
+                    );
+                    (song.title.clone(), page)
+                })
+                .collect()
+        };
+
+        // The page numbers for the table of contents are collected in a first pass
+        let toc_entries = build_songs(&mut PdfBuilder::new("", Styles::default()));
+        let mut toc_builder = PdfBuilder::new("", Styles::default());
+        toc_builder.add_table_of_contents(&self.title, &toc_entries);
+        let toc_pages = toc_builder.page_count();
+        let toc_entries = toc_entries
+            .into_iter()
+            .map(|(title, page)| (title, page + toc_pages))
+            .collect::<Vec<(String, usize)>>();
+
+        let mut builder = PdfBuilder::new(self.title.as_str(), Styles::default());
+        builder.add_table_of_contents(&self.title, &toc_entries);
+        build_songs(&mut builder);
+
+        builder.build().render()
+    }
+}
+
+impl From<&Setlist> for Songbook {
+    fn from(setlist: &Setlist) -> Self {
+        Self::new(
+            setlist.name(),
+            setlist
+                .iter()
+                .map(|entry| SongbookEntry::new(entry.id(), entry.settings()))
+                .collect(),
+        )
+    }
+}
+
+impl From<&SharingSetlist> for Songbook {
+    fn from(setlist: &SharingSetlist) -> Self {
+        Self::new(
+            setlist.name.as_str(),
+            setlist
+                .songs
+                .iter()
+                .map(|entry| {
+                    let settings = match (entry.transpose_semitone, &entry.note) {
+                        (None, None) => None,
+                        (transpose_semitone, note) => Some(SongSettings::new(
+                            transpose_semitone.unwrap_or(0),
+                            Formatting::default(),
+                            note.clone().unwrap_or_default(),
+                        )),
+                    };
+                    SongbookEntry::new(entry.id.clone(), settings)
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_helpers::get_test_catalog;
+
+    use super::*;
+
+    fn get_test_songbook() -> Songbook {
+        Songbook::new(
+            "Gig",
+            vec![
+                SongbookEntry::new("song-1", None),
+                SongbookEntry::new(
+                    "song-40",
+                    Some(SongSettings::new(2, Formatting::default(), "")),
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_render_html() {
+        let output = get_test_songbook()
+            .render(&get_test_catalog(), Format::HTML)
+            .unwrap();
+
+        assert!(output.starts_with("<nav class=\"songbook-toc\">\n<h1>Gig</h1>\n<ol>\n<li><a href=\"#songbook-song-1\">Song 1</a></li>\n<li><a href=\"#songbook-song-2\">Song 40</a></li>\n</ol>\n</nav>\n"));
+        assert_eq!(
+            output.matches("<section class=\"songbook-song\"").count(),
+            2
+        );
+        assert!(output.contains("id=\"songbook-song-2\""));
+        // `Song 40` is transposed by two semitones (D -> E)
+        assert!(output.contains(">E</span>"));
+    }
+
+    #[test]
+    fn test_render_text() {
+        let output = get_test_songbook()
+            .render(&get_test_catalog(), Format::Text)
+            .unwrap();
+        let pages = output.split(TEXT_PAGE_BREAK).collect::<Vec<&str>>();
+
+        assert_eq!(pages.len(), 3);
+        assert_eq!(pages[0], "Gig\n\n  1. Song 1\n  2. Song 40\n");
+        assert!(pages[2].starts_with("Swing Low Sweet Chariot\n"));
+    }
+
+    #[cfg(feature = "pdf")]
+    #[test]
+    fn test_render_pdf() {
+        let output = get_test_songbook()
+            .render(&get_test_catalog(), Format::PDF)
+            .unwrap();
+
+        assert!(output.starts_with("%PDF-1.4\n"));
+        assert!(output.contains("/Count 3"));
+        assert!(output.contains(&format!("(  1. Song 1 {} 2) Tj", ".".repeat(68))));
+        assert!(output.contains(&format!("(  2. Song 40 {} 3) Tj", ".".repeat(67))));
+    }
+
+    #[test]
+    fn test_render_unsupported_format() {
+        assert!(get_test_songbook()
+            .render(&get_test_catalog(), Format::SongBeamer)
+            .is_err());
+    }
+
+    #[test]
+    fn test_render_missing_song() {
+        let songbook = Songbook::new("Gig", vec![SongbookEntry::new("not-existing", None)]);
+        let error = songbook
+            .render(&get_test_catalog(), Format::Text)
+            .unwrap_err();
+
+        assert!(error.to_string().contains("not-existing"));
+    }
+
+    #[test]
+    fn test_from_sharing_setlist_query() {
+        let songbook = Songbook::from_sharing_setlist_query(
+            "v=2&name=Gig&id=1&songs[0][id]=song-1&songs[1][id]=song-40&songs[1][tr]=-2\
+            &creation_date=2022-05-20T14%3A04%3A37Z&modification_date=2022-05-20T14%3A04%3A37Z",
+        )
+        .unwrap();
+
+        assert_eq!(songbook.title(), "Gig");
+        assert_eq!(
+            songbook.entries(),
+            &[
+                SongbookEntry::new("song-1", None),
+                SongbookEntry::new(
+                    "song-40",
+                    Some(SongSettings::new(-2, Formatting::default(), ""))
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_sharing_setlist_query_invalid() {
+        assert!(Songbook::from_sharing_setlist_query("v=1&name=Gig").is_err());
+    }
+}
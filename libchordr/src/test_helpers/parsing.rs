@@ -1,7 +1,7 @@
 use crate::models::meta::tags::Tag;
 use crate::models::meta::{BNotation, Tags};
 use crate::parser::{MetaInformation, Node, SectionType};
-use crate::tokenizer::{Meta, Modifier, Token};
+use crate::tokenizer::{Meta, Modifier, Span, Token};
 
 /// Return a small example set of Tokens
 ///
@@ -238,6 +238,7 @@ pub fn get_test_ast() -> Node {
             head: Box::new(Node::quote("Chorus")),
             section_type: SectionType::Reference,
             children: vec![],
+            span: Span::default(),
         },
         Node::newline(),
     ])
@@ -255,6 +256,7 @@ pub fn get_test_ast_with_quote() -> Node {
             head: Box::new(Node::quote("Chorus 2x")),
             section_type: SectionType::Reference,
             children: vec![],
+            span: Span::default(),
         },
         Node::newline(),
         Node::section(
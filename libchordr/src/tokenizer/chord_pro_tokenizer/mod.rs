@@ -0,0 +1,420 @@
+use std::io::BufRead;
+
+use crate::error::Error;
+
+use super::{
+    Meta, Modifier, Position, Span, SpannedToken, Token, Tokenizer, TokenizerError,
+    TokenizerErrorKind,
+};
+
+const COMMENT_START: char = '#';
+const DIRECTIVE_START: char = '{';
+const DIRECTIVE_END: char = '}';
+const CHORD_START: char = '[';
+const CHORD_END: char = ']';
+
+/// Tokenizer for ChordPro files (`.cho`, `.chopro`, `.chordpro`)
+///
+/// The ChordPro directives are mapped onto the `Token`s produced by the chorddown tokenizer, so
+/// that the result can be handled by the same `Parser`
+pub(crate) struct ChordProTokenizer {}
+
+impl ChordProTokenizer {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Tokenizer for ChordProTokenizer {
+    fn tokenize_with_spans<R: BufRead>(
+        &self,
+        mut input: R,
+    ) -> Result<(Vec<SpannedToken>, Vec<TokenizerError>), Error> {
+        let mut state = State::default();
+        let mut line = String::new();
+        while 0 < input.read_line(&mut line)? {
+            log::trace!("Tokenize line `{}`", line.trim_end());
+            state.line_number += 1;
+            // Lines starting with `#` are comments in ChordPro and will be skipped completely
+            if !line.trim_start().starts_with(COMMENT_START) {
+                let content = line.trim_end_matches(&['\r', '\n'][..]);
+                state.tokenize_line(content);
+                if line.ends_with('\n') {
+                    let column = content.chars().count() + 1;
+                    state.push(Token::Newline, state.span(column, column + 1));
+                }
+            }
+
+            line.clear()
+        }
+
+        Ok((state.tokens, state.warnings))
+    }
+}
+
+#[derive(Default)]
+struct State {
+    tokens: Vec<SpannedToken>,
+    warnings: Vec<TokenizerError>,
+
+    /// Signals that a section has been closed via `{end_of_*}` and the following lyrics belong
+    /// to a new (implicit) verse
+    section_closed: bool,
+
+    /// 1-based number of the line that is currently tokenized
+    line_number: usize,
+}
+
+impl State {
+    fn tokenize_line(&mut self, line: &str) {
+        let trimmed = line.trim();
+        if trimmed.starts_with(DIRECTIVE_START) {
+            let start = column_of(line, line.len() - line.trim_start().len());
+            let span = self.span(start, start + trimmed.chars().count());
+            return self.tokenize_directive(trimmed, span);
+        }
+
+        if !trimmed.is_empty() && self.section_closed {
+            self.section_closed = false;
+            // The implicit verse has no source, so it is located at the start of the line
+            let span = self.span(1, 1);
+            self.push(Token::headline(2, "Verse", Modifier::None), span);
+            self.push(Token::Newline, span);
+        }
+
+        self.tokenize_lyrics(line)
+    }
+
+    fn tokenize_directive(&mut self, directive: &str, span: Span) {
+        let inner = match directive.strip_suffix(DIRECTIVE_END) {
+            Some(i) => &i[1..],
+            None => {
+                self.warn(TokenizerErrorKind::UnclosedDirective, span);
+                &directive[1..]
+            }
+        };
+
+        let (name, value) = split_directive(inner);
+        match name.to_lowercase().as_str() {
+            "title" | "t" => self.push(Token::headline(1, value, Modifier::None), span),
+            "subtitle" | "st" => self.push(Token::Meta(Meta::subtitle(value)), span),
+            "start_of_chorus" | "soc" => {
+                self.start_section(value, "Chorus", Modifier::Chorus, span)
+            }
+            "start_of_verse" | "sov" => self.start_section(value, "Verse", Modifier::None, span),
+            "start_of_bridge" | "sob" => {
+                self.start_section(value, "Bridge", Modifier::Bridge, span)
+            }
+            "end_of_chorus" | "eoc" | "end_of_verse" | "eov" | "end_of_bridge" | "eob" => {
+                self.section_closed = true
+            }
+            "comment" | "c" | "comment_italic" | "ci" | "comment_box" | "cb" | "highlight" => {
+                self.push(Token::quote(value), span)
+            }
+            "chorus" => self.push(
+                Token::quote(if value.is_empty() { "Chorus" } else { value }),
+                span,
+            ),
+            "ccli" => self.push(Token::Meta(Meta::ccli_song_id(value)), span),
+            "meta" => {
+                let (meta_name, meta_value) = split_directive(value);
+                self.push_meta(meta_name, meta_value, span)
+            }
+            _ => self.push_meta(name, value, span),
+        }
+    }
+
+    fn start_section(&mut self, label: &str, default_label: &str, modifier: Modifier, span: Span) {
+        self.section_closed = false;
+        let text = if label.is_empty() {
+            default_label
+        } else {
+            label
+        };
+        self.push(Token::headline(2, text, modifier), span)
+    }
+
+    fn push_meta(&mut self, name: &str, value: &str, span: Span) {
+        match Meta::from_keyword_and_content(name, value) {
+            Some(meta) => self.push(Token::Meta(meta), span),
+            None => {
+                log::debug!("Ignore unknown ChordPro directive `{}`", name);
+                self.warn(TokenizerErrorKind::UnknownDirective, span)
+            }
+        }
+    }
+
+    fn tokenize_lyrics(&mut self, line: &str) {
+        // Byte offset of `rest` inside `line`
+        let mut offset = 0;
+        let mut rest = line;
+        while let Some(chord_start) = rest.find(CHORD_START) {
+            let (literal, remainder) = rest.split_at(chord_start);
+            self.push_literal(line, offset, literal);
+            offset += chord_start;
+
+            match remainder.find(CHORD_END) {
+                Some(chord_end) => {
+                    let span = self.span(
+                        column_of(line, offset),
+                        column_of(line, offset + chord_end + 1),
+                    );
+                    self.push(Token::chord(&remainder[1..chord_end]), span);
+                    rest = &remainder[chord_end + 1..];
+                    offset += chord_end + 1;
+                }
+                None => {
+                    let span = self.span(column_of(line, offset), column_of(line, line.len()));
+                    self.warn(TokenizerErrorKind::UnclosedChord, span);
+                    self.push_literal(line, offset + 1, &remainder[1..]);
+                    return;
+                }
+            }
+        }
+        self.push_literal(line, offset, rest);
+    }
+
+    /// Push the `literal` found at the byte `offset` of `line`
+    fn push_literal(&mut self, line: &str, offset: usize, literal: &str) {
+        if !literal.is_empty() {
+            let span = self.span(
+                column_of(line, offset),
+                column_of(line, offset + literal.len()),
+            );
+            self.push(Token::literal(literal), span)
+        }
+    }
+
+    fn push(&mut self, token: Token, span: Span) {
+        self.tokens.push(SpannedToken::new(token, span))
+    }
+
+    fn warn(&mut self, kind: TokenizerErrorKind, span: Span) {
+        self.warnings.push(TokenizerError::new(kind, span))
+    }
+
+    /// Build a Span between the columns of the current line
+    fn span(&self, start_column: usize, end_column: usize) -> Span {
+        Span::new(
+            Position::new(self.line_number, start_column),
+            Position::new(self.line_number, end_column),
+        )
+    }
+}
+
+/// Return the 1-based (character) column of the byte `offset` in `line`
+fn column_of(line: &str, offset: usize) -> usize {
+    line[..offset].chars().count() + 1
+}
+
+/// Split the directive into the name and the (trimmed) value
+///
+/// ChordPro allows both `{name: value}` and `{name value}`
+fn split_directive(inner: &str) -> (&str, &str) {
+    let inner = inner.trim();
+    match inner
+        .char_indices()
+        .find(|(_, c)| *c == ':' || c.is_whitespace())
+    {
+        Some((position, separator)) => (
+            inner[..position].trim(),
+            // The separator may be multibyte whitespace (e.g. a no-break space)
+            inner[position + separator.len_utf8()..].trim(),
+        ),
+        None => (inner, ""),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tokenizer::Modifier;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_tokenize_long() {
+        use Token::Newline;
+        let content = include_str!("../../../tests/resources/swing_low_sweet_chariot.cho");
+        let (tokens, warnings) = ChordProTokenizer::new()
+            .tokenize(content.as_bytes())
+            .unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(
+            tokens[0..26],
+            vec![
+                Newline,
+                Token::headline(1, "Swing Low Sweet Chariot", Modifier::None),
+                Newline,
+                Newline,
+                Token::headline(2, "Chorus", Modifier::Chorus),
+                Newline,
+                Token::literal("Swing "),
+                Token::chord("D"),
+                Token::literal("low, sweet "),
+                Token::chord("G"),
+                Token::literal("chari"),
+                Token::chord("D"),
+                Token::literal("ot,"),
+                Newline,
+                Token::literal("Comin’ for to carry me "),
+                Token::chord("A7"),
+                Token::literal("home."),
+                Newline,
+                Token::literal("Swing "),
+                Token::chord("D7"),
+                Token::literal("low, sweet "),
+                Token::chord("G"),
+                Token::literal("chari"),
+                Token::chord("D"),
+                Token::literal("ot,"),
+                Newline,
+            ]
+        );
+        assert_eq!(
+            tokens[tokens.len() - 4..],
+            vec![Newline, Newline, Token::quote("Chorus"), Newline]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_implicit_verse() {
+        use Token::Newline;
+        let content = r"{soc}
+Swing [D]low
+{eoc}
+
+I [D]looked over Jordan
+";
+        let (tokens, _warnings) = ChordProTokenizer::new()
+            .tokenize(content.as_bytes())
+            .unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::headline(2, "Chorus", Modifier::Chorus),
+                Newline,
+                Token::literal("Swing "),
+                Token::chord("D"),
+                Token::literal("low"),
+                Newline,
+                Newline,
+                Newline,
+                Token::headline(2, "Verse", Modifier::None),
+                Newline,
+                Token::literal("I "),
+                Token::chord("D"),
+                Token::literal("looked over Jordan"),
+                Newline,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_meta() {
+        let content = r"{title: Swing Low}
+{subtitle: Sweet Chariot}
+{artist: The Fantastic Corns}
+{key: Cm}
+{meta: composer Daniel Corn}
+{capo 2}
+# A comment that will be ignored
+{unknown_directive: value}";
+        let (tokens, warnings) = ChordProTokenizer::new()
+            .tokenize(content.as_bytes())
+            .unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::headline(1, "Swing Low", Modifier::None),
+                Token::Newline,
+                Token::Meta(Meta::subtitle("Sweet Chariot")),
+                Token::Newline,
+                Token::Meta(Meta::artist("The Fantastic Corns")),
+                Token::Newline,
+                Token::Meta(Meta::key("Cm")),
+                Token::Newline,
+                Token::Meta(Meta::composer("Daniel Corn")),
+                Token::Newline,
+                Token::Meta(Meta::capo("2")),
+                Token::Newline,
+            ]
+        );
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_tokenize_meta_with_unicode_whitespace() {
+        let content = "{title\u{3000}Swing Low}\n{artist\u{a0}The Fantastic Corns}";
+        let (tokens, warnings) = ChordProTokenizer::new()
+            .tokenize(content.as_bytes())
+            .unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::headline(1, "Swing Low", Modifier::None),
+                Token::Newline,
+                Token::Meta(Meta::artist("The Fantastic Corns")),
+            ]
+        );
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_tokenize_sections() {
+        let content = r"{start_of_verse: Verse 1}
+{sob}
+{start_of_chorus: Refrain}
+{comment: Repeat}
+{chorus}";
+        let (tokens, _warnings) = ChordProTokenizer::new()
+            .tokenize(content.as_bytes())
+            .unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::headline(2, "Verse 1", Modifier::None),
+                Token::Newline,
+                Token::headline(2, "Bridge", Modifier::Bridge),
+                Token::Newline,
+                Token::headline(2, "Refrain", Modifier::Chorus),
+                Token::Newline,
+                Token::quote("Repeat"),
+                Token::Newline,
+                Token::quote("Chorus"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_unclosed_chord() {
+        let (tokens, warnings) = ChordProTokenizer::new()
+            .tokenize("Swing [D low".as_bytes())
+            .unwrap();
+        assert_eq!(
+            tokens,
+            vec![Token::literal("Swing "), Token::literal("D low")]
+        );
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind(), TokenizerErrorKind::UnclosedChord);
+    }
+
+    #[test]
+    fn test_tokenize_with_spans() {
+        let span =
+            |line, start, end| Span::new(Position::new(line, start), Position::new(line, end));
+        let (tokens, _warnings) = ChordProTokenizer::new()
+            .tokenize_with_spans("{title: Swing}\nSwäng [D]low".as_bytes())
+            .unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                SpannedToken::new(Token::headline(1, "Swing", Modifier::None), span(1, 1, 15)),
+                SpannedToken::new(Token::Newline, span(1, 15, 16)),
+                SpannedToken::new(Token::literal("Swäng "), span(2, 1, 7)),
+                SpannedToken::new(Token::chord("D"), span(2, 7, 10)),
+                SpannedToken::new(Token::literal("low"), span(2, 10, 13)),
+            ]
+        );
+    }
+}
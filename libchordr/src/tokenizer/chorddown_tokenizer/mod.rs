@@ -2,8 +2,7 @@ use std::io::BufRead;
 
 use crate::error::Error;
 
-use super::tokenizer_error::TokenizerError;
-use super::{Token, Tokenizer};
+use super::{SpannedToken, Tokenizer, TokenizerError};
 
 use self::scanner::Scanner;
 use self::state_machine::Fsm;
@@ -23,20 +22,22 @@ impl ChorddownTokenizer {
 }
 
 impl Tokenizer for ChorddownTokenizer {
-    fn tokenize<R: BufRead>(&self, input: R) -> Result<(Vec<Token>, Vec<TokenizerError>), Error> {
-        let lexemes_vec = Scanner::new().scan(input)?;
-        let lexemes = lexemes_vec.iter().peekable();
-        let mut tokens: Vec<Token> = vec![];
+    fn tokenize_with_spans<R: BufRead>(
+        &self,
+        input: R,
+    ) -> Result<(Vec<SpannedToken>, Vec<TokenizerError>), Error> {
+        let lexemes = Scanner::new().scan(input)?;
+        let mut tokens: Vec<SpannedToken> = vec![];
         let mut fsm = Fsm::new();
 
-        for lexeme in lexemes {
-            if let Some(changed_state) = fsm.characterize_lexeme(lexeme) {
-                let token = fsm.build_token();
+        for (lexeme, span) in &lexemes {
+            if let Some(changed_state) = fsm.characterize_lexeme(lexeme, *span) {
+                let token = fsm.build_token(lexeme, *span);
 
                 if let Some(token) = token {
                     tokens.push(token);
                 }
-                fsm.set_state(changed_state);
+                fsm.set_state(changed_state, lexeme, *span);
             }
         }
 
@@ -49,7 +50,7 @@ mod tests {
     use crate::models::meta::tags::Tag;
     use crate::models::meta::{BNotation, Tags};
     use crate::test_helpers::get_test_tokens;
-    use crate::tokenizer::{Meta, Modifier};
+    use crate::tokenizer::{Meta, Modifier, Position, Span, Token, TokenizerErrorKind};
 
     use super::*;
 
@@ -100,6 +101,31 @@ Swing [D]low, sweet [G]chari[D]ot
         );
     }
 
+    #[test]
+    fn test_tokenize_with_spans() {
+        let span =
+            |line, start, end| Span::new(Position::new(line, start), Position::new(line, end));
+        let content = "# Title\nSwing [D]low\n[Em";
+        let (tokens, warnings) = ChorddownTokenizer::new()
+            .tokenize_with_spans(content.as_bytes())
+            .unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                SpannedToken::new(Token::headline(1, "Title", Modifier::None), span(1, 1, 8)),
+                SpannedToken::new(Token::Newline, span(1, 8, 9)),
+                SpannedToken::new(Token::literal("Swing "), span(2, 1, 7)),
+                SpannedToken::new(Token::chord("D"), span(2, 7, 10)),
+                SpannedToken::new(Token::literal("low"), span(2, 10, 13)),
+                SpannedToken::new(Token::Newline, span(2, 13, 14)),
+                SpannedToken::new(Token::chord("Em"), span(3, 1, 4)),
+            ]
+        );
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind(), TokenizerErrorKind::UnexpectedEndOfFile);
+        assert_eq!(warnings[0].span(), span(3, 4, 4));
+    }
+
     #[test]
     fn test_tokenize_crlf() {
         use Token::Newline;
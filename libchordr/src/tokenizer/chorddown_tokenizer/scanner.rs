@@ -2,6 +2,7 @@ use std::io::BufRead;
 
 use crate::error::Error;
 use crate::tokenizer::chorddown_tokenizer::lexeme::Lexeme;
+use crate::tokenizer::{Position, Span};
 
 use super::keywords::{
     BRIDGE_MARK, CHORD_END, CHORD_START, CHORUS_MARK, COLON, HEADER_START, NEWLINE, QUOTE_START,
@@ -11,17 +12,24 @@ const CARRIAGE_RETURN: char = '\r';
 const LITERAL_BUFFER_CAPACITY: usize = 20;
 
 pub struct Scanner {
-    lexemes: Vec<Lexeme>,
+    lexemes: Vec<(Lexeme, Span)>,
+    /// Position of the current character
+    position: Position,
+    /// Position of the first character in the literal buffer
+    literal_start: Position,
 }
 
 impl Scanner {
     pub fn new() -> Self {
         Self {
             lexemes: Vec::with_capacity(400),
+            position: Position::new(1, 1),
+            literal_start: Position::new(1, 1),
         }
     }
 
-    pub fn scan<R: BufRead>(mut self, mut input: R) -> Result<Vec<Lexeme>, Error> {
+    /// Split the input into lexemes and return them with their location in the input
+    pub fn scan<R: BufRead>(mut self, mut input: R) -> Result<Vec<(Lexeme, Span)>, Error> {
         let mut literal_buffer = String::with_capacity(LITERAL_BUFFER_CAPACITY);
 
         let mut line = String::new();
@@ -40,8 +48,19 @@ impl Scanner {
                     COLON => self.build_n_push(&mut literal_buffer, Lexeme::Colon),
                     CHORUS_MARK => self.build_n_push(&mut literal_buffer, Lexeme::ChorusMark),
                     BRIDGE_MARK => self.build_n_push(&mut literal_buffer, Lexeme::BridgeMark),
-                    _ => literal_buffer.push(current_character),
+                    _ => {
+                        if literal_buffer.is_empty() {
+                            self.literal_start = self.position;
+                        }
+                        literal_buffer.push(current_character)
+                    }
                 }
+
+                self.position = if current_character == NEWLINE {
+                    Position::new(self.position.line + 1, 1)
+                } else {
+                    self.position.next_column()
+                };
             }
 
             line.clear()
@@ -57,7 +76,12 @@ impl Scanner {
     fn build_n_push(&mut self, literal_buffer: &mut String, lexeme: Lexeme) {
         self.build_n_drain(literal_buffer);
 
-        self.lexemes.push(lexeme)
+        let span = if lexeme == Lexeme::Eof {
+            Span::new(self.position, self.position)
+        } else {
+            Span::new(self.position, self.position.next_column())
+        };
+        self.lexemes.push((lexeme, span))
     }
 
     /// Build a `Lexeme::Literal` from the current content of `literal_buffer`, push it to the
@@ -65,10 +89,14 @@ impl Scanner {
     #[inline(always)]
     fn build_n_drain(&mut self, literal_buffer: &mut String) {
         if !literal_buffer.is_empty() {
-            self.lexemes.push(Lexeme::Literal(std::mem::replace(
-                literal_buffer,
-                String::with_capacity(LITERAL_BUFFER_CAPACITY),
-            )));
+            let span = Span::new(self.literal_start, self.position);
+            self.lexemes.push((
+                Lexeme::Literal(std::mem::replace(
+                    literal_buffer,
+                    String::with_capacity(LITERAL_BUFFER_CAPACITY),
+                )),
+                span,
+            ));
         }
     }
 }
@@ -102,6 +130,15 @@ mod tests {
     use super::shortcuts::*;
     use super::*;
 
+    fn scan(content: &str) -> Vec<Lexeme> {
+        Scanner::new()
+            .scan(content.as_bytes())
+            .unwrap()
+            .into_iter()
+            .map(|(lexeme, _)| lexeme)
+            .collect()
+    }
+
     #[test]
     fn scan_test() {
         let content = r"
@@ -112,8 +149,7 @@ Composer: Daniel Corn
 ##! Chorus
 Swing [D]low, sweet [G]chari[D]ot,
 ";
-        let scanner = Scanner::new();
-        let lexemes = scanner.scan(content.as_bytes()).unwrap();
+        let lexemes = scan(content);
         assert_eq!(lexemes.len(), 30);
 
         assert_eq!(
@@ -153,14 +189,32 @@ Swing [D]low, sweet [G]chari[D]ot,
         );
     }
 
+    #[test]
+    fn scan_positions_test() {
+        let span =
+            |line, start, end| Span::new(Position::new(line, start), Position::new(line, end));
+        let lexemes = Scanner::new().scan("Lä [D]\nx".as_bytes()).unwrap();
+        assert_eq!(
+            lexemes,
+            vec![
+                (lit("Lä "), span(1, 1, 4)),
+                (CS, span(1, 4, 5)),
+                (lit("D"), span(1, 5, 6)),
+                (CE, span(1, 6, 7)),
+                (NL, span(1, 7, 8)),
+                (lit("x"), span(2, 1, 2)),
+                (EOF, span(2, 2, 2)),
+            ]
+        );
+    }
+
     #[test]
     fn scan_crlf_test() {
         let content = "\r
 # Swing Low Sweet Chariot\r
 
 ";
-        let scanner = Scanner::new();
-        let lexemes = scanner.scan(content.as_bytes()).unwrap();
+        let lexemes = scan(content);
         assert_eq!(lexemes.len(), 6);
 
         assert_eq!(
@@ -171,16 +225,16 @@ Swing [D]low, sweet [G]chari[D]ot,
 
     #[test]
     fn scan_test_complex_chords() {
-        let lexemes = Scanner::new().scan("[Dm7]".as_bytes()).unwrap();
+        let lexemes = scan("[Dm7]");
         assert_eq!(lexemes, vec![CS, lit("Dm7"), CE, EOF]);
 
-        let lexemes = Scanner::new().scan("[Dm7/B]".as_bytes()).unwrap();
+        let lexemes = scan("[Dm7/B]");
         assert_eq!(lexemes, vec![CS, lit("Dm7/B"), CE, EOF]);
 
-        let lexemes = Scanner::new().scan("[D#m7]".as_bytes()).unwrap();
+        let lexemes = scan("[D#m7]");
         assert_eq!(lexemes, vec![CS, lit("D"), H, lit("m7"), CE, EOF]);
 
-        let lexemes = Scanner::new().scan("[B/F#maj7]".as_bytes()).unwrap();
+        let lexemes = scan("[B/F#maj7]");
         assert_eq!(lexemes, vec![CS, lit("B/F"), H, lit("maj7"), CE, EOF]);
     }
 
@@ -189,8 +243,7 @@ Swing [D]low, sweet [G]chari[D]ot,
         let content = r"
 ##- Pre-chorus
 ";
-        let scanner = Scanner::new();
-        let lexemes = scanner.scan(content.as_bytes()).unwrap();
+        let lexemes = scan(content);
         assert_eq!(lexemes.len(), 9);
 
         assert_eq!(
@@ -202,8 +255,7 @@ Swing [D]low, sweet [G]chari[D]ot,
     #[test]
     fn scan_bride_with_exclamation_marks_test() {
         let content = r"##- Bride Loud!!";
-        let scanner = Scanner::new();
-        let lexemes = scanner.scan(content.as_bytes()).unwrap();
+        let lexemes = scan(content);
         // assert_eq!(lexemes.len(), 7);
 
         assert_eq!(lexemes, vec![H, H, B, lit(" Bride Loud"), CM, CM, EOF,]);
@@ -212,8 +264,7 @@ Swing [D]low, sweet [G]chari[D]ot,
     #[test]
     fn scan_chorus_with_exclamation_marks_test() {
         let content = r"##! Chorus Loud!!";
-        let scanner = Scanner::new();
-        let lexemes = scanner.scan(content.as_bytes()).unwrap();
+        let lexemes = scan(content);
         assert_eq!(lexemes.len(), 7);
 
         assert_eq!(lexemes, vec![H, H, CM, lit(" Chorus Loud"), CM, CM, EOF,]);
@@ -226,8 +277,7 @@ Swing [D]low, sweet [G]chari[D]ot,
 
 Tags: #low #sweet
 ";
-        let scanner = Scanner::new();
-        let lexemes = scanner.scan(content.as_bytes()).unwrap();
+        let lexemes = scan(content);
         assert_eq!(lexemes.len(), 14);
 
         assert_eq!(
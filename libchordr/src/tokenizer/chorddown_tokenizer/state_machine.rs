@@ -1,7 +1,8 @@
 use std::convert::TryFrom;
 
-use crate::tokenizer::tokenizer_error::TokenizerError;
-use crate::tokenizer::{Meta, Modifier, Token};
+use crate::tokenizer::{
+    Meta, Modifier, Position, Span, SpannedToken, Token, TokenizerError, TokenizerErrorKind,
+};
 
 use super::lexeme::Lexeme;
 use super::mode::Mode;
@@ -11,6 +12,8 @@ pub(crate) struct Fsm {
     literal_buffer: String,
     header_level: u8,
     header_modifier: Option<Modifier>,
+    /// Position where the Token of the current [Mode] started
+    token_start: Position,
     pub warnings: Vec<TokenizerError>,
 }
 
@@ -21,6 +24,7 @@ impl Fsm {
             literal_buffer: String::new(),
             header_level: 0,
             header_modifier: None,
+            token_start: Position::new(1, 1),
             warnings: vec![],
         }
     }
@@ -28,7 +32,7 @@ impl Fsm {
     /// Return the [Mode] that is signaled by `lexeme` if it did change
     ///
     /// `None` is returned if there is no change to the current [Mode] (=`self.state`)
-    pub fn characterize_lexeme(&mut self, lexeme: &Lexeme) -> Option<Mode> {
+    pub fn characterize_lexeme(&mut self, lexeme: &Lexeme, span: Span) -> Option<Mode> {
         match self.state {
             Mode::Bof | Mode::Newline => match lexeme {
                 Lexeme::HeaderStart => {
@@ -38,7 +42,7 @@ impl Fsm {
                 Lexeme::Newline => Some(Mode::Newline),
                 Lexeme::ChordStart => Some(Mode::Chord),
                 Lexeme::ChordEnd => {
-                    self.warnings.push(TokenizerError::UnexpectedChordEnd);
+                    self.warn(TokenizerErrorKind::UnexpectedChordEnd, span);
 
                     Some(Mode::Literal)
                 }
@@ -59,13 +63,16 @@ impl Fsm {
                     }
                     Lexeme::Newline => {
                         // Unclosed chord
-                        self.warnings.push(TokenizerError::UnclosedChord);
+                        self.warn(
+                            TokenizerErrorKind::UnclosedChord,
+                            Span::new(self.token_start, span.start),
+                        );
                         Some(Mode::Newline)
                     }
                     Lexeme::ChordStart => {
                         // Nested chord
                         self.append_lexeme(lexeme);
-                        self.warnings.push(TokenizerError::NestedChord);
+                        self.warn(TokenizerErrorKind::NestedChord, span);
                         None
                     }
                     Lexeme::ChordEnd => Some(Mode::Literal),
@@ -74,7 +81,7 @@ impl Fsm {
                     | Lexeme::ChorusMark
                     | Lexeme::BridgeMark => {
                         self.append_lexeme(lexeme);
-                        self.warnings.push(TokenizerError::InvalidChordCharacter);
+                        self.warn(TokenizerErrorKind::InvalidChordCharacter, span);
                         None
                     }
                     Lexeme::Literal(_) => {
@@ -82,7 +89,7 @@ impl Fsm {
                         None
                     }
                     Lexeme::Eof => {
-                        self.warnings.push(TokenizerError::UnexpectedEndOfFile);
+                        self.warn(TokenizerErrorKind::UnexpectedEndOfFile, span);
                         Fsm::build_eof()
                     }
                 }
@@ -150,7 +157,7 @@ impl Fsm {
                 match lexeme {
                     Lexeme::Newline => Some(Mode::Newline),
                     Lexeme::HeaderStart => {
-                        self.warnings.push(TokenizerError::UnexpectedHeaderStart);
+                        self.warn(TokenizerErrorKind::UnexpectedHeaderStart, span);
                         self.append_lexeme(lexeme);
                         None
                     }
@@ -158,7 +165,7 @@ impl Fsm {
                     Lexeme::ChordStart => Some(Mode::Chord),
                     Lexeme::ChordEnd => {
                         // Chord End without an opening bracket
-                        self.warnings.push(TokenizerError::UnexpectedChordEnd);
+                        self.warn(TokenizerErrorKind::UnexpectedChordEnd, span);
 
                         None
                     }
@@ -171,7 +178,7 @@ impl Fsm {
                         None
                     }
                     Lexeme::Eof => {
-                        self.warnings.push(TokenizerError::UnexpectedEndOfFile);
+                        self.warn(TokenizerErrorKind::UnexpectedEndOfFile, span);
                         Fsm::build_eof()
                     }
                 }
@@ -184,7 +191,21 @@ impl Fsm {
         Some(Mode::Eof)
     }
 
-    pub fn build_token(&mut self) -> Option<Token> {
+    /// Build the Token for the current [Mode], which ends at the given `lexeme`
+    pub fn build_token(&mut self, lexeme: &Lexeme, span: Span) -> Option<SpannedToken> {
+        let end = match (self.state, lexeme) {
+            // The closing bracket belongs to the chord
+            (Mode::Chord, Lexeme::ChordEnd) => span.end,
+            (Mode::Newline, _) => self.token_start.next_column(),
+            _ => span.start,
+        };
+        let span = Span::new(self.token_start, end);
+
+        self.build_plain_token()
+            .map(|token| SpannedToken::new(token, span))
+    }
+
+    fn build_plain_token(&mut self) -> Option<Token> {
         match self.state {
             Mode::Header => self.build_token_from_header(),
             Mode::Chord => Some(Token::chord(self.consume_buffer())),
@@ -196,10 +217,21 @@ impl Fsm {
         }
     }
 
-    pub fn set_state(&mut self, state: Mode) {
+    /// Switch to the given [Mode], whose Token starts at the given `lexeme`
+    pub fn set_state(&mut self, state: Mode, lexeme: &Lexeme, span: Span) {
+        // Text following a chord starts after the closing bracket
+        self.token_start = if *lexeme == Lexeme::ChordEnd {
+            span.end
+        } else {
+            span.start
+        };
         self.state = state
     }
 
+    fn warn(&mut self, kind: TokenizerErrorKind, span: Span) {
+        self.warnings.push(TokenizerError::new(kind, span))
+    }
+
     fn build_token_from_header(&mut self) -> Option<Token> {
         let token = Token::headline(
             self.header_level,
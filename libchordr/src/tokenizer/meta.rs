@@ -28,8 +28,35 @@ pub enum Meta {
     Tags(Tags),
 }
 
+/// Keywords of all meta information (as returned by [`Meta::keyword()`])
+const KEYWORDS: [&str; 18] = [
+    "Subtitle",
+    "Original Title",
+    "Alternative Title",
+    "Artist",
+    "Composer",
+    "Lyricist",
+    "Copyright",
+    "Album",
+    "Year",
+    "Key",
+    "Original Key",
+    "Time",
+    "Tempo",
+    "Duration",
+    "Capo",
+    "CCLI Song #",
+    "B-Notation",
+    "Tags",
+];
+
 impl Meta {
-    fn from_keyword_and_content(word: &str, content: &str) -> Option<Self> {
+    /// Return the keywords of all meta information that is recognized by the tokenizer
+    pub fn keywords() -> &'static [&'static str] {
+        &KEYWORDS
+    }
+
+    pub(crate) fn from_keyword_and_content(word: &str, content: &str) -> Option<Self> {
         let content = content.trim();
         match word.trim().to_lowercase().as_str() {
             "artist" => Some(Self::artist(content)),
@@ -205,3 +232,16 @@ impl TryFrom<&String> for Meta {
         TryFrom::try_from(value.as_str())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keywords() {
+        for keyword in Meta::keywords() {
+            let meta = Meta::from_keyword_and_content(keyword, "H").unwrap();
+            assert_eq!(meta.keyword(), *keyword);
+        }
+    }
+}
@@ -2,24 +2,43 @@ use std::io::BufRead;
 
 use crate::error::Error;
 
+use self::chord_pro_tokenizer::ChordProTokenizer;
 use self::chorddown_tokenizer::ChorddownTokenizer;
 pub use self::meta::Meta;
 pub use self::modifier::Modifier;
-pub use self::token::Token;
-use self::tokenizer_error::TokenizerError;
+pub use self::span::{Position, Span};
+pub use self::token::{SpannedToken, Token};
+pub use self::tokenizer_error::{TokenizerError, TokenizerErrorKind};
 
+mod chord_pro_tokenizer;
 mod chorddown_tokenizer;
 mod meta;
 mod modifier;
+mod span;
 mod token;
 mod tokenizer_error;
 
 pub trait Tokenizer {
     /// Tokenize the given input
-    fn tokenize<R: BufRead>(&self, input: R) -> Result<(Vec<Token>, Vec<TokenizerError>), Error>;
+    fn tokenize<R: BufRead>(&self, input: R) -> Result<(Vec<Token>, Vec<TokenizerError>), Error> {
+        let (tokens, warnings) = self.tokenize_with_spans(input)?;
+
+        Ok((tokens.into_iter().map(|t| t.token).collect(), warnings))
+    }
+
+    /// Tokenize the given input and keep the location of each Token in the source
+    fn tokenize_with_spans<R: BufRead>(
+        &self,
+        input: R,
+    ) -> Result<(Vec<SpannedToken>, Vec<TokenizerError>), Error>;
 }
 
 /// Build a new Tokenizer instance
 pub fn build_tokenizer() -> impl Tokenizer {
     ChorddownTokenizer::new()
 }
+
+/// Build a new Tokenizer instance for ChordPro files
+pub fn build_chord_pro_tokenizer() -> impl Tokenizer {
+    ChordProTokenizer::new()
+}
@@ -0,0 +1,79 @@
+use std::fmt::{Display, Formatter};
+
+/// Location of a character in the source
+///
+/// Lines and columns are 1-based and columns count characters (not bytes). The default
+/// `Position` (line 0) marks an unknown location
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    pub fn new(line: usize, column: usize) -> Self {
+        Self { line, column }
+    }
+
+    /// Return the Position of the next character in the same line
+    pub(crate) fn next_column(self) -> Self {
+        Self {
+            line: self.line,
+            column: self.column + 1,
+        }
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// Range of the source from `start` up to (but not including) `end`
+///
+/// Tokens and nodes built without source information have the default (unknown) `Span`
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    pub fn new(start: Position, end: Position) -> Self {
+        Self { start, end }
+    }
+
+    /// Return if the location in the source is not known
+    pub fn is_unknown(&self) -> bool {
+        self.start.line == 0
+    }
+
+    /// Return if the Position lies inside the Span
+    pub fn contains(&self, position: Position) -> bool {
+        self.start <= position && position < self.end
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.start.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contains() {
+        let span = Span::new(Position::new(2, 5), Position::new(2, 9));
+
+        assert!(span.contains(Position::new(2, 5)));
+        assert!(span.contains(Position::new(2, 8)));
+        assert!(!span.contains(Position::new(2, 9)));
+        assert!(!span.contains(Position::new(1, 6)));
+        assert!(!span.is_unknown());
+        assert!(Span::default().is_unknown());
+    }
+}
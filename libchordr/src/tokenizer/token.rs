@@ -1,4 +1,5 @@
 use super::meta::Meta;
+use super::span::Span;
 use crate::tokenizer::modifier::Modifier;
 
 #[derive(Debug, PartialOrd, PartialEq, Clone)]
@@ -42,3 +43,35 @@ impl Token {
         Token::Quote(value.into())
     }
 }
+
+/// Token together with its location in the source
+#[derive(Debug, PartialEq, Clone)]
+pub struct SpannedToken {
+    pub token: Token,
+    pub span: Span,
+}
+
+impl SpannedToken {
+    pub fn new(token: Token, span: Span) -> Self {
+        Self { token, span }
+    }
+}
+
+impl AsRef<Token> for Token {
+    fn as_ref(&self) -> &Token {
+        self
+    }
+}
+
+impl AsRef<Token> for SpannedToken {
+    fn as_ref(&self) -> &Token {
+        &self.token
+    }
+}
+
+impl From<Token> for SpannedToken {
+    /// Wrap a Token without source information
+    fn from(token: Token) -> Self {
+        Self::new(token, Span::default())
+    }
+}
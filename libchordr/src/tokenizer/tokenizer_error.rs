@@ -1,27 +1,60 @@
 use std::error::Error;
 use std::fmt::{Display, Formatter, Result};
 
-#[derive(Debug)]
-pub enum TokenizerError {
+use super::Span;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TokenizerErrorKind {
     UnclosedChord,
     NestedChord,
     InvalidChordCharacter,
     UnexpectedChordEnd,
     UnexpectedHeaderStart,
     UnexpectedEndOfFile,
+    UnclosedDirective,
+    UnknownDirective,
 }
 
-impl Display for TokenizerError {
+impl Display for TokenizerErrorKind {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         match self {
-            TokenizerError::UnclosedChord => f.write_str("UnclosedChord"),
-            TokenizerError::NestedChord => f.write_str("NestedChord"),
-            TokenizerError::InvalidChordCharacter => f.write_str("InvalidChordCharacter"),
-            TokenizerError::UnexpectedChordEnd => f.write_str("UnexpectedChordEnd"),
-            TokenizerError::UnexpectedHeaderStart => f.write_str("UnexpectedHeaderStart"),
-            TokenizerError::UnexpectedEndOfFile => f.write_str("UnexpectedEndOfFile"),
+            TokenizerErrorKind::UnclosedChord => f.write_str("UnclosedChord"),
+            TokenizerErrorKind::NestedChord => f.write_str("NestedChord"),
+            TokenizerErrorKind::InvalidChordCharacter => f.write_str("InvalidChordCharacter"),
+            TokenizerErrorKind::UnexpectedChordEnd => f.write_str("UnexpectedChordEnd"),
+            TokenizerErrorKind::UnexpectedHeaderStart => f.write_str("UnexpectedHeaderStart"),
+            TokenizerErrorKind::UnexpectedEndOfFile => f.write_str("UnexpectedEndOfFile"),
+            TokenizerErrorKind::UnclosedDirective => f.write_str("UnclosedDirective"),
+            TokenizerErrorKind::UnknownDirective => f.write_str("UnknownDirective"),
         }
     }
 }
 
+/// Problem the tokenizer recovered from, with its location in the input
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenizerError {
+    kind: TokenizerErrorKind,
+    span: Span,
+}
+
+impl TokenizerError {
+    pub fn new(kind: TokenizerErrorKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+
+    pub fn kind(&self) -> TokenizerErrorKind {
+        self.kind
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Display for TokenizerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{} ({})", self.kind, self.span)
+    }
+}
+
 impl Error for TokenizerError {}
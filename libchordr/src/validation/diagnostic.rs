@@ -0,0 +1,135 @@
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+#[derive(Serialize, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Warning => f.write_str("warning"),
+            Severity::Error => f.write_str("error"),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiagnosticKind {
+    /// The file could not be read
+    Io,
+    /// The tokenizer recovered from malformed input (e.g. an unclosed chord)
+    Tokenizer,
+    /// The tokenizer failed, so the file could not be checked
+    TokenizerFailure,
+    /// A chord could not be parsed
+    InvalidChord,
+    /// A line in the head of the song looks like meta information, but the keyword is unknown
+    UnknownMetaKeyword,
+    /// A reference (`> Chorus`) does not point to a section of the song
+    UnresolvedReference,
+    /// A section with the same name has already been defined
+    DuplicateSection,
+    /// The song has no title (`# Title`)
+    MissingTitle,
+    /// The `Key` meta information is not a valid chord
+    InvalidKey,
+}
+
+impl DiagnosticKind {
+    pub fn severity(&self) -> Severity {
+        match self {
+            DiagnosticKind::Io
+            | DiagnosticKind::TokenizerFailure
+            | DiagnosticKind::InvalidChord
+            | DiagnosticKind::UnresolvedReference
+            | DiagnosticKind::MissingTitle
+            | DiagnosticKind::InvalidKey => Severity::Error,
+            DiagnosticKind::Tokenizer
+            | DiagnosticKind::UnknownMetaKeyword
+            | DiagnosticKind::DuplicateSection => Severity::Warning,
+        }
+    }
+}
+
+/// Problem found in a song file
+///
+/// `line` and `column` are 1-based and count characters
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<PathBuf>,
+    line: usize,
+    column: usize,
+    severity: Severity,
+    kind: DiagnosticKind,
+    message: String,
+}
+
+impl Diagnostic {
+    pub fn new<S: Into<String>>(
+        kind: DiagnosticKind,
+        line: usize,
+        column: usize,
+        message: S,
+    ) -> Self {
+        Self {
+            path: None,
+            line,
+            column,
+            severity: kind.severity(),
+            kind,
+            message: message.into(),
+        }
+    }
+
+    pub fn with_path<P: Into<PathBuf>>(self, path: P) -> Self {
+        Self {
+            path: Some(path.into()),
+            ..self
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    pub fn kind(&self) -> DiagnosticKind {
+        self.kind
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(path) = &self.path {
+            write!(f, "{}:", path.to_string_lossy())?;
+        }
+        write!(
+            f,
+            "{}:{}: {}: {}",
+            self.line, self.column, self.severity, self.message
+        )
+    }
+}
@@ -0,0 +1,491 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::catalog_builder::CatalogBuilder;
+use crate::error::{Error, Result};
+use crate::models::chord::{Chord, Chords};
+use crate::models::file_type::FileType;
+use crate::models::meta::BNotation;
+use crate::models::structure::{ReferenceResolver, Section, SectionIdentifier, SectionType};
+use crate::tokenizer::{
+    build_tokenizer, Meta, SpannedToken, Token, Tokenizer, TokenizerError, TokenizerErrorKind,
+};
+
+pub use self::diagnostic::{Diagnostic, DiagnosticKind, Severity};
+
+mod diagnostic;
+
+/// Check Chorddown files for problems that the parser silently ignores or that make it fail
+///
+/// In contrast to the parser the Validator does not stop at the first error, but reports all
+/// problems it finds
+#[derive(Default)]
+pub struct Validator {}
+
+/// Line of the source with the tokens and warnings of the tokenizer
+struct Line<'a> {
+    number: usize,
+    text: &'a str,
+    tokens: Vec<SpannedToken>,
+    warnings: Vec<TokenizerError>,
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Validate all Chorddown files in the directory
+    pub fn validate_directory<P: AsRef<Path>>(
+        &self,
+        path: P,
+        recursive: bool,
+    ) -> Result<Vec<Diagnostic>> {
+        let path = path.as_ref();
+        if !path.is_dir() {
+            return Err(Error::io_error(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is not a directory", path.to_string_lossy()),
+            )));
+        }
+
+        let mut files = vec![];
+        let mut diagnostics = vec![];
+        for result in
+            CatalogBuilder::new().collect_song_files(path, &[FileType::Chorddown], recursive)
+        {
+            match result {
+                Ok(file) => files.push(file),
+                Err(error) => diagnostics.push(
+                    Diagnostic::new(DiagnosticKind::Io, 1, 1, error.to_string())
+                        .with_path(error.path()),
+                ),
+            }
+        }
+        files.sort();
+        for file in files {
+            diagnostics.append(&mut self.validate_file(file));
+        }
+
+        Ok(diagnostics)
+    }
+
+    /// Validate the Chorddown file
+    pub fn validate_file<P: AsRef<Path>>(&self, path: P) -> Vec<Diagnostic> {
+        let path = path.as_ref();
+        match fs::read_to_string(path) {
+            Ok(source) => self
+                .validate_source(&source)
+                .into_iter()
+                .map(|diagnostic| diagnostic.with_path(path))
+                .collect(),
+            Err(e) => vec![Diagnostic::new(
+                DiagnosticKind::Io,
+                1,
+                1,
+                format!("Could not read file: {}", e),
+            )
+            .with_path(path)],
+        }
+    }
+
+    /// Validate the Chorddown source
+    pub fn validate_source(&self, source: &str) -> Vec<Diagnostic> {
+        let lines = match tokenize_lines(source) {
+            Ok(lines) => lines,
+            Err(error) => return vec![tokenizer_failure(&error)],
+        };
+        let b_notation = lines
+            .iter()
+            .flat_map(|line| line.tokens.iter())
+            .find_map(|token| match &token.token {
+                Token::Meta(Meta::BNotation(b_notation)) => Some(*b_notation),
+                _ => None,
+            })
+            .unwrap_or_default();
+
+        let mut diagnostics = vec![];
+        let mut in_head = true;
+        for line in &lines {
+            if line.tokens.iter().any(|t| is_section_start(&t.token)) {
+                in_head = false;
+            }
+            check_tokenizer_warnings(line, &mut diagnostics);
+            check_chords(line, b_notation, &mut diagnostics);
+            check_meta(line, b_notation, in_head, &mut diagnostics);
+        }
+        check_title(&lines, &mut diagnostics);
+        check_sections(&lines, &mut diagnostics);
+
+        diagnostics.sort_by_key(|d| (d.line(), d.column()));
+        diagnostics
+    }
+}
+
+/// Tokenize the source and group the tokens and warnings by the line they start in
+fn tokenize_lines(source: &str) -> Result<Vec<Line<'_>>> {
+    let mut lines: Vec<Line<'_>> = source
+        .lines()
+        .enumerate()
+        .map(|(index, text)| Line {
+            number: index + 1,
+            text,
+            tokens: vec![],
+            warnings: vec![],
+        })
+        .collect();
+
+    // Terminate the last line, so that a missing final newline is not reported as an
+    // unexpected end of file
+    let source = if source.ends_with('\n') {
+        source.to_owned()
+    } else {
+        format!("{}\n", source)
+    };
+    let (tokens, warnings) = build_tokenizer().tokenize_with_spans(source.as_bytes())?;
+
+    for token in tokens {
+        if let Some(line) = lines.get_mut(token.span.start.line.wrapping_sub(1)) {
+            line.tokens.push(token)
+        }
+    }
+    for warning in warnings {
+        if let Some(line) = lines.get_mut(warning.span().start.line.wrapping_sub(1)) {
+            line.warnings.push(warning)
+        }
+    }
+
+    Ok(lines)
+}
+
+/// Report the error that stopped the tokenizer at its location (or the start of the file)
+fn tokenizer_failure(error: &Error) -> Diagnostic {
+    let (line, column) = match error.span() {
+        Some(span) if !span.is_unknown() => (span.start.line, span.start.column),
+        _ => (1, 1),
+    };
+
+    Diagnostic::new(
+        DiagnosticKind::TokenizerFailure,
+        line,
+        column,
+        format!("Could not tokenize the file: {}", error),
+    )
+}
+
+fn is_section_start(token: &Token) -> bool {
+    matches!(token, Token::Headline { level, .. } if *level > 1) || matches!(token, Token::Quote(_))
+}
+
+/// Return the 1-based column of the character at the byte `offset`
+fn column_at(text: &str, offset: usize) -> usize {
+    text[..offset].chars().count() + 1
+}
+
+fn is_unclosed_chord(line: &Line<'_>, token: &SpannedToken) -> bool {
+    line.warnings.iter().any(|warning| {
+        warning.kind() == TokenizerErrorKind::UnclosedChord
+            && warning.span().start == token.span.start
+    })
+}
+
+fn check_tokenizer_warnings(line: &Line<'_>, diagnostics: &mut Vec<Diagnostic>) {
+    // `#` is valid in meta information (e.g. `Tags: #oldie` or `CCLI Song #: 123`)
+    let is_meta = line
+        .tokens
+        .iter()
+        .any(|t| matches!(t.token, Token::Meta(_)));
+    for warning in &line.warnings {
+        let message = match warning.kind() {
+            TokenizerErrorKind::UnexpectedHeaderStart if is_meta => continue,
+            // Characters inside chords are validated when the chord is parsed (e.g. `-` separates
+            // alternative chords)
+            TokenizerErrorKind::InvalidChordCharacter => continue,
+            TokenizerErrorKind::UnclosedChord => "Chord is not closed",
+            TokenizerErrorKind::NestedChord => "Chords must not be nested",
+            TokenizerErrorKind::UnexpectedChordEnd => "Closing bracket without an opening bracket",
+            TokenizerErrorKind::UnexpectedHeaderStart => "Unexpected '#' inside the text",
+            TokenizerErrorKind::UnexpectedEndOfFile => "Unexpected end of file",
+            TokenizerErrorKind::UnclosedDirective => "Directive is not closed",
+            TokenizerErrorKind::UnknownDirective => "Unknown directive",
+        };
+
+        diagnostics.push(Diagnostic::new(
+            DiagnosticKind::Tokenizer,
+            line.number,
+            warning.span().start.column,
+            message,
+        ));
+    }
+}
+
+fn check_chords(line: &Line<'_>, b_notation: BNotation, diagnostics: &mut Vec<Diagnostic>) {
+    for token in &line.tokens {
+        if let Token::Chord(raw) = &token.token {
+            // Unclosed chords are already reported as tokenizer warnings
+            if is_unclosed_chord(line, token) {
+                continue;
+            }
+            if let Err(error) = Chords::try_from(raw, b_notation) {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticKind::InvalidChord,
+                    line.number,
+                    // Point to the chord instead of the bracket
+                    token.span.start.column + 1,
+                    format!("Invalid chord '{}': {}", raw, error),
+                ));
+            }
+        }
+    }
+}
+
+fn check_meta(
+    line: &Line<'_>,
+    b_notation: BNotation,
+    in_head: bool,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let content_column = || match line.text.find(':') {
+        Some(offset) => {
+            let content = &line.text[offset + 1..];
+            column_at(
+                line.text,
+                offset + 1 + content.len() - content.trim_start().len(),
+            )
+        }
+        None => 1,
+    };
+
+    for token in &line.tokens {
+        match &token.token {
+            Token::Meta(meta @ (Meta::Key(key) | Meta::OriginalKey(key)))
+                if Chord::try_from(key, b_notation).is_err() =>
+            {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticKind::InvalidKey,
+                    line.number,
+                    content_column(),
+                    format!("{} '{}' is not a valid chord", meta.keyword(), key),
+                ));
+            }
+            Token::Literal(text) if in_head && line.tokens.len() <= 2 => {
+                if let Some(keyword) = unknown_meta_keyword(text) {
+                    diagnostics.push(Diagnostic::new(
+                        DiagnosticKind::UnknownMetaKeyword,
+                        line.number,
+                        token.span.start.column,
+                        format!("Unknown meta keyword '{}'", keyword),
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Return the keyword if the text looks like meta information (`Keyword: Content`)
+///
+/// Known keywords are turned into `Token::Meta` by the tokenizer, so the keyword is unknown
+fn unknown_meta_keyword(text: &str) -> Option<&str> {
+    let (keyword, content) = text.split_once(':')?;
+    let keyword = keyword.trim();
+    let content = content.trim();
+    let is_keyword = keyword.starts_with(char::is_alphabetic)
+        && keyword.split_whitespace().count() <= 3
+        && keyword
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_' | '#'));
+
+    // Ignore URLs (`https://...`)
+    if is_keyword && !content.is_empty() && !content.starts_with("//") {
+        Some(keyword)
+    } else {
+        None
+    }
+}
+
+fn check_title(lines: &[Line<'_>], diagnostics: &mut Vec<Diagnostic>) {
+    let has_title = lines
+        .iter()
+        .flat_map(|line| line.tokens.iter())
+        .any(|token| matches!(token.token, Token::Headline { level: 1, .. }));
+
+    if !has_title {
+        diagnostics.push(Diagnostic::new(
+            DiagnosticKind::MissingTitle,
+            1,
+            1,
+            "The song has no title (`# Title`)",
+        ));
+    }
+}
+
+fn check_sections(lines: &[Line<'_>], diagnostics: &mut Vec<Diagnostic>) {
+    let mut sections: Vec<Section> = vec![];
+    for spanned_token in lines.iter().flat_map(|line| line.tokens.iter()) {
+        let token = &spanned_token.token;
+        let (title, section_type, is_reference) = match token {
+            Token::Headline {
+                level,
+                text,
+                modifier,
+            } if *level > 1 => (text, SectionType::from(modifier), false),
+            Token::Quote(text) => (text, SectionType::Reference, true),
+            Token::Newline => continue,
+            _ => {
+                if let Some(section) = sections.last_mut() {
+                    section.has_content = true;
+                }
+                continue;
+            }
+        };
+        if let Ok(identifier) = SectionIdentifier::try_from(token) {
+            sections.push(Section {
+                section_type,
+                title: title.trim().to_owned(),
+                identifier,
+                has_content: false,
+                is_reference,
+                span: spanned_token.span,
+            });
+        }
+    }
+
+    let resolver = ReferenceResolver::new();
+    for (index, section) in sections.iter().enumerate() {
+        let line_number = section.span.start.line;
+        if section.is_reference {
+            if resolver.resolve_reference(section, &sections).is_none() {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticKind::UnresolvedReference,
+                    line_number,
+                    1,
+                    format!("Reference '{}' does not match any section", section.title),
+                ));
+            }
+        } else if section.has_content {
+            let previous = sections[..index].iter().find(|other| {
+                !other.is_reference && other.has_content && other.identifier == section.identifier
+            });
+            if let Some(previous) = previous {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticKind::DuplicateSection,
+                    line_number,
+                    1,
+                    format!(
+                        "Section '{}' is already defined on line {}",
+                        section.title, previous.span.start.line
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tokenizer::{Position, Span};
+
+    use super::*;
+
+    fn kinds(source: &str) -> Vec<(DiagnosticKind, usize, usize)> {
+        Validator::new()
+            .validate_source(source)
+            .iter()
+            .map(|d| (d.kind(), d.line(), d.column()))
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_valid_song() {
+        let source = include_str!("../../tests/resources/swing_low_sweet_chariot.chorddown");
+
+        assert_eq!(Validator::new().validate_source(source), vec![]);
+    }
+
+    #[test]
+    fn test_tokenizer_failure() {
+        let span = Span::new(Position::new(3, 5), Position::new(3, 6));
+        let diagnostic = tokenizer_failure(&Error::unknown_error("Broken").with_span(span));
+
+        assert_eq!(diagnostic.kind(), DiagnosticKind::TokenizerFailure);
+        assert_eq!(diagnostic.severity(), Severity::Error);
+        assert_eq!((diagnostic.line(), diagnostic.column()), (3, 5));
+
+        let diagnostic = tokenizer_failure(&Error::unknown_error("Broken"));
+        assert_eq!((diagnostic.line(), diagnostic.column()), (1, 1));
+    }
+
+    #[test]
+    fn test_validate_chords() {
+        assert_eq!(
+            kinds("# Song\n\n## Verse\nLa [G]la [Xyz]la [Q]\n"),
+            vec![
+                (DiagnosticKind::InvalidChord, 4, 11),
+                (DiagnosticKind::InvalidChord, 4, 19)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_tokenizer_warnings() {
+        assert_eq!(
+            kinds("# Song\n\n## Verse\nLa [G la\nLa] la\n"),
+            vec![
+                (DiagnosticKind::Tokenizer, 4, 4),
+                (DiagnosticKind::Tokenizer, 5, 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_meta() {
+        assert_eq!(
+            kinds("# Song\nArtist: Me\nKey: X\nArrangement: Band\n\n## Verse\nNote: me\n"),
+            vec![
+                (DiagnosticKind::InvalidKey, 3, 6),
+                (DiagnosticKind::UnknownMetaKeyword, 4, 1)
+            ]
+        );
+        assert_eq!(
+            kinds("# Song\nB-Notation: H\nKey: H\nhttps://example.com\n"),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_validate_missing_title() {
+        assert_eq!(
+            kinds("## Verse\nLa\n"),
+            vec![(DiagnosticKind::MissingTitle, 1, 1)]
+        );
+    }
+
+    #[test]
+    fn test_validate_sections() {
+        let source =
+            "# Song\n\n##! Chorus\nLa\n\n## Verse\nLa\n\n## Verse\nLa\n\n> Chorus\n> Bridge\n";
+        assert_eq!(
+            kinds(source),
+            vec![
+                (DiagnosticKind::DuplicateSection, 9, 1),
+                (DiagnosticKind::UnresolvedReference, 13, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diagnostic_display() {
+        let diagnostic = Validator::new()
+            .validate_source("## Verse\n")
+            .remove(0)
+            .with_path("song.chorddown");
+
+        assert_eq!(diagnostic.severity(), Severity::Error);
+        assert_eq!(
+            diagnostic.to_string(),
+            "song.chorddown:1:1: error: The song has no title (`# Title`)"
+        );
+    }
+}
@@ -14,15 +14,11 @@ use std::path::Path;
 use diesel::SqliteConnection;
 use rocket::fairing::AdHoc;
 use rocket::fs::{FileServer, NamedFile};
-use rocket::response::status;
-use rocket::serde::json::Json;
-use rocket::{http, Build, Rocket, State};
+use rocket::{Build, Rocket, State};
 use rocket_sync_db_pools::database;
 
-use libchordr::models::catalog::Catalog;
-use libchordr::prelude::{CatalogBuilder, FileType};
-
 use crate::config::Config;
+use crate::routes::catalog::CatalogCacheState;
 
 mod authentication;
 mod config;
@@ -60,29 +56,6 @@ async fn html_fallback(config: &State<Config>) -> io::Result<NamedFile> {
     NamedFile::open(Path::new(&config.static_files_dir).join("index.html")).await
 }
 
-#[get("/catalog.json")]
-fn catalog(config: &State<Config>) -> Result<Json<Catalog>, status::Custom<String>> {
-    match CatalogBuilder::new().build_catalog_for_directory(
-        &config.song_dir,
-        FileType::Chorddown,
-        true,
-    ) {
-        Err(e) => Err(status::Custom(
-            http::Status::InternalServerError,
-            e.to_string(),
-        )),
-        Ok(catalog_result) => {
-            if !catalog_result.errors.is_empty() {
-                for error in catalog_result.errors {
-                    log::error!("{}", error);
-                }
-            }
-
-            Ok(Json(catalog_result.catalog))
-        }
-    }
-}
-
 async fn run_db_migrations(rocket: Rocket<Build>) -> Rocket<Build> {
     let conn = DbConn::get_one(&rocket)
         .await
@@ -98,6 +71,7 @@ fn rocket_build() -> Rocket<Build> {
     rocket::build()
         .attach(cors::Cors::new(vec!["http://localhost:8080"]))
         .attach(DbConn::fairing())
+        .manage(CatalogCacheState::default())
         .attach(AdHoc::on_ignite("Database Migrations", run_db_migrations))
         .attach(AdHoc::on_ignite(
             "Build application configuration",
@@ -110,7 +84,8 @@ fn rocket_build() -> Rocket<Build> {
             let config = build_application_config(&rocket);
             rocket.mount("/", FileServer::from(config.static_files_dir).rank(1))
         }))
-        .mount("/", routes![index, catalog])
+        .mount("/", routes![index])
+        .mount("/", routes::catalog::get_routes())
         .mount("/api/status", routes::status::get_routes())
         .mount("/api/setlist", routes::setlist::get_routes())
         .mount("/api/user", routes::user::get_routes())
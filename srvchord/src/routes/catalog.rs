@@ -0,0 +1,222 @@
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::sync::Mutex;
+
+use rocket::http::{Header, Status};
+use rocket::request::{FromRequest, Outcome};
+use rocket::response::status;
+use rocket::serde::json::Json;
+use rocket::{get, Request, State};
+
+use libchordr::models::catalog::Catalog;
+use libchordr::prelude::{CatalogBuilder, CatalogCache, CatalogDiff, CatalogTrait, FileType};
+
+use crate::config::Config;
+
+pub fn get_routes() -> Vec<rocket::Route> {
+    routes![crate::routes::catalog::catalog]
+}
+
+/// Number of previous Catalog revisions kept to send clients only the changes
+const CATALOG_HISTORY_SIZE: usize = 5;
+
+/// Cache of the song files shared between the requests, so only changed files are parsed
+#[derive(Default)]
+pub struct CatalogCacheState(Mutex<CatalogState>);
+
+#[derive(Default)]
+struct CatalogState {
+    cache: CatalogCache,
+    /// Recent Catalog revisions (the newest is at the back)
+    history: VecDeque<Catalog>,
+}
+
+impl CatalogState {
+    fn push_history(&mut self, catalog: &Catalog) {
+        if self
+            .history
+            .back()
+            .is_some_and(|c| c.revision() == catalog.revision())
+        {
+            return;
+        }
+
+        self.history.push_back(catalog.clone());
+        while self.history.len() > CATALOG_HISTORY_SIZE {
+            self.history.pop_front();
+        }
+    }
+
+    fn find_revision(&self, revision: &str) -> Option<&Catalog> {
+        self.history.iter().find(|c| c.revision() == revision)
+    }
+}
+
+/// Entity tags sent by the client in the `If-None-Match` header
+pub struct IfNoneMatch(Vec<String>);
+
+impl IfNoneMatch {
+    /// Return if the client already has the version with the given entity tag
+    ///
+    /// Weak and strong tags are compared the same way, as required for `If-None-Match`
+    fn matches(&self, etag: &str) -> bool {
+        self.0
+            .iter()
+            .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for IfNoneMatch {
+    type Error = Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(IfNoneMatch(
+            request
+                .headers()
+                .get("If-None-Match")
+                .flat_map(|value| value.split(','))
+                .map(|tag| tag.trim().to_owned())
+                .filter(|tag| !tag.is_empty())
+                .collect(),
+        ))
+    }
+}
+
+#[derive(Responder)]
+pub enum CatalogResponse {
+    #[response(status = 200)]
+    Modified(Json<Catalog>, Header<'static>, Header<'static>),
+    #[response(status = 200)]
+    Delta(Json<CatalogDiff>, Header<'static>, Header<'static>),
+    #[response(status = 304)]
+    NotModified((), Header<'static>),
+}
+
+/// Return the Catalog
+///
+/// If `since` is the revision of a recently served Catalog, only the changes since that revision
+/// are returned as a `CatalogDiff`. Only the last `CATALOG_HISTORY_SIZE` revisions are kept in
+/// memory (and none after a restart), so for an older or unknown revision the full Catalog is
+/// returned instead
+#[get("/catalog.json?<since>")]
+pub fn catalog(
+    config: &State<Config>,
+    state: &State<CatalogCacheState>,
+    if_none_match: IfNoneMatch,
+    since: Option<String>,
+) -> Result<CatalogResponse, status::Custom<String>> {
+    // If a previous build panicked the cache may be incomplete, which only causes files to be
+    // parsed again
+    let mut state = match state.0.lock() {
+        Ok(state) => state,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    match CatalogBuilder::new().build_catalog_for_directory_incremental(
+        &config.song_dir,
+        &[FileType::Chorddown, FileType::ChordPro],
+        true,
+        &mut state.cache,
+    ) {
+        Err(e) => Err(status::Custom(Status::InternalServerError, e.to_string())),
+        Ok(catalog_result) => {
+            if !catalog_result.errors.is_empty() {
+                for error in catalog_result.errors {
+                    log::error!("{}", error);
+                }
+            }
+            let changes = catalog_result.changes;
+            if !changes.is_empty() {
+                log::info!(
+                    "Catalog changed: {} added, {} changed, {} removed",
+                    changes.added.len(),
+                    changes.changed.len(),
+                    changes.removed.len()
+                );
+            }
+
+            let catalog = catalog_result.catalog;
+            state.push_history(&catalog);
+
+            let etag = format!("\"{}\"", catalog.revision());
+            if if_none_match.matches(&etag) {
+                return Ok(CatalogResponse::NotModified((), Header::new("ETag", etag)));
+            }
+
+            // Let clients revalidate the Catalog on every request
+            let cache_control = Header::new("Cache-Control", "no-cache");
+            match since
+                .as_deref()
+                .and_then(|since| state.find_revision(since))
+            {
+                Some(base) => Ok(CatalogResponse::Delta(
+                    Json(CatalogDiff::compute(base, &catalog)),
+                    Header::new("ETag", etag),
+                    cache_control,
+                )),
+                None => Ok(CatalogResponse::Modified(
+                    Json(catalog),
+                    Header::new("ETag", etag),
+                    cache_control,
+                )),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_helpers::run_test_fn;
+    use rocket::http::{Header, Status};
+
+    #[test]
+    fn test_catalog() {
+        run_test_fn(|client, _conn| {
+            let response = client.get("/catalog.json").dispatch();
+            assert_eq!(response.status(), Status::Ok);
+            assert_eq!(
+                response.headers().get_one("Cache-Control"),
+                Some("no-cache")
+            );
+
+            let etag = response.headers().get_one("ETag").unwrap().to_owned();
+            assert!(etag.starts_with('"') && etag.ends_with('"'));
+            assert!(response.into_string().unwrap().contains("\"songs\""));
+
+            let response = client
+                .get("/catalog.json")
+                .header(Header::new("If-None-Match", etag.clone()))
+                .dispatch();
+            assert_eq!(response.status(), Status::NotModified);
+            assert_eq!(response.headers().get_one("ETag"), Some(etag.as_str()));
+
+            let response = client
+                .get("/catalog.json")
+                .header(Header::new("If-None-Match", "\"outdated-revision\""))
+                .dispatch();
+            assert_eq!(response.status(), Status::Ok);
+        })
+    }
+
+    #[test]
+    fn test_catalog_since() {
+        run_test_fn(|client, _conn| {
+            let response = client.get("/catalog.json").dispatch();
+            let etag = response.headers().get_one("ETag").unwrap().to_owned();
+            let revision = etag.trim_matches('"');
+
+            let response = client
+                .get(format!("/catalog.json?since={}", revision))
+                .dispatch();
+            assert_eq!(response.status(), Status::Ok);
+            let body = response.into_string().unwrap();
+            assert!(body.contains("\"base_revision\""));
+            assert!(!body.contains("\"songs\""));
+
+            // Unknown revisions fall back to the full Catalog
+            let response = client.get("/catalog.json?since=unknown").dispatch();
+            assert_eq!(response.status(), Status::Ok);
+            assert!(response.into_string().unwrap().contains("\"songs\""));
+        })
+    }
+}
@@ -1,3 +1,4 @@
+pub mod catalog;
 pub mod setlist;
 pub mod status;
 pub mod user;
@@ -6,12 +6,15 @@ use clap::{App, Arg, ArgMatches, SubCommand};
 use simplelog::{ColorChoice, Config, TerminalMode};
 
 use crate::error::{Error, Result};
+use crate::file_filter::FileFilter;
 use crate::prelude::*;
 
 mod error;
+mod file_filter;
 mod helper;
 mod prelude;
 mod service;
+mod sync_state;
 
 fn main() {
     let output_arg = Arg::with_name("OUTPUT")
@@ -19,7 +22,7 @@ fn main() {
         .help("Output directory path");
     let service_arg = Arg::with_name("SERVICE")
         .required(true)
-        .help("Online service to use (dropbox, WebDAV)");
+        .help("Service to use (dropbox, WebDAV, local, git)");
     let api_token_arg = Arg::with_name("API_TOKEN")
         .long("api-key")
         .takes_value(true)
@@ -37,11 +40,30 @@ fn main() {
     let url_arg = Arg::with_name("URL")
         .long("url")
         .takes_value(true)
-        .help("WebDAV entry point URL");
+        .help("WebDAV entry point URL or Git repository URL");
     let remote_directory_arg = Arg::with_name("REMOTE_DIRECTORY")
         .long("remote-directory")
         .takes_value(true)
-        .help("Remote directory to list");
+        .help("Remote directory to list (source directory for the local service)");
+    let dry_run_arg = Arg::with_name("DRY_RUN")
+        .long("dry-run")
+        .help("Only report the changes without touching any file");
+    let max_delete_arg = Arg::with_name("MAX_DELETE")
+        .long("max-delete")
+        .takes_value(true)
+        .help("Maximum percentage of the synchronized files that may be deleted (default: 50)");
+    let include_arg = Arg::with_name("INCLUDE")
+        .long("include")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+        .help("Only synchronize files matching the glob pattern (e.g. '*.chorddown')");
+    let exclude_arg = Arg::with_name("EXCLUDE")
+        .long("exclude")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+        .help("Do not synchronize files matching the glob pattern");
     let args = App::new(env!("CARGO_PKG_NAME"))
         .version(env!("CARGO_PKG_VERSION"))
         .author("Daniel Corn <info@cundd.net>")
@@ -56,7 +78,27 @@ fn main() {
                 .arg(username_arg.clone())
                 .arg(password_arg.clone())
                 .arg(url_arg.clone())
-                .arg(remote_directory_arg.clone()),
+                .arg(remote_directory_arg.clone())
+                .arg(dry_run_arg.clone())
+                .arg(max_delete_arg.clone())
+                .arg(include_arg.clone())
+                .arg(exclude_arg.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("sync")
+                .version(env!("CARGO_PKG_VERSION"))
+                .about("Download remote changes and upload local changes")
+                .arg(service_arg.clone())
+                .arg(output_arg.clone())
+                .arg(api_token_arg.clone())
+                .arg(username_arg.clone())
+                .arg(password_arg.clone())
+                .arg(url_arg.clone())
+                .arg(remote_directory_arg.clone())
+                .arg(dry_run_arg.clone())
+                .arg(max_delete_arg.clone())
+                .arg(include_arg.clone())
+                .arg(exclude_arg.clone()),
         )
         .get_matches();
 
@@ -65,6 +107,8 @@ fn main() {
     }
     let error = if let Some(matches) = args.subcommand_matches("download") {
         download(matches)
+    } else if let Some(matches) = args.subcommand_matches("sync") {
+        sync(matches)
     } else {
         eprintln!("Missing argument 'subcommand'");
         Ok(())
@@ -79,10 +123,86 @@ fn download(args: &ArgMatches<'_>) -> Result<()> {
     let service_config = build_service_config(args)?;
     let service = Services::new(service_config.clone())?;
 
-    helper::download(&service, &service_config)?;
+    let options = build_sync_options(args)?;
+    let summary = helper::download(&service, &service_config, &options)?;
+    print_summary(&summary, &options);
+    Ok(())
+}
+
+fn sync(args: &ArgMatches<'_>) -> Result<()> {
+    let service_config = build_service_config(args)?;
+    let service = Services::new(service_config.clone())?;
+
+    let options = build_sync_options(args)?;
+    let summary = helper::sync(&service, &service_config, &options)?;
+    print_summary(&summary, &options);
     Ok(())
 }
 
+fn print_summary(summary: &helper::SyncSummary, options: &helper::SyncOptions) {
+    if options.dry_run {
+        println!("Dry run, no file has been changed");
+        let groups = [
+            ("Download", &summary.downloaded),
+            ("Upload", &summary.uploaded),
+            ("Delete remote", &summary.deleted_remote),
+            ("Delete local", &summary.deleted_local),
+            ("Rename local", &summary.renamed),
+            ("Conflict", &summary.conflicts),
+        ];
+        for (label, names) in groups {
+            for name in names {
+                println!("  {}: {}", label, name);
+            }
+        }
+        return;
+    }
+
+    println!(
+        "Downloaded {}, uploaded {}, deleted {} remote and {} local, renamed {}, skipped {}, failed {} file(s)",
+        summary.downloaded.len(),
+        summary.uploaded.len(),
+        summary.deleted_remote.len(),
+        summary.deleted_local.len(),
+        summary.renamed.len(),
+        summary.skipped.len(),
+        summary.failed.len()
+    );
+    if !summary.conflicts.is_empty() {
+        println!("Conflicts (remote version saved as copy):");
+        for conflict in &summary.conflicts {
+            println!("  {}", conflict);
+        }
+    }
+}
+
+fn build_sync_options(args: &ArgMatches<'_>) -> Result<helper::SyncOptions> {
+    let default = helper::SyncOptions::default();
+    let max_delete_percent = match args.value_of("MAX_DELETE") {
+        Some(val) => match val.parse::<u8>() {
+            Ok(percent) if percent <= 100 => percent,
+            _ => {
+                return Err(Error::invalid_argument_error(format!(
+                    "Invalid percentage '{}' for --max-delete",
+                    val
+                )))
+            }
+        },
+        None => default.max_delete_percent,
+    };
+
+    let filter = FileFilter::new(
+        args.values_of("INCLUDE").into_iter().flatten(),
+        args.values_of("EXCLUDE").into_iter().flatten(),
+    )?;
+
+    Ok(helper::SyncOptions {
+        dry_run: args.is_present("DRY_RUN"),
+        max_delete_percent,
+        filter,
+    })
+}
+
 fn get_api_key(args: &ArgMatches<'_>) -> Result<String> {
     if let Some(t) = args.value_of("API_TOKEN") {
         return Ok(t.to_owned());
@@ -1,7 +1,9 @@
 use std::error::Error as StdError;
 use std::fmt::{Display, Error as FmtError, Formatter};
 
-use dropbox_sdk::files::{DownloadError, ListFolderError};
+use dropbox_sdk::files::{
+    DeleteError, DownloadError, ListFolderContinueError, ListFolderError, UploadError,
+};
 use reqwest::Error as RequestError;
 use xml::reader::Error as XmlError;
 
@@ -20,8 +22,20 @@ impl Error {
         Error::new(Kind::DownloadError(description.into()))
     }
 
-    pub fn skip_download<S: Into<String>>(description: S) -> Self {
-        Error::new(Kind::SkipDownload(description.into()))
+    pub fn upload_error<S: Into<String>>(description: S) -> Self {
+        Error::new(Kind::UploadError(description.into()))
+    }
+
+    pub fn delete_error<S: Into<String>>(description: S) -> Self {
+        Error::new(Kind::DeleteError(description.into()))
+    }
+
+    pub fn sync_state_error<S: Into<String>>(description: S) -> Self {
+        Error::new(Kind::SyncStateError(description.into()))
+    }
+
+    pub fn sync_aborted<S: Into<String>>(description: S) -> Self {
+        Error::new(Kind::SyncAborted(description.into()))
     }
 
     pub fn missing_argument_error<S: Into<String>>(description: S) -> Self {
@@ -79,12 +93,30 @@ impl From<ListFolderError> for Error {
     }
 }
 
+impl From<ListFolderContinueError> for Error {
+    fn from(error: ListFolderContinueError) -> Self {
+        Error::download_error(format!("{}", error))
+    }
+}
+
 impl From<DownloadError> for Error {
     fn from(error: DownloadError) -> Self {
         Error::download_error(format!("{}", error))
     }
 }
 
+impl From<UploadError> for Error {
+    fn from(error: UploadError) -> Self {
+        Error::upload_error(format!("{}", error))
+    }
+}
+
+impl From<DeleteError> for Error {
+    fn from(error: DeleteError) -> Self {
+        Error::delete_error(format!("{}", error))
+    }
+}
+
 impl From<RequestError> for Error {
     fn from(error: RequestError) -> Self {
         Error::download_error(format!("{}", error))
@@ -121,8 +153,17 @@ enum Kind {
     /// Error trying to download files or file information
     DownloadError(String),
 
-    /// "Error" kind signaling why a download was skipped
-    SkipDownload(String),
+    /// Error trying to upload a file
+    UploadError(String),
+
+    /// Error trying to delete a remote file
+    DeleteError(String),
+
+    /// Error reading or writing the persisted synchronization state
+    SyncStateError(String),
+
+    /// Error if a synchronization was aborted to protect the files
+    SyncAborted(String),
 
     /// Error if an unknown service was requested
     UnknownServiceError(String),
@@ -152,7 +193,10 @@ impl Display for Kind {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
         match self {
             Kind::DownloadError(s) => write!(f, "Download error: {}", s),
-            Kind::SkipDownload(s) => write!(f, "{}", s),
+            Kind::UploadError(s) => write!(f, "Upload error: {}", s),
+            Kind::DeleteError(s) => write!(f, "Delete error: {}", s),
+            Kind::SyncStateError(s) => write!(f, "Sync state error: {}", s),
+            Kind::SyncAborted(s) => write!(f, "Sync aborted: {}", s),
             Kind::UnknownServiceError(s) => write!(f, "Unknown service error: {}", s),
             Kind::MissingArgumentError(s) => write!(f, "Missing argument error: {}", s),
             Kind::InvalidArgumentError(s) => write!(f, "Invalid argument error: {}", s),
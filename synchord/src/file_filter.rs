@@ -0,0 +1,84 @@
+use glob::{MatchOptions, Pattern};
+
+use crate::error::{Error, Result};
+
+/// Filter selecting the files to synchronize by glob patterns
+///
+/// The patterns are matched against the path relative to the remote directory (e.g.
+/// `de/swing-low.chorddown`). `*` also matches the path separator, so `*.chorddown` selects
+/// the songs in all sub directories
+#[derive(Debug, Clone, Default)]
+pub struct FileFilter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl FileFilter {
+    /// Build a filter from the include and exclude patterns
+    ///
+    /// If no include pattern is given all files not excluded are selected
+    pub fn new<I, E>(include: I, exclude: E) -> Result<Self>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+        E: IntoIterator,
+        E::Item: AsRef<str>,
+    {
+        Ok(Self {
+            include: build_patterns(include)?,
+            exclude: build_patterns(exclude)?,
+        })
+    }
+
+    /// Return if the file at `relative_path` should be synchronized
+    pub fn matches(&self, relative_path: &str) -> bool {
+        let options = MatchOptions::new();
+        let is_included = self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|p| p.matches_with(relative_path, options));
+
+        is_included
+            && !self
+                .exclude
+                .iter()
+                .any(|p| p.matches_with(relative_path, options))
+    }
+}
+
+fn build_patterns<I>(patterns: I) -> Result<Vec<Pattern>>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    patterns
+        .into_iter()
+        .map(|pattern| {
+            let pattern = pattern.as_ref();
+            Pattern::new(pattern).map_err(|e| {
+                Error::invalid_argument_error(format!("Invalid pattern '{}': {}", pattern, e))
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        let filter = FileFilter::default();
+        assert!(filter.matches("swing-low.chorddown"));
+        assert!(filter.matches("README.md"));
+
+        let filter = FileFilter::new(["*.chorddown"], ["drafts/*"]).unwrap();
+        assert!(filter.matches("swing-low.chorddown"));
+        assert!(filter.matches("de/stille-nacht.chorddown"));
+        assert!(!filter.matches("README.md"));
+        assert!(!filter.matches("drafts/new-song.chorddown"));
+
+        assert!(FileFilter::new(["[.chorddown"], Vec::<String>::new()).is_err());
+    }
+}
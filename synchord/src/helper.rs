@@ -1,72 +1,604 @@
 use crate::error::{Error, Result};
+use crate::file_filter::FileFilter;
 use crate::service::*;
-use chrono::{DateTime, Utc};
+use crate::sync_state::{SyncState, SyncStateEntry};
 use log::{debug, error, info, warn};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
 use std::path::{Path, PathBuf};
 
-pub fn download(
-    service: &Services,
-    service_config: &AbstractServiceConfig,
-) -> Result<Vec<FileEntry>> {
-    let files = service.list_files()?;
-    if files.is_empty() {
-        info!("No files found");
+/// Marker inserted into the file name of conflict copies (`song.conflict.chorddown`)
+const CONFLICT_MARKER: &str = "conflict";
+
+/// Direction in which files are synchronized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncMode {
+    /// Download remote changes, local changes are kept but not uploaded
+    Download,
+    /// Download remote changes and upload local changes
+    TwoWay,
+}
+
+/// Action required to bring a file in sync
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyncAction {
+    /// The remote file is new or has been changed
+    Download,
+    /// The local file is new or has been changed
+    Upload,
+    /// The local file has been deleted
+    DeleteRemote,
+    /// The remote file has been deleted
+    DeleteLocal,
+    /// The remote file has been renamed from the given name
+    Rename(String),
+    /// The file exists on both sides but has not been synchronized before, the contents have to
+    /// be compared
+    Compare,
+    /// The file has been changed on both sides
+    Conflict,
+    /// The file has been deleted on both sides
+    Forget,
+    /// The file did not change
+    None,
+    /// The file can not be synchronized automatically
+    Skip(String),
+}
+
+/// Local file as seen by [`sync`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalFile {
+    /// SHA-256 hash of the file's content
+    pub hash: String,
+}
+
+/// Options for [`download`] and [`sync`]
+#[derive(Debug, Clone)]
+pub struct SyncOptions {
+    /// Only report the changes, without touching any file
+    pub dry_run: bool,
+
+    /// Maximum percentage of the synchronized files that may be deleted in one run
+    ///
+    /// Protects the library from being wiped if the service returns an incomplete listing
+    pub max_delete_percent: u8,
+
+    /// Selects the files to synchronize, other files are neither downloaded nor uploaded
+    pub filter: FileFilter,
+}
+
+impl Default for SyncOptions {
+    fn default() -> Self {
+        Self {
+            dry_run: false,
+            max_delete_percent: 50,
+            filter: FileFilter::default(),
+        }
     }
-    for file in &files {
-        let destination = destination_for_file(&file.path(), service_config)?;
-        if let Err(e) = check_if_should_download(file, &destination) {
-            warn!("Skip download file {}: {}", file.path(), e)
+}
+
+/// Files handled by [`download`] and [`sync`]
+#[derive(Debug, Default)]
+pub struct SyncSummary {
+    pub downloaded: Vec<String>,
+    pub uploaded: Vec<String>,
+    pub deleted_remote: Vec<String>,
+    pub deleted_local: Vec<String>,
+    /// Local files renamed after the remote file has been renamed (`old -> new`)
+    pub renamed: Vec<String>,
+    /// Conflict copies of files that changed locally and remotely
+    pub conflicts: Vec<String>,
+    pub skipped: Vec<String>,
+    pub failed: Vec<String>,
+}
+
+/// Download new and changed remote files and remove local files deleted remotely
+///
+/// Local changes are never overwritten: if a file changed locally and remotely the remote
+/// version is stored as conflict copy next to it (see [`conflict_path`])
+pub fn download<S: ServiceTrait, C: ServiceConfigurationTrait>(
+    service: &S,
+    service_config: &C,
+    options: &SyncOptions,
+) -> Result<SyncSummary> {
+    synchronize(service, service_config, SyncMode::Download, options)
+}
+
+/// Synchronize the files in both directions
+///
+/// Remote changes are downloaded, local changes are uploaded and deletions are propagated to the
+/// other side. If a file changed locally and remotely the remote version is stored as conflict
+/// copy next to it (see [`conflict_path`])
+pub fn sync<S: ServiceTrait, C: ServiceConfigurationTrait>(
+    service: &S,
+    service_config: &C,
+    options: &SyncOptions,
+) -> Result<SyncSummary> {
+    synchronize(service, service_config, SyncMode::TwoWay, options)
+}
+
+fn synchronize<S: ServiceTrait, C: ServiceConfigurationTrait>(
+    service: &S,
+    service_config: &C,
+    mode: SyncMode,
+    options: &SyncOptions,
+) -> Result<SyncSummary> {
+    let output_path = get_output_path(service_config)?;
+    let mut state = SyncState::load(&output_path)?;
+
+    let mut remote_files = BTreeMap::new();
+    for file in service.list_files()? {
+        let name = file.relative_path();
+        if !is_safe_relative_path(&name) {
+            warn!("Ignore remote file with invalid path {}", name);
+        } else if options.filter.matches(&name) {
+            remote_files.insert(name, file);
         } else {
-            match service.download(file.clone(), &destination) {
-                Ok(_) => info!("Downloaded file {}", file.path()),
-                Err(e) => error!("Could not download file {}: {}", file.path(), e),
+            debug!("Ignore remote file {}", name);
+        }
+    }
+    if remote_files.is_empty() {
+        info!("No files found");
+    }
+    let mut local_files = list_local_files(&output_path)?;
+    local_files.retain(|name, _| options.filter.matches(name));
+
+    let names: BTreeSet<String> = remote_files
+        .keys()
+        .chain(local_files.keys())
+        .cloned()
+        .chain(state.names().map(ToOwned::to_owned))
+        // The names are joined with the output path, so they must not point outside of it
+        .filter(|name| is_safe_relative_path(name))
+        .collect();
+
+    let mut plan: BTreeMap<String, SyncAction> = names
+        .into_iter()
+        .map(|name| {
+            let action = plan_sync_action(
+                remote_files.get(&name),
+                local_files.get(&name),
+                state.get(&name),
+                mode,
+            );
+            (name, action)
+        })
+        .collect();
+    detect_renames(&mut plan, &remote_files, &state);
+    check_deletions(&plan, &state, options)?;
+
+    let mut summary = SyncSummary::default();
+    for (name, action) in plan {
+        let remote = remote_files.get(&name);
+        let local = local_files.get(&name);
+        let local_path = output_path.join(&name);
+
+        if options.dry_run {
+            if action != SyncAction::None {
+                info!("Dry run: {:?} {}", action, name);
+            }
+            summary.add(&name, &action, None);
+            continue;
+        }
+
+        let result = match (&action, remote, local) {
+            (SyncAction::Download, Some(remote), _) => fetch(service, remote, &local_path)
+                .and_then(|_| {
+                    info!("Downloaded file {}", name);
+                    state.insert(&name, build_state_entry(remote, &local_path)?);
+                    Ok(None)
+                }),
+            (SyncAction::Upload, _, Some(local)) => {
+                service.upload(&local_path, &name).map(|remote| {
+                    info!("Uploaded file {}", name);
+                    state.insert(&name, state_entry(&remote, local));
+                    None
+                })
+            }
+            (SyncAction::DeleteRemote, Some(remote), _) => {
+                service.delete(remote.clone()).map(|_| {
+                    info!("Deleted remote file {}", name);
+                    state.remove(&name);
+                    None
+                })
+            }
+            (SyncAction::DeleteLocal, _, Some(_)) => remove_local_file(&local_path, &output_path)
+                .map(|_| {
+                    info!("Deleted local file {}", name);
+                    state.remove(&name);
+                    None
+                }),
+            (SyncAction::Rename(from), Some(remote), _) => {
+                rename_local_file(&output_path.join(from), &local_path, &output_path).map(|_| {
+                    info!("Renamed local file {} to {}", from, name);
+                    if let Some(entry) = state.remove(from) {
+                        state.insert(
+                            &name,
+                            SyncStateEntry {
+                                remote_revision: remote.revision(),
+                                local_hash: entry.local_hash,
+                            },
+                        );
+                    }
+                    None
+                })
+            }
+            (SyncAction::Compare, Some(remote), Some(local)) => {
+                match compare(service, remote, local, &local_path) {
+                    Ok(Comparison::Equal) => {
+                        debug!("File {} is up to date", name);
+                        state.insert(&name, state_entry(remote, local));
+                        Ok(None)
+                    }
+                    Ok(Comparison::Conflict(conflict)) => {
+                        state.insert(&name, state_entry(remote, local));
+                        Ok(Some(conflict))
+                    }
+                    Err(e) => Err(e),
+                }
+            }
+            (SyncAction::Conflict, Some(remote), Some(local)) => {
+                let conflict = conflict_path(&local_path);
+                fetch(service, remote, &conflict).map(|_| {
+                    // Remember the current revisions, so that the conflict is only reported
+                    // once. Resolving it locally will then be treated as a local change
+                    state.insert(&name, state_entry(remote, local));
+                    Some(conflict)
+                })
+            }
+            (SyncAction::Forget, _, _) => {
+                state.remove(&name);
+                Ok(None)
+            }
+            (SyncAction::Skip(reason), _, _) => {
+                warn!("Skip file {}: {}", name, reason);
+                Ok(None)
+            }
+            _ => Ok(None),
+        };
+
+        match result {
+            Ok(conflict) => summary.add(&name, &action, conflict),
+            Err(e) => {
+                error!("Could not synchronize file {}: {}", name, e);
+                summary.failed.push(name);
             }
         }
     }
-    Ok(files)
+
+    if !options.dry_run {
+        state.save(&output_path)?;
+    }
+
+    Ok(summary)
 }
 
-pub fn check_if_should_download(source: &FileEntry, destination: &Path) -> Result<()> {
-    if !(destination.exists()) {
-        return Ok(());
-    }
-
-    match destination.metadata() {
-        Err(_) => Err(Error::download_error("Could not fetch metadata")),
-        Ok(metadata) => match metadata.modified() {
-            Err(_) => Err(Error::download_error("Could not fetch modification time")),
-            Ok(modified) => {
-                let remote_time = source.modified_date();
-                let local_time: DateTime<Utc> = DateTime::from(modified);
-                let local_time_utc = local_time.with_timezone(&remote_time.timezone());
-
-                debug!(
-                    "Compare remote vs local file time: {} vs {}",
-                    remote_time, local_time_utc
-                );
-                if local_time_utc < remote_time {
-                    info!("Remote file is newer than local file, will overwrite");
-                    Ok(())
-                } else {
-                    Err(Error::skip_download("Local file is newer than remote"))
+impl SyncSummary {
+    fn add(&mut self, name: &str, action: &SyncAction, conflict: Option<PathBuf>) {
+        let name = name.to_owned();
+        match action {
+            SyncAction::Download => self.downloaded.push(name),
+            SyncAction::Upload => self.uploaded.push(name),
+            SyncAction::DeleteRemote => self.deleted_remote.push(name),
+            SyncAction::DeleteLocal => self.deleted_local.push(name),
+            SyncAction::Rename(from) => self.renamed.push(format!("{} -> {}", from, name)),
+            SyncAction::Conflict | SyncAction::Compare => {
+                if let Some(conflict) = conflict {
+                    warn!(
+                        "File {} changed locally and remotely. The remote version was saved as {}",
+                        name,
+                        conflict.display()
+                    );
+                    self.conflicts.push(conflict.to_string_lossy().into_owned());
+                } else if action == &SyncAction::Conflict {
+                    // Dry run
+                    self.conflicts.push(name);
                 }
             }
-        },
+            SyncAction::Skip(_) => self.skipped.push(name),
+            SyncAction::Forget | SyncAction::None => {}
+        }
     }
 }
 
-fn destination_for_file<P: AsRef<Path>, S: ServiceConfigurationTrait>(
-    file: &P,
-    service_config: &S,
-) -> Result<PathBuf> {
-    let output_path = get_output_path(service_config)?;
+/// Replace the deletion and download of a remotely renamed file with a local rename
+///
+/// A file counts as renamed if a new remote file has the revision of a file that has been
+/// deleted remotely. Services that assign new revisions on rename fall back to downloading the
+/// new and deleting the old file
+fn detect_renames(
+    plan: &mut BTreeMap<String, SyncAction>,
+    remote_files: &BTreeMap<String, FileEntry>,
+    state: &SyncState,
+) {
+    let deleted: Vec<String> = plan
+        .iter()
+        .filter(|(_, action)| **action == SyncAction::DeleteLocal)
+        .map(|(name, _)| name.clone())
+        .collect();
+
+    for from in deleted {
+        let revision = match state.get(&from) {
+            Some(entry) => &entry.remote_revision,
+            None => continue,
+        };
+        let target = plan
+            .iter()
+            .find_map(|(name, action)| match remote_files.get(name) {
+                Some(remote)
+                    if *action == SyncAction::Download
+                        && state.get(name).is_none()
+                        && remote.revision() == *revision =>
+                {
+                    Some(name.clone())
+                }
+                _ => None,
+            });
+
+        if let Some(to) = target {
+            plan.insert(from.clone(), SyncAction::None);
+            plan.insert(to, SyncAction::Rename(from));
+        }
+    }
+}
+
+/// Abort if the plan would delete more files than allowed by the safety threshold
+fn check_deletions(
+    plan: &BTreeMap<String, SyncAction>,
+    state: &SyncState,
+    options: &SyncOptions,
+) -> Result<()> {
+    let deletions = plan
+        .values()
+        .filter(|action| matches!(action, SyncAction::DeleteLocal | SyncAction::DeleteRemote))
+        .count();
+    let allowed = (state.len() * options.max_delete_percent as usize / 100).max(1);
+
+    if deletions > allowed {
+        Err(Error::sync_aborted(format!(
+            "Refusing to delete {} of {} synchronized files (allowed are {}%). \
+            Check the listing of the service or increase the threshold",
+            deletions,
+            state.len(),
+            options.max_delete_percent
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Decide how to synchronize a file given its remote entry, the local file and the state of the
+/// last synchronization
+pub fn plan_sync_action(
+    remote: Option<&FileEntry>,
+    local: Option<&LocalFile>,
+    state: Option<&SyncStateEntry>,
+    mode: SyncMode,
+) -> SyncAction {
+    let two_way = mode == SyncMode::TwoWay;
+    match (remote, local, state) {
+        (Some(remote), Some(local), Some(state)) => {
+            let remote_changed = remote.revision() != state.remote_revision;
+            let local_changed = local.hash != state.local_hash;
+            match (remote_changed, local_changed) {
+                (true, true) => SyncAction::Conflict,
+                (true, false) => SyncAction::Download,
+                (false, true) if two_way => SyncAction::Upload,
+                (false, _) => SyncAction::None,
+            }
+        }
+        // The file has not been synchronized before (e.g. the state has been deleted)
+        (Some(_), Some(_), None) => SyncAction::Compare,
+        (Some(remote), None, Some(state)) => {
+            // A remote change wins over the local deletion
+            if two_way && remote.revision() == state.remote_revision {
+                SyncAction::DeleteRemote
+            } else {
+                SyncAction::Download
+            }
+        }
+        (Some(_), None, None) => SyncAction::Download,
+        (None, Some(local), Some(state)) => {
+            // A local change wins over the remote deletion
+            if local.hash == state.local_hash {
+                SyncAction::DeleteLocal
+            } else if two_way {
+                SyncAction::Upload
+            } else {
+                SyncAction::Skip("File has been deleted remotely but changed locally".to_owned())
+            }
+        }
+        (None, Some(_), None) if two_way => SyncAction::Upload,
+        (None, Some(_), None) => SyncAction::None,
+        (None, None, Some(_)) => SyncAction::Forget,
+        (None, None, None) => SyncAction::None,
+    }
+}
+
+/// Return the path of the conflict copy for the file at `path`
+///
+/// The marker is inserted before the extension (`song.chorddown` becomes
+/// `song.conflict.chorddown`), so that the copy keeps its file type
+pub fn conflict_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let file_name = match path.extension() {
+        Some(extension) => format!(
+            "{}.{}.{}",
+            stem,
+            CONFLICT_MARKER,
+            extension.to_string_lossy()
+        ),
+        None => format!("{}.{}", stem, CONFLICT_MARKER),
+    };
+
+    path.with_file_name(file_name)
+}
+
+fn is_conflict_copy(name: &str) -> bool {
+    let stem = Path::new(name)
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy();
+
+    stem.ends_with(&format!(".{}", CONFLICT_MARKER))
+        || name.ends_with(&format!(".{}", CONFLICT_MARKER))
+}
 
-    match file.as_ref().file_name() {
-        Some(file_name) => Ok(output_path.join(file_name)),
-        None => Err(Error::io_error(format!(
-            "Could not get remove name of file {}",
-            file.as_ref().to_string_lossy()
-        ))),
+/// Download the remote file to `destination`
+///
+/// The file is downloaded to a temporary file first, so that an interrupted download does not
+/// leave a truncated file behind
+fn fetch<S: ServiceTrait>(service: &S, file: &FileEntry, destination: &Path) -> Result<()> {
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let temporary_path = temporary_path(destination);
+    service.download(file.clone(), &temporary_path)?;
+    fs::rename(&temporary_path, destination)?;
+
+    Ok(())
+}
+
+/// Outcome of [`compare`]
+enum Comparison {
+    /// The remote and the local file have the same content
+    Equal,
+    /// The remote version has been stored as conflict copy at the path
+    Conflict(PathBuf),
+}
+
+/// Compare a remote file with the local file that has not been synchronized before
+///
+/// If the contents differ the remote version is stored as conflict copy. Without a sync state
+/// there is no way to tell which side has been changed (the modification times of different
+/// machines can not be trusted), so the local file is never overwritten
+fn compare<S: ServiceTrait>(
+    service: &S,
+    remote: &FileEntry,
+    local: &LocalFile,
+    local_path: &Path,
+) -> Result<Comparison> {
+    let temporary_path = temporary_path(local_path);
+    service.download(remote.clone(), &temporary_path)?;
+
+    if hash_file(&temporary_path)? == local.hash {
+        fs::remove_file(&temporary_path)?;
+        return Ok(Comparison::Equal);
+    }
+
+    let conflict = conflict_path(local_path);
+    fs::rename(&temporary_path, &conflict)?;
+    Ok(Comparison::Conflict(conflict))
+}
+
+fn state_entry(remote: &FileEntry, local: &LocalFile) -> SyncStateEntry {
+    SyncStateEntry {
+        remote_revision: remote.revision(),
+        local_hash: local.hash.clone(),
+    }
+}
+
+fn build_state_entry(file: &FileEntry, local_path: &Path) -> Result<SyncStateEntry> {
+    Ok(SyncStateEntry {
+        remote_revision: file.revision(),
+        local_hash: hash_file(local_path)?,
+    })
+}
+
+/// Return the SHA-256 hash of the file's content
+pub(crate) fn hash_file(path: &Path) -> Result<String> {
+    Ok(format!("{:x}", Sha256::digest(fs::read(path)?)))
+}
+
+/// Return the hidden file a download to `destination` is written to
+fn temporary_path(destination: &Path) -> PathBuf {
+    let file_name = destination
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+
+    destination.with_file_name(format!(".{}.download", file_name))
+}
+
+/// Return the regular files inside `directory` and its sub directories
+///
+/// The files are keyed by their path relative to `directory`. Conflict copies are ignored
+fn list_local_files(directory: &Path) -> Result<BTreeMap<String, LocalFile>> {
+    let mut files = BTreeMap::new();
+    for (name, path) in list_directory_files(directory)? {
+        if is_conflict_copy(&name) {
+            continue;
+        }
+        files.insert(
+            name,
+            LocalFile {
+                hash: hash_file(&path)?,
+            },
+        );
+    }
+
+    Ok(files)
+}
+
+/// Return the relative paths (using `/` as separator) and paths of the regular files inside
+/// `directory` and its sub directories
+///
+/// Hidden files and directories (like the sync state or temporary downloads) are ignored
+pub(crate) fn list_directory_files(directory: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let mut files = vec![];
+    let mut directories = vec![(String::new(), directory.to_path_buf())];
+    while let Some((prefix, directory)) = directories.pop() {
+        for entry in fs::read_dir(&directory)? {
+            let entry = entry?;
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            if file_name.starts_with('.') {
+                continue;
+            }
+
+            let name = format!("{}{}", prefix, file_name);
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                directories.push((format!("{}/", name), entry.path()));
+            } else if file_type.is_file() {
+                files.push((name, entry.path()));
+            }
+        }
+    }
+    files.sort();
+
+    Ok(files)
+}
+
+/// Remove the local file and the directories left empty up to `output_path`
+fn remove_local_file(path: &Path, output_path: &Path) -> Result<()> {
+    fs::remove_file(path)?;
+    remove_empty_directories(path, output_path);
+
+    Ok(())
+}
+
+/// Move the local file, creating the missing directories of the destination
+fn rename_local_file(from: &Path, to: &Path, output_path: &Path) -> Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::rename(from, to)?;
+    remove_empty_directories(from, output_path);
+
+    Ok(())
+}
+
+/// Remove the empty parent directories of `path` inside `output_path`
+fn remove_empty_directories(path: &Path, output_path: &Path) {
+    for directory in path.ancestors().skip(1) {
+        // `remove_dir()` fails for directories that are not empty
+        if directory == output_path
+            || !directory.starts_with(output_path)
+            || fs::remove_dir(directory).is_err()
+        {
+            break;
+        }
     }
 }
 
@@ -98,3 +630,385 @@ fn get_output_path<S: ServiceConfigurationTrait>(service_config: &S) -> Result<P
         )))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+
+    fn remote(revision: &str) -> FileEntry {
+        FileEntry::new(
+            "/Lyrics/song.chorddown",
+            120,
+            DateTime::parse_from_rfc3339("2022-10-01T10:00:00Z").unwrap(),
+        )
+        .with_revision(revision)
+    }
+
+    fn local(hash: &str) -> LocalFile {
+        LocalFile {
+            hash: hash.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_plan_sync_action_with_state() {
+        let synced = SyncStateEntry {
+            remote_revision: "rev-1".to_owned(),
+            local_hash: "hash-1".to_owned(),
+        };
+        let remote_unchanged = remote("rev-1");
+        let remote_changed = remote("rev-2");
+        let local_unchanged = local("hash-1");
+        let local_changed = local("hash-2");
+
+        let plan = |r: Option<&FileEntry>, l: Option<&LocalFile>, mode: SyncMode| {
+            plan_sync_action(r, l, Some(&synced), mode)
+        };
+
+        for mode in [SyncMode::Download, SyncMode::TwoWay] {
+            assert_eq!(
+                plan(Some(&remote_unchanged), Some(&local_unchanged), mode),
+                SyncAction::None
+            );
+            assert_eq!(
+                plan(Some(&remote_changed), Some(&local_unchanged), mode),
+                SyncAction::Download
+            );
+            assert_eq!(
+                plan(Some(&remote_changed), Some(&local_changed), mode),
+                SyncAction::Conflict
+            );
+            assert_eq!(
+                plan(Some(&remote_changed), None, mode),
+                SyncAction::Download
+            );
+            assert_eq!(
+                plan(None, Some(&local_unchanged), mode),
+                SyncAction::DeleteLocal
+            );
+            assert_eq!(plan(None, None, mode), SyncAction::Forget);
+        }
+
+        assert_eq!(
+            plan(
+                Some(&remote_unchanged),
+                Some(&local_changed),
+                SyncMode::TwoWay
+            ),
+            SyncAction::Upload
+        );
+        assert_eq!(
+            plan(
+                Some(&remote_unchanged),
+                Some(&local_changed),
+                SyncMode::Download
+            ),
+            SyncAction::None
+        );
+        assert_eq!(
+            plan(Some(&remote_unchanged), None, SyncMode::TwoWay),
+            SyncAction::DeleteRemote
+        );
+        assert_eq!(
+            plan(Some(&remote_unchanged), None, SyncMode::Download),
+            SyncAction::Download
+        );
+        assert_eq!(
+            plan(None, Some(&local_changed), SyncMode::TwoWay),
+            SyncAction::Upload
+        );
+        assert!(matches!(
+            plan(None, Some(&local_changed), SyncMode::Download),
+            SyncAction::Skip(_)
+        ));
+    }
+
+    #[test]
+    fn test_plan_sync_action_without_state() {
+        let remote_file = remote("rev-1");
+        let local_file = local("hash-1");
+
+        for mode in [SyncMode::Download, SyncMode::TwoWay] {
+            assert_eq!(
+                plan_sync_action(Some(&remote_file), None, None, mode),
+                SyncAction::Download
+            );
+            assert_eq!(
+                plan_sync_action(Some(&remote_file), Some(&local_file), None, mode),
+                SyncAction::Compare
+            );
+        }
+        assert_eq!(
+            plan_sync_action(None, Some(&local_file), None, SyncMode::TwoWay),
+            SyncAction::Upload
+        );
+        assert_eq!(
+            plan_sync_action(None, Some(&local_file), None, SyncMode::Download),
+            SyncAction::None
+        );
+    }
+
+    fn synced_state(names: &[&str]) -> SyncState {
+        let mut state = SyncState::default();
+        for name in names {
+            state.insert(
+                *name,
+                SyncStateEntry {
+                    remote_revision: format!("rev-{}", name),
+                    local_hash: format!("hash-{}", name),
+                },
+            );
+        }
+        state
+    }
+
+    #[test]
+    fn test_detect_renames() {
+        let state = synced_state(&["old.chorddown", "deleted.chorddown"]);
+        let mut remote_files = BTreeMap::new();
+        remote_files.insert("new.chorddown".to_owned(), remote("rev-old.chorddown"));
+        remote_files.insert("other.chorddown".to_owned(), remote("rev-other.chorddown"));
+
+        let mut plan = BTreeMap::new();
+        plan.insert("old.chorddown".to_owned(), SyncAction::DeleteLocal);
+        plan.insert("deleted.chorddown".to_owned(), SyncAction::DeleteLocal);
+        plan.insert("new.chorddown".to_owned(), SyncAction::Download);
+        plan.insert("other.chorddown".to_owned(), SyncAction::Download);
+
+        detect_renames(&mut plan, &remote_files, &state);
+        assert_eq!(plan["old.chorddown"], SyncAction::None);
+        assert_eq!(
+            plan["new.chorddown"],
+            SyncAction::Rename("old.chorddown".to_owned())
+        );
+        assert_eq!(plan["deleted.chorddown"], SyncAction::DeleteLocal);
+        assert_eq!(plan["other.chorddown"], SyncAction::Download);
+    }
+
+    #[test]
+    fn test_check_deletions() {
+        let state = synced_state(&["a", "b", "c", "d"]);
+        let options = SyncOptions::default();
+
+        let mut plan = BTreeMap::new();
+        plan.insert("a".to_owned(), SyncAction::DeleteLocal);
+        plan.insert("b".to_owned(), SyncAction::DeleteRemote);
+        plan.insert("c".to_owned(), SyncAction::None);
+        assert!(check_deletions(&plan, &state, &options).is_ok());
+
+        // An empty listing would delete all files
+        plan.insert("c".to_owned(), SyncAction::DeleteLocal);
+        assert!(check_deletions(&plan, &state, &options).is_err());
+
+        let options = SyncOptions {
+            max_delete_percent: 100,
+            ..SyncOptions::default()
+        };
+        assert!(check_deletions(&plan, &state, &options).is_ok());
+    }
+
+    fn test_directory(name: &str) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("synchord-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(directory.join("remote")).unwrap();
+        fs::create_dir_all(directory.join("local")).unwrap();
+
+        directory
+    }
+
+    fn local_service_config(directory: &Path) -> AbstractServiceConfig {
+        let missing = || Err(Error::missing_argument_error("Not needed"));
+
+        AbstractServiceConfig::build(
+            missing(),
+            missing(),
+            Ok(directory.join("remote").to_string_lossy().into_owned()),
+            missing(),
+            missing(),
+            directory.join("local"),
+            ServiceIdentifier::Local,
+        )
+    }
+
+    fn read(path: PathBuf) -> String {
+        fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn test_sync_with_local_service() {
+        let directory = test_directory("sync");
+        let remote = directory.join("remote");
+        let local = directory.join("local");
+        fs::write(remote.join("a.chorddown"), "# A\n[C]La").unwrap();
+        fs::write(remote.join("b.chorddown"), "# B\n[G]La").unwrap();
+
+        let config = local_service_config(&directory);
+        let service = Services::new(config.clone()).unwrap();
+        let options = SyncOptions::default();
+
+        let summary = sync(&service, &config, &options).unwrap();
+        assert_eq!(summary.downloaded, vec!["a.chorddown", "b.chorddown"]);
+        assert_eq!(read(local.join("a.chorddown")), "# A\n[C]La");
+
+        // Local changes are uploaded
+        fs::write(local.join("a.chorddown"), "# A\n[C]Lalala").unwrap();
+        fs::write(local.join("c.chorddown"), "# C\n[D]La").unwrap();
+        let summary = sync(&service, &config, &options).unwrap();
+        assert_eq!(summary.uploaded, vec!["a.chorddown", "c.chorddown"]);
+        assert_eq!(read(remote.join("a.chorddown")), "# A\n[C]Lalala");
+
+        // Remote renames are applied locally
+        fs::rename(remote.join("b.chorddown"), remote.join("b2.chorddown")).unwrap();
+        let summary = sync(&service, &config, &options).unwrap();
+        assert_eq!(summary.renamed, vec!["b.chorddown -> b2.chorddown"]);
+        assert!(!local.join("b.chorddown").exists());
+        assert_eq!(read(local.join("b2.chorddown")), "# B\n[G]La");
+
+        // Changes on both sides are reported as conflict
+        fs::write(remote.join("a.chorddown"), "# A\n[C]Remote").unwrap();
+        fs::write(local.join("a.chorddown"), "# A\n[C]Local").unwrap();
+        let summary = sync(&service, &config, &options).unwrap();
+        assert_eq!(summary.conflicts.len(), 1);
+        assert_eq!(read(local.join("a.chorddown")), "# A\n[C]Local");
+        assert_eq!(read(local.join("a.conflict.chorddown")), "# A\n[C]Remote");
+        assert!(!remote.join("a.conflict.chorddown").exists());
+
+        // Remote deletions are applied locally
+        fs::remove_file(remote.join("c.chorddown")).unwrap();
+        let dry_run = SyncOptions {
+            dry_run: true,
+            ..SyncOptions::default()
+        };
+        let summary = download(&service, &config, &dry_run).unwrap();
+        assert_eq!(summary.deleted_local, vec!["c.chorddown"]);
+        assert!(local.join("c.chorddown").exists());
+        let summary = download(&service, &config, &options).unwrap();
+        assert_eq!(summary.deleted_local, vec!["c.chorddown"]);
+        assert!(!local.join("c.chorddown").exists());
+
+        // An empty listing must not wipe the local files
+        fs::remove_file(remote.join("a.chorddown")).unwrap();
+        fs::remove_file(remote.join("b2.chorddown")).unwrap();
+        assert!(download(&service, &config, &options).is_err());
+        assert!(local.join("b2.chorddown").exists());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_sync_keeps_unsynchronized_local_file() {
+        let directory = test_directory("sync-unsynchronized");
+        let remote = directory.join("remote");
+        let local = directory.join("local");
+        fs::write(local.join("song.chorddown"), "local edit").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(local.join("song.chorddown"))
+            .unwrap()
+            .set_modified(std::time::SystemTime::UNIX_EPOCH)
+            .unwrap();
+        fs::write(remote.join("song.chorddown"), "remote v1").unwrap();
+
+        let config = local_service_config(&directory);
+        let service = Services::new(config.clone()).unwrap();
+        let options = SyncOptions::default();
+
+        // The file has not been synchronized before: the newer remote version must not replace
+        // the local file
+        let summary = sync(&service, &config, &options).unwrap();
+        assert!(summary.downloaded.is_empty());
+        assert_eq!(summary.conflicts.len(), 1);
+        assert_eq!(read(local.join("song.chorddown")), "local edit");
+        assert_eq!(read(local.join("song.conflict.chorddown")), "remote v1");
+
+        // The conflict is only reported once
+        let summary = sync(&service, &config, &options).unwrap();
+        assert!(summary.conflicts.is_empty());
+        assert!(summary.downloaded.is_empty());
+        assert!(summary.uploaded.is_empty());
+        assert_eq!(read(remote.join("song.chorddown")), "remote v1");
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_sync_sub_directories() {
+        let directory = test_directory("sync-sub-directories");
+        let remote = directory.join("remote");
+        let local = directory.join("local");
+        fs::create_dir_all(remote.join("de")).unwrap();
+        fs::create_dir_all(remote.join("en/drafts")).unwrap();
+        fs::write(remote.join("de/stille-nacht.chorddown"), "# Stille Nacht").unwrap();
+        fs::write(remote.join("en/swing-low.chorddown"), "# Swing low").unwrap();
+        fs::write(remote.join("en/drafts/new.chorddown"), "# New").unwrap();
+        fs::write(remote.join("en/notes.txt"), "Notes").unwrap();
+
+        let config = local_service_config(&directory);
+        let service = Services::new(config.clone()).unwrap();
+        let options = SyncOptions {
+            filter: FileFilter::new(["*.chorddown"], ["*/drafts/*"]).unwrap(),
+            ..SyncOptions::default()
+        };
+
+        let summary = sync(&service, &config, &options).unwrap();
+        assert_eq!(
+            summary.downloaded,
+            vec!["de/stille-nacht.chorddown", "en/swing-low.chorddown"]
+        );
+        assert_eq!(
+            read(local.join("de/stille-nacht.chorddown")),
+            "# Stille Nacht"
+        );
+        assert!(!local.join("en/notes.txt").exists());
+        assert!(!local.join("en/drafts").exists());
+
+        // New local files are uploaded into the same sub directory
+        fs::create_dir_all(local.join("fr")).unwrap();
+        fs::write(local.join("fr/frere-jacques.chorddown"), "# Frère Jacques").unwrap();
+        fs::write(local.join("fr/todo.txt"), "Todo").unwrap();
+        let summary = sync(&service, &config, &options).unwrap();
+        assert_eq!(summary.uploaded, vec!["fr/frere-jacques.chorddown"]);
+        assert!(remote.join("fr/frere-jacques.chorddown").exists());
+        assert!(!remote.join("fr/todo.txt").exists());
+
+        // Moving a file to another directory is applied as rename
+        fs::create_dir_all(remote.join("de/weihnachten")).unwrap();
+        fs::rename(
+            remote.join("de/stille-nacht.chorddown"),
+            remote.join("de/weihnachten/stille-nacht.chorddown"),
+        )
+        .unwrap();
+        let summary = sync(&service, &config, &options).unwrap();
+        assert_eq!(
+            summary.renamed,
+            vec!["de/stille-nacht.chorddown -> de/weihnachten/stille-nacht.chorddown"]
+        );
+        assert!(local.join("de/weihnachten/stille-nacht.chorddown").exists());
+
+        // Directories left empty are removed
+        fs::remove_file(remote.join("en/swing-low.chorddown")).unwrap();
+        let summary = sync(&service, &config, &options).unwrap();
+        assert_eq!(summary.deleted_local, vec!["en/swing-low.chorddown"]);
+        assert!(!local.join("en").exists());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_conflict_path() {
+        assert_eq!(
+            conflict_path(Path::new("/songs/swing-low.chorddown")),
+            PathBuf::from("/songs/swing-low.conflict.chorddown")
+        );
+        assert_eq!(
+            conflict_path(Path::new("/songs/README")),
+            PathBuf::from("/songs/README.conflict")
+        );
+        assert!(is_conflict_copy("swing-low.conflict.chorddown"));
+        assert!(is_conflict_copy("README.conflict"));
+        assert!(!is_conflict_copy("conflict.chorddown"));
+        assert!(!is_conflict_copy("swing-low.chorddown"));
+    }
+}
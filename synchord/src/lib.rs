@@ -1,4 +1,6 @@
 pub mod error;
+pub mod file_filter;
 pub mod helper;
 pub mod prelude;
 pub mod service;
+pub mod sync_state;
@@ -1,4 +1,5 @@
 use std::convert::TryFrom;
+use std::fs;
 use std::fs::File;
 use std::io;
 use std::io::prelude::*;
@@ -6,7 +7,10 @@ use std::path::{Path, PathBuf};
 
 use chrono::DateTime;
 use dropbox_sdk::client_trait::{Endpoint, HttpClient, HttpRequestResultRaw, ParamsType, Style};
-use dropbox_sdk::files::{DownloadArg, FileMetadata, ListFolderArg, Metadata};
+use dropbox_sdk::files::{
+    CommitInfo, DeleteArg, DownloadArg, FileMetadata, ListFolderArg, ListFolderContinueArg,
+    Metadata, WriteMode,
+};
 use dropbox_sdk::UserAuthClient;
 
 use crate::error::{Error, Result};
@@ -80,18 +84,27 @@ impl ServiceTrait for DropboxService {
     /// service will only have access to the contents of the App folder.
     ///
     /// https://www.dropbox.com/developers/apps
+    ///
+    /// Sub folders are listed recursively. Dropbox returns large listings in pages, which are
+    /// fetched until the listing is complete
     fn list_files(&self) -> Result<Vec<FileEntry>, Error> {
         let path_relative_to_app_folder = "".to_owned();
-        let request_argument: ListFolderArg = ListFolderArg::new(path_relative_to_app_folder);
-        let result = dropbox_sdk::files::list_folder(&self, &request_argument)??;
+        let request_argument: ListFolderArg =
+            ListFolderArg::new(path_relative_to_app_folder).with_recursive(true);
+        let mut result = dropbox_sdk::files::list_folder(&self, &request_argument)??;
+        let mut entries = std::mem::take(&mut result.entries);
+        while result.has_more {
+            let request_argument = ListFolderContinueArg::new(result.cursor);
+            result = dropbox_sdk::files::list_folder_continue(&self, &request_argument)??;
+            entries.append(&mut result.entries);
+        }
 
-        Ok(result
-            .entries
+        Ok(entries
             .iter()
             .filter_map(|m| {
                 match m {
                     Metadata::File(data) => FileEntry::try_from(data).ok(),
-                    Metadata::Folder(_) => None,  // Not implemented
+                    Metadata::Folder(_) => None,  // Listed recursively
                     Metadata::Deleted(_) => None, // Not implemented
                 }
             })
@@ -105,6 +118,27 @@ impl ServiceTrait for DropboxService {
 
         Ok(())
     }
+
+    fn upload(&self, source: &Path, destination: &str) -> Result<FileEntry> {
+        let body = fs::read(source)?;
+        let request_argument = CommitInfo::new(format!("/{}", destination.trim_start_matches('/')))
+            .with_mode(WriteMode::Overwrite);
+        let metadata = dropbox_sdk::files::upload(&self, &request_argument, &body)??;
+
+        FileEntry::try_from(&metadata).map_err(|_| {
+            Error::upload_error(format!(
+                "Invalid metadata for uploaded file {}",
+                destination
+            ))
+        })
+    }
+
+    fn delete(&self, file: FileEntry) -> Result<()> {
+        let request_argument = DeleteArg::new(file.path().to_owned());
+        dropbox_sdk::files::delete_v2(&self, &request_argument)??;
+
+        Ok(())
+    }
 }
 
 impl HttpClient for &DropboxService {
@@ -138,13 +172,16 @@ impl TryFrom<&FileMetadata> for FileEntry {
     type Error = ();
 
     fn try_from(value: &FileMetadata) -> Result<Self, Self::Error> {
-        let path = match value.path_lower {
-            Some(ref p) => p,
-            None => return Err(()),
+        // Prefer the display path, so that the local file names keep their case
+        let path = match (&value.path_display, &value.path_lower) {
+            (Some(p), _) | (None, Some(p)) => p,
+            (None, None) => return Err(()),
         };
 
         match DateTime::parse_from_rfc3339(&value.server_modified) {
-            Ok(date) => Ok(FileEntry::new(path, value.size as usize, date)),
+            Ok(date) => Ok(FileEntry::new(path, value.size as usize, date)
+                .with_revision(&value.rev)
+                .with_relative_path(path.trim_start_matches('/'))),
             Err(_) => Err(()),
         }
     }
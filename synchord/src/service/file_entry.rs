@@ -1,3 +1,5 @@
+use std::path::{Component, Path};
+
 use chrono::{DateTime, FixedOffset};
 
 #[derive(Clone, Debug)]
@@ -5,6 +7,8 @@ pub struct FileEntry {
     path: String,
     size: usize,
     modified_date: DateTime<FixedOffset>,
+    revision: Option<String>,
+    relative_path: Option<String>,
 }
 
 #[allow(dead_code)]
@@ -18,6 +22,24 @@ impl FileEntry {
             path: path.into(),
             size,
             modified_date,
+            revision: None,
+            relative_path: None,
+        }
+    }
+
+    /// Set the revision (Dropbox `rev` or WebDAV `ETag`) the service assigned to the file
+    pub fn with_revision<S: Into<String>>(self, revision: S) -> Self {
+        Self {
+            revision: Some(revision.into()),
+            ..self
+        }
+    }
+
+    /// Set the path of the file relative to the remote directory (e.g. `de/song.chorddown`)
+    pub fn with_relative_path<S: Into<String>>(self, relative_path: S) -> Self {
+        Self {
+            relative_path: Some(relative_path.into()),
+            ..self
         }
     }
 
@@ -25,6 +47,17 @@ impl FileEntry {
         self.path.as_str()
     }
 
+    /// Return the path relative to the remote directory, using `/` as separator
+    ///
+    /// The local directory structure mirrors these paths. If the service did not report a
+    /// relative path the file name is used
+    pub fn relative_path(&self) -> String {
+        match &self.relative_path {
+            Some(relative_path) => relative_path.clone(),
+            None => self.path.rsplit('/').next().unwrap_or_default().to_owned(),
+        }
+    }
+
     pub fn size(&self) -> usize {
         self.size
     }
@@ -32,4 +65,40 @@ impl FileEntry {
     pub fn modified_date(&self) -> DateTime<FixedOffset> {
         self.modified_date
     }
+
+    /// Return the revision of the remote file
+    ///
+    /// If the service did not report a revision the modification date is used
+    pub fn revision(&self) -> String {
+        match &self.revision {
+            Some(revision) => revision.clone(),
+            None => self.modified_date.to_rfc3339(),
+        }
+    }
+}
+
+/// Return if the relative path only consists of normal segments
+///
+/// Paths containing `..`, `.` or a root could point outside of the local directory
+pub(crate) fn is_safe_relative_path(path: &str) -> bool {
+    !path.is_empty()
+        && Path::new(path)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_safe_relative_path() {
+        assert!(is_safe_relative_path("swing-low.chorddown"));
+        assert!(is_safe_relative_path("de/stille-nacht.chorddown"));
+        assert!(!is_safe_relative_path(""));
+        assert!(!is_safe_relative_path("../evil.chorddown"));
+        assert!(!is_safe_relative_path("de/../../evil.chorddown"));
+        assert!(!is_safe_relative_path("./evil.chorddown"));
+        assert!(!is_safe_relative_path("/etc/evil.chorddown"));
+    }
 }
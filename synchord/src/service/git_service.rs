@@ -0,0 +1,420 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use log::{debug, info};
+
+use crate::error::{Error, Result};
+use crate::service::file_entry::FileEntry;
+use crate::service::local_service::LocalService;
+use crate::service::{
+    AbstractServiceConfig, ServiceConfigurationTrait, ServiceIdentifier, ServiceTrait,
+};
+
+/// Name used for commits if Git has no `user.name` configured
+const DEFAULT_USER_NAME: &str = "synchord";
+
+/// E-mail address used for commits if Git has no `user.email` configured
+const DEFAULT_USER_EMAIL: &str = "synchord@localhost";
+
+/// Service synchronizing with a Git repository through the local `git` executable
+///
+/// The repository is cloned into a checkout directory next to the local directory (it must not
+/// be inside, because the catalog is built from all files in the local directory). Before the
+/// files are listed the checkout is updated with `git pull`. Uploads and deletions are committed
+/// and pushed
+pub struct GitService {
+    url: String,
+    checkout_directory: PathBuf,
+    /// Directory inside the repository containing the songs
+    sub_directory: PathBuf,
+}
+
+impl GitService {
+    /// Clone the repository or pull the latest changes if it has already been cloned
+    fn update_checkout(&self) -> Result<()> {
+        if self.checkout_directory.join(".git").is_dir() {
+            debug!(
+                "Pull {} into {}",
+                self.url,
+                self.checkout_directory.display()
+            );
+            self.git(&["pull", "--ff-only", "--quiet"])
+        } else {
+            info!(
+                "Clone {} into {}",
+                self.url,
+                self.checkout_directory.display()
+            );
+            run_git(
+                Command::new("git")
+                    .args(["clone", "--quiet", "--", &self.url])
+                    .arg(&self.checkout_directory),
+            )
+        }
+    }
+
+    /// Return a service for the songs directory inside the checkout
+    fn local_service(&self) -> Result<LocalService> {
+        LocalService::with_source_directory(self.checkout_directory.join(&self.sub_directory))
+    }
+
+    /// Commit the changes of the path and push them
+    ///
+    /// If the path did not change (e.g. a file was uploaded with the same content) nothing is
+    /// committed
+    fn commit_and_push(&self, path: &Path, message: &str) -> Result<()> {
+        let path = path.to_string_lossy();
+        self.git(&["add", "--all", "--", &path])?;
+        if self.git_succeeds(&["diff", "--cached", "--quiet", "--", &path])? {
+            debug!("No changes to commit for {}", path);
+            return Ok(());
+        }
+
+        let identity = self.identity_args()?;
+        run_git(self.git_command(&[]).args(&identity).args([
+            "commit",
+            "--quiet",
+            "--message",
+            message,
+            "--",
+            &path,
+        ]))?;
+
+        let result = self.rebase_and_push(&identity);
+        if result.is_err() {
+            // Drop the local commit, otherwise the checkout diverges from the remote and can not
+            // be fast-forwarded anymore. The change is still in the local directory and will be
+            // uploaded again with the next synchronization
+            self.git_succeeds(&["rebase", "--abort"])?;
+            self.git(&["reset", "--quiet", "--hard", "@{upstream}"])?;
+        }
+
+        result
+    }
+
+    /// Rebase the local commit onto the changes pushed in the meantime and push it
+    fn rebase_and_push(&self, identity: &[String]) -> Result<()> {
+        run_git(
+            self.git_command(&[])
+                .args(identity)
+                .args(["pull", "--rebase", "--quiet"]),
+        )?;
+        self.git(&["push", "--quiet"])
+    }
+
+    /// Return the arguments to fall back to a default identity, so that commits also work on
+    /// servers without a configured Git user
+    fn identity_args(&self) -> Result<Vec<String>> {
+        let mut args = vec![];
+        for (key, default) in [
+            ("user.name", DEFAULT_USER_NAME),
+            ("user.email", DEFAULT_USER_EMAIL),
+        ] {
+            if !self.git_succeeds(&["config", "--get", key])? {
+                args.push("-c".to_owned());
+                args.push(format!("{}={}", key, default));
+            }
+        }
+
+        Ok(args)
+    }
+
+    fn git(&self, args: &[&str]) -> Result<()> {
+        run_git(&mut self.git_command(args))
+    }
+
+    /// Run git and return if it exited successfully
+    fn git_succeeds(&self, args: &[&str]) -> Result<bool> {
+        let output = self
+            .git_command(args)
+            .output()
+            .map_err(|e| Error::io_error(format!("Could not run git: {}", e)))?;
+
+        Ok(output.status.success())
+    }
+
+    fn git_command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new("git");
+        command.arg("-C").arg(&self.checkout_directory).args(args);
+
+        command
+    }
+}
+
+fn run_git(command: &mut Command) -> Result<()> {
+    let output = command
+        .output()
+        .map_err(|e| Error::io_error(format!("Could not run git: {}", e)))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(Error::io_error(format!(
+            "git failed with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+pub struct GitServiceConfiguration {
+    url: String,
+    sub_directory: PathBuf,
+    local_directory: PathBuf,
+}
+
+impl ServiceConfigurationTrait for GitServiceConfiguration {
+    fn from_service_config(service_config: AbstractServiceConfig) -> Result<Self> {
+        Ok(Self {
+            url: service_config.url()?,
+            // The remote directory is optional: by default the repository root is used
+            sub_directory: PathBuf::from(service_config.remote_directory().unwrap_or_default()),
+            local_directory: service_config.local_directory().to_path_buf(),
+        })
+    }
+
+    fn identifier(&self) -> ServiceIdentifier {
+        ServiceIdentifier::Git
+    }
+
+    fn local_directory(&self) -> &Path {
+        self.local_directory.as_path()
+    }
+}
+
+impl ServiceTrait for GitService {
+    type Configuration = GitServiceConfiguration;
+
+    fn new(configuration: Self::Configuration) -> Result<Self> {
+        let local_directory = configuration.local_directory;
+        let directory_name = match local_directory.file_name() {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => {
+                return Err(Error::invalid_argument_error(format!(
+                    "Invalid output directory {}",
+                    local_directory.display()
+                )))
+            }
+        };
+
+        Ok(Self {
+            url: configuration.url,
+            checkout_directory: local_directory
+                .with_file_name(format!(".{}.git-checkout", directory_name)),
+            sub_directory: configuration.sub_directory,
+        })
+    }
+
+    fn identifier(&self) -> ServiceIdentifier {
+        ServiceIdentifier::Git
+    }
+
+    fn list_files(&self) -> Result<Vec<FileEntry>> {
+        self.update_checkout()?;
+        self.local_service()?.list_files()
+    }
+
+    fn download(&self, file: FileEntry, destination: &Path) -> Result<()> {
+        self.local_service()?.download(file, destination)
+    }
+
+    fn upload(&self, source: &Path, destination: &str) -> Result<FileEntry> {
+        let entry = self.local_service()?.upload(source, destination)?;
+        self.commit_and_push(Path::new(entry.path()), &format!("Update {}", destination))?;
+
+        Ok(entry)
+    }
+
+    fn delete(&self, file: FileEntry) -> Result<()> {
+        let path = PathBuf::from(file.path());
+        let name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        self.local_service()?.delete(file)?;
+        self.commit_and_push(&path, &format!("Delete {}", name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn git_in(directory: &Path, args: &[&str]) {
+        run_git(
+            Command::new("git")
+                .arg("-C")
+                .arg(directory)
+                .args([
+                    "-c",
+                    "user.name=synchord",
+                    "-c",
+                    "user.email=synchord@example.com",
+                ])
+                .args(args),
+        )
+        .unwrap();
+    }
+
+    fn git_output(directory: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(directory)
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+
+    fn is_git_installed() -> bool {
+        if Command::new("git").arg("--version").output().is_err() {
+            eprintln!("git is not installed, skip test");
+            return false;
+        }
+        true
+    }
+
+    /// Create a repository with a song inside `directory` and return the service for it
+    fn prepare_repository(name: &str) -> (PathBuf, GitService) {
+        let directory =
+            std::env::temp_dir().join(format!("synchord-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        let repository = directory.join("repository");
+        fs::create_dir_all(repository.join("songs")).unwrap();
+        fs::create_dir_all(directory.join("local")).unwrap();
+
+        fs::write(repository.join("songs/swing-low.chorddown"), "# Swing low").unwrap();
+        fs::write(repository.join("README.md"), "Songs").unwrap();
+        git_in(&repository, &["init", "--quiet"]);
+        git_in(&repository, &["add", "--all"]);
+        git_in(
+            &repository,
+            &["commit", "--quiet", "--message", "Add songs"],
+        );
+        // Pushing requires a bare repository
+        git_in(
+            &directory,
+            &["clone", "--quiet", "--bare", "repository", "remote.git"],
+        );
+
+        let service = GitService::new(GitServiceConfiguration {
+            url: directory.join("remote.git").to_string_lossy().into_owned(),
+            sub_directory: PathBuf::from("songs"),
+            local_directory: directory.join("local"),
+        })
+        .unwrap();
+
+        (directory, service)
+    }
+
+    #[test]
+    fn test_clone_and_list() {
+        if !is_git_installed() {
+            return;
+        }
+        let (directory, service) = prepare_repository("git");
+
+        let files = service.list_files().unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(directory.join(".local.git-checkout/.git").is_dir());
+
+        let destination = directory.join("local/swing-low.chorddown");
+        service.download(files[0].clone(), &destination).unwrap();
+        assert_eq!(fs::read_to_string(destination).unwrap(), "# Swing low");
+
+        // Listing again pulls the existing checkout
+        assert_eq!(service.list_files().unwrap().len(), 1);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_upload_and_delete() {
+        if !is_git_installed() {
+            return;
+        }
+        let (directory, service) = prepare_repository("git-push");
+        let remote = directory.join("remote.git");
+        let tracked_files = || git_output(&remote, &["ls-tree", "-r", "--name-only", "HEAD"]);
+        let commit_count = || git_output(&remote, &["rev-list", "--count", "HEAD"]);
+        service.list_files().unwrap();
+
+        let source = directory.join("stille-nacht.chorddown");
+        fs::write(&source, "# Stille Nacht").unwrap();
+        service
+            .upload(&source, "de/stille-nacht.chorddown")
+            .unwrap();
+        assert!(tracked_files().contains("songs/de/stille-nacht.chorddown"));
+        assert_eq!(commit_count().trim(), "2");
+
+        // Uploading the same content again succeeds without an empty commit
+        service
+            .upload(&source, "de/stille-nacht.chorddown")
+            .unwrap();
+        assert_eq!(commit_count().trim(), "2");
+
+        let file = service
+            .list_files()
+            .unwrap()
+            .into_iter()
+            .find(|f| f.relative_path() == "de/stille-nacht.chorddown")
+            .unwrap();
+        service.delete(file).unwrap();
+        assert!(!tracked_files().contains("stille-nacht.chorddown"));
+        assert_eq!(commit_count().trim(), "3");
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_upload_with_concurrent_and_failing_push() {
+        use std::os::unix::fs::PermissionsExt;
+
+        if !is_git_installed() {
+            return;
+        }
+        let (directory, service) = prepare_repository("git-push-failure");
+        let remote = directory.join("remote.git");
+        let commit_count = || git_output(&remote, &["rev-list", "--count", "HEAD"]);
+        service.list_files().unwrap();
+
+        // Someone else pushes a change after the checkout has been updated
+        git_in(&directory, &["clone", "--quiet", "remote.git", "other"]);
+        fs::write(directory.join("other/README.md"), "More songs").unwrap();
+        git_in(
+            &directory.join("other"),
+            &["commit", "--quiet", "-am", "Update"],
+        );
+        git_in(&directory.join("other"), &["push", "--quiet"]);
+
+        let source = directory.join("stille-nacht.chorddown");
+        fs::write(&source, "# Stille Nacht").unwrap();
+        service.upload(&source, "stille-nacht.chorddown").unwrap();
+        assert_eq!(commit_count().trim(), "3");
+
+        // The remote rejects all pushes
+        let hook = remote.join("hooks/pre-receive");
+        fs::write(&hook, "#!/bin/sh\nexit 1\n").unwrap();
+        fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+
+        fs::write(&source, "# Stille Nacht, heilige Nacht").unwrap();
+        assert!(service.upload(&source, "stille-nacht.chorddown").is_err());
+        assert_eq!(commit_count().trim(), "3");
+
+        // The checkout has not diverged from the remote and can still be updated
+        fs::remove_file(&hook).unwrap();
+        let checkout = directory.join(".local.git-checkout");
+        assert_eq!(
+            git_output(&checkout, &["rev-parse", "HEAD"]),
+            git_output(&remote, &["rev-parse", "HEAD"])
+        );
+        assert_eq!(service.list_files().unwrap().len(), 2);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,112 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+
+use crate::error::{Error, Result};
+use crate::helper::{hash_file, list_directory_files};
+use crate::service::file_entry::FileEntry;
+use crate::service::{
+    AbstractServiceConfig, ServiceConfigurationTrait, ServiceIdentifier, ServiceTrait,
+};
+
+/// Service mirroring the files of another local directory (e.g. a mounted network share)
+pub struct LocalService {
+    source_directory: PathBuf,
+}
+
+impl LocalService {
+    pub(crate) fn with_source_directory(source_directory: PathBuf) -> Result<Self> {
+        if !source_directory.is_dir() {
+            return Err(Error::invalid_argument_error(format!(
+                "Source directory {} does not exist",
+                source_directory.display()
+            )));
+        }
+
+        Ok(Self { source_directory })
+    }
+
+    /// Return the entry for the file at `path`
+    ///
+    /// The content hash is used as revision, so that changes are detected independent of the
+    /// precision of the file system's modification times
+    fn file_entry(path: &Path) -> Result<FileEntry> {
+        let metadata = path.metadata()?;
+        let modified: DateTime<Utc> = DateTime::from(metadata.modified()?);
+
+        Ok(FileEntry::new(
+            path.to_string_lossy(),
+            metadata.len() as usize,
+            modified.into(),
+        )
+        .with_revision(hash_file(path)?))
+    }
+}
+
+pub struct LocalServiceConfiguration {
+    source_directory: PathBuf,
+    local_directory: PathBuf,
+}
+
+impl ServiceConfigurationTrait for LocalServiceConfiguration {
+    fn from_service_config(service_config: AbstractServiceConfig) -> Result<Self> {
+        Ok(Self {
+            source_directory: PathBuf::from(service_config.remote_directory()?),
+            local_directory: service_config.local_directory().to_path_buf(),
+        })
+    }
+
+    fn identifier(&self) -> ServiceIdentifier {
+        ServiceIdentifier::Local
+    }
+
+    fn local_directory(&self) -> &Path {
+        self.local_directory.as_path()
+    }
+}
+
+impl ServiceTrait for LocalService {
+    type Configuration = LocalServiceConfiguration;
+
+    fn new(configuration: Self::Configuration) -> Result<Self> {
+        Self::with_source_directory(configuration.source_directory)
+    }
+
+    fn identifier(&self) -> ServiceIdentifier {
+        ServiceIdentifier::Local
+    }
+
+    /// List the regular files inside the source directory and its sub directories
+    ///
+    /// Hidden files and directories are ignored
+    fn list_files(&self) -> Result<Vec<FileEntry>> {
+        list_directory_files(&self.source_directory)?
+            .into_iter()
+            .map(|(name, path)| Ok(Self::file_entry(&path)?.with_relative_path(name)))
+            .collect()
+    }
+
+    fn download(&self, file: FileEntry, destination: &Path) -> Result<()> {
+        fs::copy(file.path(), destination)?;
+
+        Ok(())
+    }
+
+    fn upload(&self, source: &Path, destination: &str) -> Result<FileEntry> {
+        let relative_path = destination.trim_start_matches('/');
+        let path = self.source_directory.join(relative_path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(source, &path)?;
+
+        Ok(Self::file_entry(&path)?.with_relative_path(relative_path))
+    }
+
+    fn delete(&self, file: FileEntry) -> Result<()> {
+        fs::remove_file(file.path())?;
+
+        Ok(())
+    }
+}
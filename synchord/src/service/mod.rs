@@ -1,13 +1,18 @@
 mod abstract_service_config;
 mod dropbox_service;
 mod file_entry;
+mod git_service;
+mod local_service;
 mod service_configuration;
 mod web_dav_service;
 
 pub use self::abstract_service_config::AbstractServiceConfig;
 // pub use self::abstract_service_config::ServiceConfigTrait;
 pub use self::dropbox_service::DropboxService;
+pub(crate) use self::file_entry::is_safe_relative_path;
 pub use self::file_entry::FileEntry;
+pub use self::git_service::GitService;
+pub use self::local_service::LocalService;
 pub use self::service_configuration::ServiceConfigurationTrait;
 pub use self::service_identifier::ServiceIdentifier;
 pub use self::web_dav_service::WebDAVService;
@@ -27,11 +32,22 @@ pub trait ServiceTrait {
     fn identifier(&self) -> ServiceIdentifier;
     fn list_files(&self) -> Result<Vec<FileEntry>>;
     fn download(&self, file: FileEntry, destination: &Path) -> Result<()>;
+
+    /// Upload the local file `source` to `destination` (relative to the remote directory)
+    ///
+    /// An existing remote file will be overwritten. Returns the entry of the uploaded file
+    fn upload(&self, source: &Path, destination: &str) -> Result<FileEntry>;
+
+    /// Delete the remote file
+    fn delete(&self, file: FileEntry) -> Result<()>;
 }
 
+#[allow(clippy::enum_variant_names)]
 pub enum Services {
     DropboxService(DropboxService),
     WebDAVService(WebDAVService),
+    LocalService(LocalService),
+    GitService(GitService),
 }
 
 impl ServiceTrait for Services {
@@ -50,6 +66,12 @@ impl ServiceTrait for Services {
             ServiceIdentifier::WebDAV => Ok(Services::WebDAVService(WebDAVService::new(
                 <WebDAVService as ServiceTrait>::Configuration::from_service_config(configuration)?,
             )?)),
+            ServiceIdentifier::Local => Ok(Services::LocalService(LocalService::new(
+                <LocalService as ServiceTrait>::Configuration::from_service_config(configuration)?,
+            )?)),
+            ServiceIdentifier::Git => Ok(Services::GitService(GitService::new(
+                <GitService as ServiceTrait>::Configuration::from_service_config(configuration)?,
+            )?)),
         }
     }
 
@@ -57,6 +79,8 @@ impl ServiceTrait for Services {
         match self {
             Services::DropboxService(service) => service.identifier(),
             Services::WebDAVService(service) => service.identifier(),
+            Services::LocalService(service) => service.identifier(),
+            Services::GitService(service) => service.identifier(),
         }
     }
 
@@ -64,6 +88,8 @@ impl ServiceTrait for Services {
         match self {
             Services::DropboxService(service) => service.list_files(),
             Services::WebDAVService(service) => service.list_files(),
+            Services::LocalService(service) => service.list_files(),
+            Services::GitService(service) => service.list_files(),
         }
     }
 
@@ -71,6 +97,26 @@ impl ServiceTrait for Services {
         match self {
             Services::DropboxService(service) => service.download(file, destination),
             Services::WebDAVService(service) => service.download(file, destination),
+            Services::LocalService(service) => service.download(file, destination),
+            Services::GitService(service) => service.download(file, destination),
+        }
+    }
+
+    fn upload(&self, source: &Path, destination: &str) -> Result<FileEntry, Error> {
+        match self {
+            Services::DropboxService(service) => service.upload(source, destination),
+            Services::WebDAVService(service) => service.upload(source, destination),
+            Services::LocalService(service) => service.upload(source, destination),
+            Services::GitService(service) => service.upload(source, destination),
+        }
+    }
+
+    fn delete(&self, file: FileEntry) -> Result<(), Error> {
+        match self {
+            Services::DropboxService(service) => service.delete(file),
+            Services::WebDAVService(service) => service.delete(file),
+            Services::LocalService(service) => service.delete(file),
+            Services::GitService(service) => service.delete(file),
         }
     }
 }
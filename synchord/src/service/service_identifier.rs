@@ -7,6 +7,8 @@ use std::fmt::{Display, Formatter};
 pub enum ServiceIdentifier {
     WebDAV,
     Dropbox,
+    Local,
+    Git,
 }
 
 impl TryFrom<&str> for ServiceIdentifier {
@@ -16,6 +18,8 @@ impl TryFrom<&str> for ServiceIdentifier {
         match value.to_lowercase().as_str() {
             "dropbox" => Ok(ServiceIdentifier::Dropbox),
             "webdav" => Ok(ServiceIdentifier::WebDAV),
+            "local" => Ok(ServiceIdentifier::Local),
+            "git" => Ok(ServiceIdentifier::Git),
             _ => Err(Error::unknown_service_error(format!(
                 "Service {} is not implemented",
                 value
@@ -32,6 +36,8 @@ impl Display for ServiceIdentifier {
             match self {
                 ServiceIdentifier::WebDAV => "WebDAV",
                 ServiceIdentifier::Dropbox => "Dropbox",
+                ServiceIdentifier::Local => "Local",
+                ServiceIdentifier::Git => "Git",
             }
         )
     }
@@ -1,18 +1,22 @@
 use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::fs;
 use std::fs::File;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
-use hyperdav::{Client, Response};
+use hyperdav::{Client, Depth, Response};
+use log::{debug, warn};
+use percent_encoding::percent_decode_str;
 use reqwest::{Method, StatusCode, Url};
 
 use crate::error::{Error, Result};
-use crate::service::file_entry::FileEntry;
+use crate::service::file_entry::{is_safe_relative_path, FileEntry};
 use crate::service::{
     AbstractServiceConfig, ServiceConfigurationTrait, ServiceIdentifier, ServiceTrait,
 };
 
-use self::propfind_parser::parse_propfind_response;
+use self::propfind_parser::{parse_propfind_response, TempFileEntry};
 
 mod propfind_parser;
 
@@ -29,6 +33,61 @@ impl WebDAVService {
     ///
     /// This method fails if the passed path doesn't exist on the WebDAV server.
     pub fn list<I>(&self, path: I) -> Result<Vec<FileEntry>>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        Ok(self
+            .propfind(path, Depth::Number(1))?
+            .into_iter()
+            .filter(|f| !f.is_directory())
+            .filter_map(|f| self.file_entry(f))
+            .collect())
+    }
+
+    /// List the files in the remote directory and all its sub collections
+    ///
+    /// A single `Depth: infinity` request is tried first. Many servers refuse it (e.g. with
+    /// `403 Forbidden`), in which case the collections are walked one level at a time
+    ///
+    /// Unlike the Dropbox listing, the result is not paged: PROPFIND (RFC 4918) has no paging
+    /// mechanism, the server always returns the complete multistatus response. Walking the
+    /// collections with `Depth: 1` bounds each response to the members of one collection
+    fn list_recursive(&self) -> Result<Vec<FileEntry>> {
+        let root = self.remote_path("");
+        match self.propfind(&root, Depth::Infinity) {
+            Ok(entries) => {
+                return Ok(entries
+                    .into_iter()
+                    .filter(|f| !f.is_directory())
+                    .filter_map(|f| self.file_entry(f))
+                    .collect())
+            }
+            Err(e) => debug!("Depth infinity not supported ({}), walk the collections", e),
+        }
+
+        let mut files = vec![];
+        let mut collections = VecDeque::from([root]);
+        while let Some(collection) = collections.pop_front() {
+            for entry in self.propfind(&collection, Depth::Number(1))? {
+                if !entry.is_directory() {
+                    files.extend(self.file_entry(entry));
+                    continue;
+                }
+
+                // The response contains the requested collection itself
+                let path = self.remove_overlapping_path_segments(entry.path().to_owned());
+                if path.len() > collection.len() && path.starts_with(&collection) {
+                    collections.push_back(path);
+                }
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// Send a PROPFIND request for `path` with the given `Depth` header
+    fn propfind<I>(&self, path: I, depth: Depth) -> Result<Vec<TempFileEntry>>
     where
         I: IntoIterator,
         I::Item: AsRef<str>,
@@ -42,18 +101,85 @@ impl WebDAVService {
         let res = prepare_hyperdav_result(
             self.client
                 .request(Method::from_str("PROPFIND").unwrap(), path)
+                .header("Depth", depth.to_string())
                 .body(body)
                 .send(),
         )?;
 
         self.check_status_code(&res.status())?;
 
-        let files = parse_propfind_response(res)?;
-        Ok(files
-            .into_iter()
-            .filter(|f| !f.is_directory())
-            .map(From::from)
-            .collect())
+        parse_propfind_response(res)
+    }
+
+    /// Build the entry for the file or return `None` if its path leaves the remote directory
+    fn file_entry(&self, file: TempFileEntry) -> Option<FileEntry> {
+        match self.relative_path(file.path()) {
+            Some(relative_path) if is_safe_relative_path(&relative_path) => {
+                Some(FileEntry::from(file).with_relative_path(relative_path))
+            }
+            Some(_) => {
+                warn!("Ignore remote file with invalid path {}", file.path());
+                None
+            }
+            None => Some(FileEntry::from(file)),
+        }
+    }
+
+    /// Return the path of the file (as reported in the `href`) relative to the remote directory
+    fn relative_path(&self, href: &str) -> Option<String> {
+        // The `href` may be an absolute URL or an absolute path
+        let path = match href.split_once("://") {
+            Some((_, rest)) => rest.split_once('/').map_or("", |(_, path)| path),
+            None => href,
+        };
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+        let base_path = percent_decode_str(self.url.path()).decode_utf8_lossy();
+        let base: Vec<String> = base_path
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(ToOwned::to_owned)
+            .chain(self.remote_path(""))
+            .collect();
+
+        if segments.len() > base.len() && segments.iter().zip(&base).all(|(s, b)| s == b) {
+            Some(segments[base.len()..].join("/"))
+        } else {
+            None
+        }
+    }
+
+    /// Create the collection at `path` and all its missing parents
+    fn create_collection(&self, path: &[String]) -> Result<()> {
+        for depth in 1..=path.len() {
+            let res = prepare_hyperdav_result(
+                self.client
+                    .request(Method::from_str("MKCOL").unwrap(), &path[..depth])
+                    .send(),
+            )?;
+
+            // `405 Method Not Allowed` signals that the collection already exists
+            let status = res.status();
+            if !status.is_success() && status != StatusCode::METHOD_NOT_ALLOWED {
+                return Err(Error::upload_error(format!(
+                    "Could not create collection {}: {}",
+                    path[..depth].join("/"),
+                    status_reason(&status)
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Return the path segments of `path` inside the remote directory
+    fn remote_path(&self, path: &str) -> Vec<String> {
+        self.remote_directory
+            .split('/')
+            .chain(path.split('/'))
+            .filter(|s| !s.is_empty())
+            .map(ToOwned::to_owned)
+            .collect()
     }
 
     fn fetch_file(&self, path: String) -> Result<Response> {
@@ -96,10 +222,7 @@ impl WebDAVService {
 
     fn check_status_code(&self, status: &StatusCode) -> Result<()> {
         if !status.is_success() {
-            Err(Error::download_error(match status.canonical_reason() {
-                Some(reason) => reason.to_string(),
-                None => status.to_string(),
-            }))
+            Err(Error::download_error(status_reason(status)))
         } else {
             Ok(())
         }
@@ -165,7 +288,7 @@ impl ServiceTrait for WebDAVService {
     }
 
     fn list_files(&self) -> Result<Vec<FileEntry>, Error> {
-        self.list(self.remote_directory.split('/').collect::<Vec<&str>>())
+        self.list_recursive()
     }
 
     fn download(&self, file: FileEntry, destination: &Path) -> Result<()> {
@@ -174,6 +297,43 @@ impl ServiceTrait for WebDAVService {
         prepare_hyperdav_result(res.copy_to(&mut file_handle))?;
         Ok(())
     }
+
+    fn upload(&self, source: &Path, destination: &str) -> Result<FileEntry> {
+        let path = self.remote_path(destination);
+        if let Some((_, parent)) = path.split_last() {
+            self.create_collection(parent)?;
+        }
+
+        let body = fs::read(source)?;
+        let res =
+            prepare_hyperdav_result(self.client.request(Method::PUT, &path).body(body).send())?;
+        if !res.status().is_success() {
+            return Err(Error::upload_error(status_reason(&res.status())));
+        }
+
+        // Fetch the entry to know the modification date the server assigned
+        self.list(&path)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::upload_error(format!("Uploaded file {} not found", destination)))
+    }
+
+    fn delete(&self, file: FileEntry) -> Result<()> {
+        let clean_path = self.remove_overlapping_path_segments(file.path().to_owned());
+        let res = prepare_hyperdav_result(self.client.request(Method::DELETE, clean_path).send())?;
+        if !res.status().is_success() {
+            return Err(Error::delete_error(status_reason(&res.status())));
+        }
+
+        Ok(())
+    }
+}
+
+fn status_reason(status: &StatusCode) -> String {
+    match status.canonical_reason() {
+        Some(reason) => reason.to_string(),
+        None => status.to_string(),
+    }
 }
 
 fn prepare_hyperdav_result<O, E: std::error::Error>(result: Result<O, E>) -> Result<O, Error> {
@@ -182,3 +342,80 @@ fn prepare_hyperdav_result<O, E: std::error::Error>(result: Result<O, E>) -> Res
         Err(e) => Err(Error::download_error(e.to_string())),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_path() {
+        let service = WebDAVService::new(WebDAVServiceConfiguration {
+            url: "https://mycloud.example.com/remote.php/webdav".into(),
+            remote_directory: "Lyrics".into(),
+            username: "this-is-me".into(),
+            password: "123-easy".into(),
+            local_directory: PathBuf::from("/tmp"),
+        })
+        .unwrap();
+
+        assert_eq!(
+            service.relative_path("/remote.php/webdav/Lyrics/swing-low.chorddown"),
+            Some("swing-low.chorddown".to_owned())
+        );
+        assert_eq!(
+            service.relative_path("/remote.php/webdav/Lyrics/de/stille-nacht.chorddown"),
+            Some("de/stille-nacht.chorddown".to_owned())
+        );
+        assert_eq!(
+            service.relative_path(
+                "https://mycloud.example.com/remote.php/webdav/Lyrics/de/stille-nacht.chorddown"
+            ),
+            Some("de/stille-nacht.chorddown".to_owned())
+        );
+        assert_eq!(service.relative_path("/remote.php/webdav/Lyrics/"), None);
+        assert_eq!(service.relative_path("/other/swing-low.chorddown"), None);
+    }
+
+    #[test]
+    fn test_file_entry_outside_of_remote_directory() {
+        let service = WebDAVService::new(WebDAVServiceConfiguration {
+            url: "https://mycloud.example.com/dav".into(),
+            remote_directory: "songs".into(),
+            username: "this-is-me".into(),
+            password: "123-easy".into(),
+            local_directory: PathBuf::from("/tmp"),
+        })
+        .unwrap();
+        let response = r#"<?xml version="1.0"?>
+<d:multistatus xmlns:d="DAV:">
+    <d:response>
+        <d:href>/dav/songs/%2E%2E/evil.chorddown</d:href>
+        <d:propstat>
+            <d:prop>
+                <d:getlastmodified>Sun, 02 Feb 2020 19:04:49 GMT</d:getlastmodified>
+                <d:resourcetype/>
+            </d:prop>
+            <d:status>HTTP/1.1 200 OK</d:status>
+        </d:propstat>
+    </d:response>
+    <d:response>
+        <d:href>/dav/songs/swing-low.chorddown</d:href>
+        <d:propstat>
+            <d:prop>
+                <d:getlastmodified>Sun, 02 Feb 2020 19:04:49 GMT</d:getlastmodified>
+                <d:resourcetype/>
+            </d:prop>
+            <d:status>HTTP/1.1 200 OK</d:status>
+        </d:propstat>
+    </d:response>
+</d:multistatus>"#;
+
+        let entries: Vec<FileEntry> = parse_propfind_response(response.as_bytes())
+            .unwrap()
+            .into_iter()
+            .filter_map(|f| service.file_entry(f))
+            .collect();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].relative_path(), "swing-low.chorddown");
+    }
+}
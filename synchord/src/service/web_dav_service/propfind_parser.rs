@@ -1,6 +1,7 @@
 use std::io::prelude::*;
 
 use chrono::{DateTime, FixedOffset};
+use percent_encoding::percent_decode_str;
 use xml::reader::{EventReader, XmlEvent};
 use xml::ParserConfig;
 
@@ -13,6 +14,7 @@ pub fn parse_propfind_response<R: Read>(read: R) -> Result<Vec<TempFileEntry>> {
         Href,
         LastModified,
         Size,
+        ETag,
         ResourceType,
         Ignored,
     }
@@ -84,6 +86,10 @@ pub fn parse_propfind_response<R: Read>(read: R) -> Result<Vec<TempFileEntry>> {
                         field: Some(Field::Size),
                         item,
                     },
+                    "getetag" => State::Item {
+                        field: Some(Field::ETag),
+                        item,
+                    },
                     "resourcetype" => State::Item {
                         field: Some(Field::ResourceType),
                         item,
@@ -110,6 +116,7 @@ pub fn parse_propfind_response<R: Read>(read: R) -> Result<Vec<TempFileEntry>> {
                     match field {
                         Field::Href => TempFileEntry::set_path(&mut item, s),
                         Field::Size => TempFileEntry::set_size(&mut item, s),
+                        Field::ETag => TempFileEntry::set_etag(&mut item, s),
                         Field::LastModified => TempFileEntry::set_modified_date(&mut item, s)?,
                         Field::ResourceType => unreachable!(),
                         Field::Ignored => {}
@@ -144,6 +151,7 @@ pub struct TempFileEntry {
     path: String,
     size: usize,
     modified_date: DateTime<FixedOffset>,
+    etag: Option<String>,
 }
 
 impl TempFileEntry {
@@ -151,8 +159,13 @@ impl TempFileEntry {
         self.is_directory
     }
 
+    pub fn path(&self) -> &str {
+        self.path.as_str()
+    }
+
     fn set_path(&mut self, path: String) {
-        self.path = path
+        // The `href` is percent-encoded, but the path is encoded again when building requests
+        self.path = percent_decode_str(&path).decode_utf8_lossy().into_owned()
     }
 
     fn set_size(&mut self, s: String) {
@@ -162,6 +175,10 @@ impl TempFileEntry {
         // otherwise do nothing - keep `self.size` as it is
     }
 
+    fn set_etag(&mut self, etag: String) {
+        self.etag = Some(etag)
+    }
+
     fn set_modified_date<S: AsRef<str>>(&mut self, date: S) -> Result<()> {
         let parsed_date = DateTime::parse_from_str(date.as_ref(), "%a, %d %b %Y %H:%M:%S GMT")?;
         self.modified_date = parsed_date;
@@ -182,13 +199,18 @@ impl Default for TempFileEntry {
             path: String::new(),
             size: 0,
             modified_date: date,
+            etag: None,
         }
     }
 }
 
 impl From<TempFileEntry> for FileEntry {
     fn from(file: TempFileEntry) -> Self {
-        FileEntry::new(file.path, file.size, file.modified_date)
+        let entry = FileEntry::new(file.path, file.size, file.modified_date);
+        match file.etag {
+            Some(etag) => entry.with_revision(etag),
+            None => entry,
+        }
     }
 }
 
@@ -228,5 +250,8 @@ mod tests {
         );
         assert_eq!(files[2].is_directory, false);
         assert_eq!(files[2].size, 18);
+
+        let entry = FileEntry::from(files[2].clone());
+        assert_eq!(entry.revision(), "\"c66d963f10fe1af45bf74598780d504\"");
     }
 }
@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// Name of the file inside the local directory the synchronization state is stored in
+pub const SYNC_STATE_FILE_NAME: &str = ".synchord-state.json";
+
+/// State of a file after it has been synchronized
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SyncStateEntry {
+    /// Revision of the remote file (see [`FileEntry::revision()`])
+    ///
+    /// [`FileEntry::revision()`]: crate::service::FileEntry::revision
+    pub remote_revision: String,
+
+    /// SHA-256 hash of the local file's content
+    pub local_hash: String,
+}
+
+/// Persisted state of the last synchronization (the sync manifest)
+///
+/// The state allows to detect on which side a file changed since the last run, independent of
+/// the clocks of the local machine and the service. Local changes are uploaded (or kept) instead
+/// of being overwritten, and files changed on both sides are reported as conflicts
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SyncState {
+    files: BTreeMap<String, SyncStateEntry>,
+}
+
+impl SyncState {
+    /// Load the state stored in `local_directory`
+    ///
+    /// Returns an empty state if the directory has never been synchronized
+    pub fn load(local_directory: &Path) -> Result<Self> {
+        let path = Self::path(local_directory);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)?;
+        serde_json::from_str(&content).map_err(|e| {
+            Error::sync_state_error(format!("Could not read {}: {}", path.display(), e))
+        })
+    }
+
+    /// Store the state in `local_directory`
+    ///
+    /// An empty state is not written, an existing state file is removed instead
+    pub fn save(&self, local_directory: &Path) -> Result<()> {
+        let path = Self::path(local_directory);
+        if self.is_empty() {
+            if path.exists() {
+                fs::remove_file(&path)?;
+            }
+            return Ok(());
+        }
+
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::sync_state_error(e.to_string()))?;
+
+        // Write to a temporary file first, so that an interrupted run does not leave a
+        // truncated state behind
+        let temporary_path = path.with_extension("json.tmp");
+        fs::write(&temporary_path, content)?;
+        fs::rename(&temporary_path, &path)?;
+
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SyncStateEntry> {
+        self.files.get(name)
+    }
+
+    pub fn insert<S: Into<String>>(&mut self, name: S, entry: SyncStateEntry) {
+        self.files.insert(name.into(), entry);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<SyncStateEntry> {
+        self.files.remove(name)
+    }
+
+    /// Return the number of synchronized files
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Return the names of all synchronized files
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(String::as_str)
+    }
+
+    fn path(local_directory: &Path) -> PathBuf {
+        local_directory.join(SYNC_STATE_FILE_NAME)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_and_load() {
+        let directory =
+            std::env::temp_dir().join(format!("synchord-test-sync-state-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+
+        assert_eq!(SyncState::load(&directory).unwrap(), SyncState::default());
+
+        let mut state = SyncState::default();
+        state.insert(
+            "swing-low.chorddown",
+            SyncStateEntry {
+                remote_revision: "5e173d511a4c8".to_owned(),
+                local_hash: "0b8ee3d6c3f7b35c".to_owned(),
+            },
+        );
+        state.save(&directory).unwrap();
+
+        let loaded = SyncState::load(&directory).unwrap();
+        assert_eq!(loaded, state);
+        assert_eq!(
+            loaded.names().collect::<Vec<_>>(),
+            vec!["swing-low.chorddown"]
+        );
+
+        SyncState::default().save(&directory).unwrap();
+        assert!(!directory.join(SYNC_STATE_FILE_NAME).exists());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -25,10 +25,15 @@ impl Component for ReloadSection {
     fn view(&self, ctx: &Context<Self>) -> Html {
         let revision = match &ctx.props().catalog {
             None => "n/a".to_string(),
-            Some(c) => match DateTime::parse_from_rfc2822(&c.revision()) {
-                Ok(d) => d.format("%a %d.%m.%y %H:%M").to_string(),
-                Err(_) => c.revision(),
-            },
+            // Catalogs built before the revision was derived from the content use the build
+            // time as revision
+            Some(c) => {
+                let built_at = c.built_at().map_or_else(|| c.revision(), str::to_owned);
+                match DateTime::parse_from_rfc2822(&built_at) {
+                    Ok(d) => d.format("%a %d.%m.%y %H:%M").to_string(),
+                    Err(_) => c.revision(),
+                }
+            }
         };
 
         let app_version = format!(
@@ -33,7 +33,7 @@ pub struct SongSearchProps {
 }
 
 impl SongSearch {
-    /// Return the [Song]s from the [Catalog] filtered by [self.search]
+    /// Return the [Song]s from the [Catalog] filtered by [self.search] ordered by their relevance
     fn get_filtered_songs<'a>(&'a self, props: &'a SongSearchProps) -> Vec<&'a Song> {
         if self.search.is_empty() || self.search_index.is_none() {
             self.get_all_songs(props)
@@ -42,7 +42,6 @@ impl SongSearch {
                 .as_ref()
                 .expect("Search index not built yet")
                 .search_by_term(&self.search)
-                .sort_by_title()
         }
     }
 
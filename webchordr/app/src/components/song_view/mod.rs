@@ -51,6 +51,7 @@ pub enum Msg {
     TransposeSet(isize),
     SetlistChange(bool),
     SemitoneNotationChange(SemitoneNotation),
+    ChordNotationChange(ChordNotation),
 }
 
 pub struct SongView {}
@@ -73,6 +74,7 @@ impl Component for SongView {
             }
             Msg::TransposeSet(v) => self.change_transpose(ctx, v),
             Msg::SemitoneNotationChange(s) => self.change_semitone_notation(ctx, s),
+            Msg::ChordNotationChange(n) => self.change_chord_notation(ctx, n),
             Msg::SetlistChange(flag) => {
                 let song = &ctx.props().song_info.song;
                 info!("Set Song {} on setlist: {:?}", song.id(), flag);
@@ -98,7 +100,18 @@ impl Component for SongView {
         );
 
         let semitone_notation = ctx.props().song_info.song_settings.semitone_notation();
+        let chord_notation = ctx
+            .props()
+            .song_info
+            .song_settings
+            .formatting()
+            .chord_notation;
         let transpose_semitone = ctx.props().song_info.song_settings.transpose_semitone();
+        // Fall back to the key detected from the chords if the song does not define one
+        let song_meta = ctx.props().song_info.song.meta();
+        let song_key = song_meta
+            .key()
+            .or_else(|| song_meta.detected_key().map(|guess| guess.key.clone()));
 
         let detail = self.convert_song_to_html_node(ctx);
         let transpose_up = ctx.link().callback(|_| Msg::TransposeUp);
@@ -106,6 +119,7 @@ impl Component for SongView {
         let transpose_set = ctx.link().callback(Msg::TransposeSet);
         let setlist_change = ctx.link().callback(Msg::SetlistChange);
         let semitone_notation_set = ctx.link().callback(Msg::SemitoneNotationChange);
+        let chord_notation_set = ctx.link().callback(Msg::ChordNotationChange);
 
         let setlist_tool = if ctx.props().enable_setlists {
             html! {
@@ -128,11 +142,14 @@ impl Component for SongView {
                         on_click_up={transpose_up}
                         on_click_down={transpose_down}
                         on_set={transpose_set}
+                        song_key={song_key}
                     />
                     {setlist_tool}
                     <SemitoneNotationTool
                         semitone_notation={semitone_notation}
                         on_change={semitone_notation_set}
+                        chord_notation={chord_notation}
+                        on_chord_notation_change={chord_notation_set}
                     />
                 </div>
             </div>
@@ -158,6 +175,7 @@ impl SongView {
         let converter_result = if transpose_semitone != 0 {
             transpose_and_convert_to_format(
                 props.song_info.song.src().as_bytes(),
+                props.song_info.song.file_type(),
                 transpose_semitone,
                 props.song_info.song.meta(),
                 formatting,
@@ -165,6 +183,7 @@ impl SongView {
         } else {
             convert_to_format(
                 props.song_info.song.src().as_bytes(),
+                props.song_info.song.file_type(),
                 props.song_info.song.meta(),
                 formatting,
             )
@@ -215,8 +234,26 @@ impl SongView {
     }
 
     fn change_semitone_notation(&mut self, ctx: &Context<Self>, s: SemitoneNotation) {
+        // An explicitly selected notation overrides the one of the key signature
         let formatting = Formatting {
             semitone_notation: s,
+            semitone_notation_for_key: false,
+            ..ctx.props().song_info.song_settings.formatting()
+        };
+
+        info!("Change formatting to {:?}", formatting);
+        self.send_change(
+            ctx,
+            ctx.props()
+                .song_info
+                .song_settings
+                .with_formatting(formatting),
+        );
+    }
+
+    fn change_chord_notation(&mut self, ctx: &Context<Self>, chord_notation: ChordNotation) {
+        let formatting = Formatting {
+            chord_notation,
             ..ctx.props().song_info.song_settings.formatting()
         };
 
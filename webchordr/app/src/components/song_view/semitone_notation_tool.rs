@@ -1,10 +1,14 @@
-use libchordr::models::meta::SemitoneNotation;
+use libchordr::models::meta::{ChordNotation, SemitoneNotation};
 use yew::prelude::*;
 
 #[derive(Properties, PartialEq, Clone)]
 pub struct SemitoneNotationToolProps {
     pub semitone_notation: SemitoneNotation,
     pub on_change: Callback<SemitoneNotation>,
+    #[prop_or_default]
+    pub chord_notation: ChordNotation,
+    #[prop_or_default]
+    pub on_chord_notation_change: Callback<ChordNotation>,
 }
 
 pub struct SemitoneNotationTool {}
@@ -29,12 +33,35 @@ impl Component for SemitoneNotationTool {
         let select_sharp = ctx.props().on_change.reform(|_| SemitoneNotation::Sharp);
         let select_flat = ctx.props().on_change.reform(|_| SemitoneNotation::Flat);
 
+        let chord_notation_buttons = [
+            ChordNotation::Letter,
+            ChordNotation::Nashville,
+            ChordNotation::Roman,
+        ]
+        .into_iter()
+        .map(|chord_notation| {
+            let mut class = vec!["discreet"];
+            if ctx.props().chord_notation == chord_notation {
+                class.push("-active");
+            }
+            let onclick = ctx
+                .props()
+                .on_chord_notation_change
+                .reform(move |_| chord_notation);
+
+            html! { <button class={class} onclick={onclick}>{chord_notation}</button> }
+        })
+        .collect::<Html>();
+
         html! {
             <div class="semitone-notation-tool">
                 <div title="Select the semitone notation">
                     <button class={sharp_class} onclick={select_sharp}>{SemitoneNotation::Sharp}</button>
                     <button class={flat_class} onclick={select_flat}>{SemitoneNotation::Flat}</button>
                 </div>
+                <div title="Select the chord notation">
+                    {chord_notation_buttons}
+                </div>
             </div>
         }
     }
@@ -1,6 +1,9 @@
+use libchordr::models::chord::{Chord, Note, NoteDisplay};
+use libchordr::modification::transposition::{semitones_between, TransposableTrait};
+use libchordr::prelude::{Formatting, SemitoneNotation};
 use log::error;
 use log::info;
-use web_sys::HtmlInputElement;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
 use yew::prelude::*;
 
 #[derive(Properties, PartialEq, Clone)]
@@ -11,10 +14,15 @@ pub struct TransposeToolProps {
     pub on_click_up: Callback<()>,
     pub on_click_down: Callback<()>,
     pub on_set: Callback<isize>,
+
+    /// Key of the song (defined or detected from the chords) which allows to select a target key
+    #[prop_or_default]
+    pub song_key: Option<Chord>,
 }
 
 pub enum Msg {
     InputChange(String),
+    KeyChange(String),
 }
 
 pub struct TransposeTool {}
@@ -41,6 +49,15 @@ impl Component for TransposeTool {
                     }
                 };
             }
+            Msg::KeyChange(v) => match (&ctx.props().song_key, v.parse::<isize>()) {
+                (Some(key), Ok(v)) => {
+                    info!("Transpose to key {:?}", v);
+                    ctx.props()
+                        .on_set
+                        .emit(semitones_between(key.root(), Note::from(v)));
+                }
+                _ => error!("Invalid key change data {:?}", v),
+            },
         };
 
         true
@@ -64,6 +81,8 @@ impl Component for TransposeTool {
             html! {<span class="value">{transpose_semitone}</span>}
         };
 
+        let key_select = self.render_key_select(ctx);
+
         let disable_down = transpose_semitone < -11;
         let disable_up = transpose_semitone > 11;
 
@@ -74,6 +93,7 @@ impl Component for TransposeTool {
                 {number_output}
                 <button class="discreet" disabled={disable_up} onclick={transpose_up}><i class="im im-angle-right"></i></button>
                 <span class="sr-only">{"Transpose song"}</span>
+                {key_select}
             </>
         };
 
@@ -96,3 +116,42 @@ impl Component for TransposeTool {
         }) as Html
     }
 }
+
+impl TransposeTool {
+    /// Render a select box to transpose the song into another key
+    ///
+    /// Each key is spelled according to its key signature (e.g. `Bb` instead of `A#`)
+    fn render_key_select(&self, ctx: &Context<Self>) -> Html {
+        let key = match &ctx.props().song_key {
+            Some(key) => key,
+            None => return html! {},
+        };
+        let current_root = key.root().transpose(ctx.props().transpose_semitone);
+        let options = (1..=12)
+            .map(|i| {
+                let root = Note::from(i as isize);
+                let target = key.clone().with_root(root);
+                let formatting = Formatting {
+                    semitone_notation: SemitoneNotation::for_key(&target),
+                    ..Formatting::default()
+                };
+                html! {
+                    <option value={i.to_string()} selected={root == current_root}>
+                        {target.note_format(formatting)}
+                    </option>
+                }
+            })
+            .collect::<Html>();
+        let onchange = ctx.link().batch_callback(|e: Event| {
+            let select = e.target_dyn_into::<HtmlSelectElement>();
+
+            select.map(|select| Msg::KeyChange(select.value()))
+        });
+
+        html! {
+            <select class="transpose-tool-key" title="Transpose to key" onchange={onchange}>
+                {options}
+            </select>
+        }
+    }
+}
@@ -2,9 +2,20 @@ use self::bs::BrowserStorageBackend;
 use crate::browser_storage::BrowserStorage;
 use crate::fetch_helper::fetch;
 use crate::WebError;
-use libchordr::prelude::Catalog;
+use libchordr::prelude::{Catalog, CatalogDiff, CatalogTrait};
+use serde::Deserialize;
 use webchordr_common::tri::Tri;
 
+/// Response of the server to a request for the changes since a Catalog revision
+///
+/// If the server does not know the revision anymore the whole Catalog is sent
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CatalogUpdate {
+    Diff(CatalogDiff),
+    Full(Catalog),
+}
+
 pub struct CatalogWebRepository {
     backend: BrowserStorageBackend,
 }
@@ -16,22 +27,38 @@ impl CatalogWebRepository {
         }
     }
 
-    async fn fetch_catalog(&self, append_timestamp: bool) -> Tri<Catalog, WebError> {
-        let base_uri = "/catalog.json";
-        let uri = if append_timestamp {
-            format!("{}?{}", base_uri, chrono::Local::now().timestamp())
-        } else {
-            base_uri.to_string()
-        };
-
-        match fetch::<Catalog>(&uri).await {
+    async fn fetch_catalog(&self) -> Tri<Catalog, WebError> {
+        // The server sends the Catalog with an `ETag` and `Cache-Control: no-cache`, so the
+        // browser revalidates its cached copy instead of downloading an unchanged Catalog again
+        match fetch::<Catalog>("/catalog.json").await {
             Ok(catalog) => Tri::Some(catalog),
             Err(error) => Tri::Err(error),
         }
     }
 
+    /// Fetch only the changes since the revision of the stored Catalog and apply them
+    async fn fetch_catalog_update(&self, stored: &Catalog) -> Tri<Catalog, WebError> {
+        let since = String::from(js_sys::encode_uri_component(&stored.revision()));
+        let uri = format!("/catalog.json?since={}", since);
+        match fetch::<CatalogUpdate>(&uri).await {
+            Ok(CatalogUpdate::Full(catalog)) => Tri::Some(catalog),
+            Ok(CatalogUpdate::Diff(diff)) => match diff.apply(stored) {
+                Ok(catalog) => Tri::Some(catalog),
+                Err(e) => {
+                    log::warn!("{}", e);
+                    self.fetch_catalog().await
+                }
+            },
+            Err(error) => Tri::Err(error),
+        }
+    }
+
     pub async fn load(&mut self) -> Tri<Catalog, WebError> {
-        match self.fetch_catalog(true).await {
+        let fetch_result = match self.backend.load() {
+            Tri::Some(stored) => self.fetch_catalog_update(&stored).await,
+            _ => self.fetch_catalog().await,
+        };
+        match fetch_result {
             Tri::Some(c) => {
                 // Store/cache the loaded Catalog
                 let _ = self.backend.store(&c);